@@ -6,6 +6,13 @@ pub enum Error {
     InvalidProofBlockHash,
     NotEnoughSignatures,
     VerifyMptProof,
+    /// The bitmap is not `ceil(validator_count / 8)` bytes long.
+    BitmapLengthMismatch {
+        expect: usize,
+        actual: usize,
+    },
+    /// The bitmap sets a bit past the last validator.
+    BitmapTrailingBits,
 
     #[cfg(feature = "proof")]
     Bls(blst::BLST_ERROR),
@@ -24,6 +31,10 @@ impl ToString for Error {
             Error::InvalidProofBlockHash => "Invalid proof block hash".to_string(),
             Error::NotEnoughSignatures => "Not enough signatures".to_string(),
             Error::VerifyMptProof => "Verify mpt proof".to_string(),
+            Error::BitmapLengthMismatch { expect, actual } => {
+                alloc::format!("Bitmap length {}, expect {}", actual, expect)
+            }
+            Error::BitmapTrailingBits => "Bitmap sets bits past the last validator".to_string(),
             #[cfg(feature = "proof")]
             Error::Bls(e) => alloc::format!("Bls error: {:?}", e),
         }
@@ -9,3 +9,46 @@ pub fn keccak_256(data: &[u8]) -> [u8; 32] {
     hasher.finalize(&mut ret);
     ret
 }
+
+/// Hash the concatenation of `parts` without building it, e.g. a domain
+/// prefix followed by a payload.
+#[cfg(feature = "hash")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "hash")))]
+pub fn keccak_256_concat(parts: &[&[u8]]) -> [u8; 32] {
+    keccak_256_iter(parts)
+}
+
+/// Same as [`keccak_256_concat`] for any sequence of byte slices.
+#[cfg(feature = "hash")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "hash")))]
+pub fn keccak_256_iter<I>(parts: I) -> [u8; 32]
+where
+    I: IntoIterator,
+    I::Item: AsRef<[u8]>,
+{
+    let mut ret = [0u8; 32];
+    let mut hasher = Keccak::v256();
+    for part in parts {
+        hasher.update(part.as_ref());
+    }
+    hasher.finalize(&mut ret);
+    ret
+}
+
+/// Hash every item in `items`, returning the digests in input order. The
+/// initial sponge state is set up once and cloned for each item.
+#[cfg(feature = "hash")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "hash")))]
+pub fn keccak_256_batch<T: AsRef<[u8]>>(items: &[T]) -> alloc::vec::Vec<[u8; 32]> {
+    let init = Keccak::v256();
+    items
+        .iter()
+        .map(|item| {
+            let mut ret = [0u8; 32];
+            let mut hasher = init.clone();
+            hasher.update(item.as_ref());
+            hasher.finalize(&mut ret);
+            ret
+        })
+        .collect()
+}
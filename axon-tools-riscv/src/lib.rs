@@ -18,6 +18,6 @@ pub use proof::verify_proof;
 
 #[cfg(feature = "hash")]
 #[cfg_attr(doc_cfg, doc(cfg(feature = "hash")))]
-pub use hash::keccak_256;
+pub use hash::{keccak_256, keccak_256_batch, keccak_256_concat, keccak_256_iter};
 
 pub mod consts;
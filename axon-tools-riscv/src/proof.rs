@@ -95,6 +95,7 @@ pub fn verify_proof(
 }
 
 fn extract_pks(proof: &Proof, validator_list: &mut [Validator]) -> Result<Vec<PublicKey>, Error> {
+    check_bitmap(&proof.bitmap, validator_list.len())?;
     validator_list.sort();
 
     let bit_map = BitVec::from_bytes(&proof.bitmap);
@@ -116,3 +117,44 @@ fn extract_pks(proof: &Proof, validator_list: &mut [Validator]) -> Result<Vec<Pu
 
     Ok(pks)
 }
+
+/// Check that `bitmap` has exactly one bit per validator, rounded up to whole
+/// bytes, and that the padding bits are zero.
+fn check_bitmap(bitmap: &[u8], validator_count: usize) -> Result<(), Error> {
+    let expect = validator_count.div_ceil(8);
+    if bitmap.len() != expect {
+        return Err(Error::BitmapLengthMismatch {
+            expect,
+            actual: bitmap.len(),
+        });
+    }
+
+    let used_bits = validator_count % 8;
+    match bitmap.last() {
+        Some(last) if used_bits != 0 && last & (0xff >> used_bits) != 0 => {
+            Err(Error::BitmapTrailingBits)
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_bitmap() {
+        assert!(check_bitmap(&[0b1110_0000], 3).is_ok());
+        assert!(check_bitmap(&[0xff, 0b1000_0000], 9).is_ok());
+        assert!(check_bitmap(&[], 0).is_ok());
+
+        assert!(matches!(
+            check_bitmap(&[0xff, 0], 8),
+            Err(Error::BitmapLengthMismatch { expect: 1, actual: 2 })
+        ));
+        assert!(matches!(
+            check_bitmap(&[0b1111_0000], 3),
+            Err(Error::BitmapTrailingBits)
+        ));
+    }
+}
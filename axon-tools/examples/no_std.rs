@@ -0,0 +1,64 @@
+//! Proof verification from a `#![no_std]` crate, such as a light client
+//! running in a contract, to keep the `std` feature optional. Check it with
+//!
+//! ```text
+//! cargo check -p axon-tools --example no_std --no-default-features --features proof
+//! ```
+//!
+//! and with `--features heapless` for the verifier that does not allocate.
+
+#![no_std]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+use axon_tools::types::{AxonBlock, Metadata, Proof, H160, H256};
+use axon_tools::validator_set::ValidatorSet;
+use axon_tools::Error;
+use rlp::DecoderError;
+
+#[derive(Debug)]
+pub enum VerifyError {
+    Decode(DecoderError),
+    Verify(Error),
+}
+
+impl From<DecoderError> for VerifyError {
+    fn from(e: DecoderError) -> Self {
+        VerifyError::Decode(e)
+    }
+}
+
+impl From<Error> for VerifyError {
+    fn from(e: Error) -> Self {
+        VerifyError::Verify(e)
+    }
+}
+
+/// Verify the RLP encoded `proof` of the RLP encoded `block` against the
+/// validators of `metadata`, returning the addresses of the signers.
+pub fn verify(
+    block: &[u8],
+    proof: &[u8],
+    previous_state_root: H256,
+    metadata: &Metadata,
+) -> Result<Vec<H160>, VerifyError> {
+    let block: AxonBlock = rlp::decode(block)?;
+    let proof: Proof = rlp::decode(proof)?;
+    let set = ValidatorSet::from_metadata(metadata)?;
+    let report = set.verify_proof(&block, previous_state_root, &proof)?;
+    Ok(report.signers)
+}
+
+/// Same as [`verify`] with nothing allocated, for a set of at most 32
+/// validators, given the RLP of the block's proposal.
+#[cfg(feature = "heapless")]
+pub fn verify_fixed(
+    proposal: &[u8],
+    proof: &axon_tools::fixed::FixedProof<4>,
+    validators: &heapless::Vec<axon_tools::fixed::FixedValidator, 32>,
+) -> Result<(), VerifyError> {
+    axon_tools::fixed::verify_proof_fixed(proposal, validators, proof)?;
+    Ok(())
+}
@@ -0,0 +1,142 @@
+//! The registry of assets that can cross between CKB and Axon. Each entry maps
+//! a CKB sUDT or xUDT, identified by its type script hash, to the ERC-20
+//! contract that represents it on Axon. Bridge fees are left to the bridge
+//! contract, whose fee rules this crate does not model.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use ethereum_types::{H160, H256};
+
+use crate::types::TypesError;
+
+/// The most decimals an asset can have for one whole token, `10^decimals`
+/// base units, to fit in the `U256` amounts of an ERC-20.
+pub const MAX_DECIMALS: u8 = 77;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "impl-serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "impl-serde", serde(rename_all = "lowercase"))]
+pub enum CkbAssetKind {
+    Sudt,
+    Xudt,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "impl-serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "impl-serde", serde(rename_all = "camelCase"))]
+pub struct AssetInfo {
+    pub symbol:        String,
+    pub kind:          CkbAssetKind,
+    pub ckb_type_hash: H256,
+    pub axon_address:  H160,
+    pub decimals:      u8,
+}
+
+impl AssetInfo {
+    pub fn validate(&self) -> Result<(), TypesError> {
+        let reason = if self.symbol.is_empty() {
+            "empty symbol"
+        } else if self.ckb_type_hash.is_zero() {
+            "zero CKB type hash"
+        } else if self.axon_address.is_zero() {
+            "zero Axon address"
+        } else if self.decimals > MAX_DECIMALS {
+            "more than 77 decimals"
+        } else {
+            return Ok(());
+        };
+
+        Err(TypesError::InvalidAsset {
+            symbol: self.symbol.clone(),
+            reason,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "impl-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AssetRegistry {
+    pub assets: Vec<AssetInfo>,
+}
+
+impl AssetRegistry {
+    /// Validate every asset and check that no CKB asset or Axon contract is
+    /// registered twice.
+    pub fn validate(&self) -> Result<(), TypesError> {
+        for (i, asset) in self.assets.iter().enumerate() {
+            asset.validate()?;
+
+            let duplicate = self.assets[..i].iter().any(|a| {
+                a.ckb_type_hash == asset.ckb_type_hash || a.axon_address == asset.axon_address
+            });
+            if duplicate {
+                return Err(TypesError::InvalidAsset {
+                    symbol: asset.symbol.clone(),
+                    reason: "registered twice",
+                });
+            }
+        }
+        Ok(())
+    }
+
+    pub fn by_ckb_type_hash(&self, type_hash: &H256) -> Option<&AssetInfo> {
+        self.assets.iter().find(|a| a.ckb_type_hash == *type_hash)
+    }
+
+    pub fn by_axon_address(&self, address: &H160) -> Option<&AssetInfo> {
+        self.assets.iter().find(|a| a.axon_address == *address)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn asset(symbol: &str) -> AssetInfo {
+        AssetInfo {
+            symbol:        symbol.into(),
+            kind:          CkbAssetKind::Sudt,
+            ckb_type_hash: H256(rand::random()),
+            axon_address:  H160(rand::random()),
+            decimals:      8,
+        }
+    }
+
+    #[test]
+    fn test_registry_validate() {
+        let mut registry = AssetRegistry {
+            assets: vec![asset("USDC"), asset("ETH")],
+        };
+        assert!(registry.validate().is_ok());
+        let address = registry.assets[1].axon_address;
+        assert_eq!(registry.by_axon_address(&address).unwrap().symbol, "ETH");
+
+        registry.assets[1].ckb_type_hash = registry.assets[0].ckb_type_hash;
+        assert!(registry.validate().is_err());
+
+        let mut btc = asset("BTC");
+        btc.decimals = MAX_DECIMALS;
+        assert!(btc.validate().is_ok());
+        btc.decimals = MAX_DECIMALS + 1;
+        assert!(matches!(
+            btc.validate(),
+            Err(TypesError::InvalidAsset {
+                reason: "more than 77 decimals",
+                ..
+            })
+        ));
+    }
+
+    #[cfg(feature = "impl-serde")]
+    #[test]
+    fn test_registry_serde() {
+        let registry = AssetRegistry {
+            assets: vec![asset("USDC")],
+        };
+        let json = serde_json::to_string(&registry).unwrap();
+        assert!(json.contains(r#""kind":"sudt""#));
+        assert!(json.contains(r#""ckbTypeHash":"0x"#));
+        assert_eq!(serde_json::from_str::<AssetRegistry>(&json).unwrap(), registry);
+    }
+}
@@ -0,0 +1,86 @@
+//! Canonical JSON for signing or content-addressing the serde form of the
+//! types in this crate: object keys sorted by their UTF-8 bytes, no
+//! whitespace, strings escaped as `serde_json` does and numbers in their
+//! shortest round-trip form. Two values with the same serde form thus always
+//! produce the same bytes, whatever the field order of the source or the
+//! features `serde_json` was built with.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use serde::Serialize;
+use serde_json::Value;
+
+/// Serialize `value` as canonical JSON.
+pub fn to_canonical_json<T: Serialize + ?Sized>(value: &T) -> serde_json::Result<String> {
+    let value = serde_json::to_value(value)?;
+    let mut out = String::new();
+    write_value(&mut out, &value)?;
+    Ok(out)
+}
+
+/// Re-encode a JSON document canonically.
+pub fn canonicalize(json: &str) -> serde_json::Result<String> {
+    let value: Value = serde_json::from_str(json)?;
+    to_canonical_json(&value)
+}
+
+fn write_value(out: &mut String, value: &Value) -> serde_json::Result<()> {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Number(n) => out.push_str(&n.to_string()),
+        Value::String(s) => out.push_str(&serde_json::to_string(s)?),
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_value(out, item)?;
+            }
+            out.push(']');
+        }
+        Value::Object(map) => {
+            let mut entries = map.iter().collect::<Vec<_>>();
+            entries.sort_unstable_by(|a, b| a.0.as_bytes().cmp(b.0.as_bytes()));
+            out.push('{');
+            for (i, (key, item)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&serde_json::to_string(key)?);
+                out.push(':');
+                write_value(out, item)?;
+            }
+            out.push('}');
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::tests::mock_header;
+    use crate::types::AxonHeader;
+
+    #[test]
+    fn test_canonicalize() {
+        let a = canonicalize(r#"{ "b": [1, {"y": true, "x": null}], "a": "é\n" }"#).unwrap();
+        assert_eq!(a, r#"{"a":"é\n","b":[1,{"x":null,"y":true}]}"#);
+        assert_eq!(canonicalize(&a).unwrap(), a);
+    }
+
+    #[test]
+    fn test_canonical_header() {
+        let header = mock_header();
+        let json = to_canonical_json(&header).unwrap();
+        assert!(json.starts_with(r#"{"base_fee_per_gas":"#));
+        assert!(!json.contains(' '));
+        assert_eq!(serde_json::from_str::<AxonHeader>(&json).unwrap(), header);
+
+        let pretty = serde_json::to_string_pretty(&header).unwrap();
+        assert_eq!(canonicalize(&pretty).unwrap(), json);
+    }
+}
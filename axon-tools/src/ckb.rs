@@ -0,0 +1,137 @@
+//! Conversions between the types of this crate and their `ckb_types`
+//! counterparts, for code that carries Axon proofs into CKB transactions.
+//! [`Hex`] converts with `From`. `H256` and `Bytes` belong to other crates,
+//! so they convert through [`IntoCkb`] and [`TryFromCkb`] instead; script args
+//! are a `packed::Bytes` and convert the same way.
+
+use bytes::Bytes;
+use ckb_types::packed::{self, Byte32};
+use ckb_types::prelude::{Entity, Pack};
+use ethereum_types::H256;
+
+use crate::types::{Hex, TypesError};
+
+impl From<Hex> for packed::Bytes {
+    fn from(hex: Hex) -> Self {
+        hex.as_bytes().pack()
+    }
+}
+
+impl From<&Hex> for packed::Bytes {
+    fn from(hex: &Hex) -> Self {
+        hex.as_bytes().pack()
+    }
+}
+
+impl From<packed::Bytes> for Hex {
+    fn from(bytes: packed::Bytes) -> Self {
+        Hex::encode(bytes.raw_data())
+    }
+}
+
+impl From<&packed::Bytes> for Hex {
+    fn from(bytes: &packed::Bytes) -> Self {
+        Hex::encode(bytes.raw_data())
+    }
+}
+
+/// Conversion into the `ckb_types` counterpart of a type.
+pub trait IntoCkb {
+    type Target;
+
+    fn into_ckb(self) -> Self::Target;
+}
+
+impl IntoCkb for H256 {
+    type Target = Byte32;
+
+    fn into_ckb(self) -> Byte32 {
+        self.0.pack()
+    }
+}
+
+impl IntoCkb for &H256 {
+    type Target = Byte32;
+
+    fn into_ckb(self) -> Byte32 {
+        self.0.pack()
+    }
+}
+
+impl IntoCkb for Bytes {
+    type Target = packed::Bytes;
+
+    fn into_ckb(self) -> packed::Bytes {
+        self.pack()
+    }
+}
+
+impl IntoCkb for &[u8] {
+    type Target = packed::Bytes;
+
+    fn into_ckb(self) -> packed::Bytes {
+        self.pack()
+    }
+}
+
+/// Conversion from a `ckb_types` value, failing if it has the wrong length.
+pub trait TryFromCkb<T>: Sized {
+    fn try_from_ckb(value: &T) -> Result<Self, TypesError>;
+}
+
+impl TryFromCkb<Byte32> for H256 {
+    fn try_from_ckb(value: &Byte32) -> Result<Self, TypesError> {
+        Ok(H256::from_slice(value.as_slice()))
+    }
+}
+
+/// Script args holding exactly a 32 byte hash, such as a type ID.
+impl TryFromCkb<packed::Bytes> for H256 {
+    fn try_from_ckb(value: &packed::Bytes) -> Result<Self, TypesError> {
+        let data = value.raw_data();
+        if data.len() != 32 {
+            return Err(TypesError::LengthMismatch {
+                expect: 32,
+                real:   data.len(),
+            });
+        }
+        Ok(H256::from_slice(&data))
+    }
+}
+
+impl TryFromCkb<packed::Bytes> for Bytes {
+    fn try_from_ckb(value: &packed::Bytes) -> Result<Self, TypesError> {
+        Ok(value.raw_data())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ckb_types::packed::Script;
+    use ckb_types::prelude::Builder;
+
+    use super::*;
+    use crate::types::tests::random_bytes;
+
+    #[test]
+    fn test_hex_roundtrip() {
+        let hex = Hex::encode(random_bytes(40));
+        let packed = packed::Bytes::from(&hex);
+        assert_eq!(packed.raw_data(), hex.as_bytes());
+        assert_eq!(Hex::from(packed), hex);
+    }
+
+    #[test]
+    fn test_h256_roundtrip() {
+        let hash = H256(rand::random());
+        let byte32 = hash.into_ckb();
+        assert_eq!(byte32.as_slice(), hash.as_bytes());
+        assert_eq!(H256::try_from_ckb(&byte32).unwrap(), hash);
+
+        let script = Script::new_builder().args(hash.as_bytes().into_ckb()).build();
+        assert_eq!(H256::try_from_ckb(&script.args()).unwrap(), hash);
+        let short = random_bytes(20).into_ckb();
+        assert!(H256::try_from_ckb(&short).is_err());
+        assert_eq!(Bytes::try_from_ckb(&short).unwrap().len(), 20);
+    }
+}
@@ -0,0 +1,926 @@
+//! A JSON-RPC client for the methods typed in [`rpc`](crate::rpc), generic
+//! over how a request reaches the node. A [`Transport`] only moves one JSON
+//! document each way, so anything from a blocking HTTP library to a unix
+//! socket or a host call out of a WASM guest can carry the typed methods of
+//! [`Client`]. Closures of the same shape are transports too, and HTTP
+//! transports over `reqwest` and `hyper` come with the features of the same
+//! names. A [`RetryTransport`] retries any of them with backoff and falls
+//! back to other endpoints.
+
+use alloc::collections::VecDeque;
+use alloc::string::{String, ToString};
+use core::cell::Cell;
+use core::ops::Range;
+use core::time::Duration;
+use std::fmt::{self, Display};
+
+use ethereum_types::{H160, H256, U256};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+
+use crate::rpc::{CkbRelatedInfo, EIP1186ProofResponse, TransactionReceipt};
+use crate::types::{try_into_u64, AxonBlock, BlockNumber, Metadata, Proof};
+
+/// Carries a JSON-RPC request to a node and returns its response.
+pub trait Transport {
+    type Error;
+
+    fn send(&self, request: Value) -> Result<Value, Self::Error>;
+}
+
+impl<F, E> Transport for F
+where
+    F: Fn(Value) -> Result<Value, E>,
+{
+    type Error = E;
+
+    fn send(&self, request: Value) -> Result<Value, E> {
+        self(request)
+    }
+}
+
+#[derive(Debug)]
+pub enum ClientError<E> {
+    Transport(E),
+    /// The node answered with a JSON-RPC error object.
+    Rpc {
+        code:    i64,
+        message: String,
+    },
+    /// The response is not a JSON-RPC response, or its result does not
+    /// deserialize into the expected type.
+    InvalidResponse(String),
+}
+
+impl<E: Display> Display for ClientError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ClientError::Transport(e) => write!(f, "Transport error: {}", e),
+            ClientError::Rpc { code, message } => write!(f, "RPC error {}: {}", code, message),
+            ClientError::InvalidResponse(e) => write!(f, "Invalid response: {}", e),
+        }
+    }
+}
+
+impl<E: fmt::Debug + Display> std::error::Error for ClientError<E> {}
+
+/// The result of each call of a [`Client::batch`], in the order of the calls.
+pub type BatchResults<R, E> = Vec<Result<R, ClientError<E>>>;
+
+pub struct Client<T> {
+    transport: T,
+    next_id:   Cell<u64>,
+}
+
+impl<T: Transport> Client<T> {
+    pub fn new(transport: T) -> Self {
+        Client {
+            transport,
+            next_id: Cell::new(1),
+        }
+    }
+
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    /// Call `method` with `params` and deserialize its result. This is what
+    /// the typed methods are built on, for the methods they do not cover.
+    pub fn call<R: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: Value,
+    ) -> Result<R, ClientError<T::Error>> {
+        let id = self.take_ids(1);
+        let response = self
+            .transport
+            .send(request(id, method, params))
+            .map_err(ClientError::Transport)?;
+        parse_response(response, id)
+    }
+
+    /// Send `calls`, each a method and its params, as one JSON-RPC batch and
+    /// return their results in the order of `calls`. A call the node fails
+    /// or leaves unanswered is an error in its own slot, only a transport
+    /// failure or a rejection of the whole batch fails the batch. Calls of
+    /// different result types are batched as [`Value`].
+    pub fn batch<R: DeserializeOwned>(
+        &self,
+        calls: &[(&str, Value)],
+    ) -> Result<BatchResults<R, T::Error>, ClientError<T::Error>> {
+        // An empty batch is an invalid request.
+        if calls.is_empty() {
+            return Ok(Vec::new());
+        }
+        let first_id = self.take_ids(calls.len() as u64);
+        let requests = calls
+            .iter()
+            .enumerate()
+            .map(|(i, (method, params))| {
+                request(first_id.wrapping_add(i as u64), method, params.clone())
+            })
+            .collect();
+
+        let responses = match self.transport.send(Value::Array(requests)) {
+            Ok(Value::Array(responses)) => responses,
+            Ok(response) => {
+                check_error(&response)?;
+                return Err(ClientError::InvalidResponse("expect an array".to_string()));
+            }
+            Err(e) => return Err(ClientError::Transport(e)),
+        };
+        // Responses may come in any order, they are matched by id.
+        let mut slots = vec![None; calls.len()];
+        for response in responses {
+            let index = response
+                .get("id")
+                .and_then(Value::as_u64)
+                .map(|id| id.wrapping_sub(first_id) as usize)
+                .filter(|index| *index < calls.len());
+            if let Some(index) = index {
+                slots[index] = Some(response);
+            }
+        }
+
+        Ok(slots
+            .into_iter()
+            .enumerate()
+            .map(|(i, slot)| {
+                let id = first_id.wrapping_add(i as u64);
+                match slot {
+                    Some(response) => parse_response(response, id),
+                    None => Err(ClientError::InvalidResponse(format!("no response to id {}", id))),
+                }
+            })
+            .collect())
+    }
+
+    /// The blocks of `heights` with their proofs, in height order. Heights
+    /// are fetched `batch_size` at a time, as one JSON-RPC batch sent when
+    /// the pairs fetched before it are consumed. Nothing runs concurrently:
+    /// batching saves the round trips of a per-height loop, and a slow
+    /// consumer holds back the requests rather than piling up responses. A
+    /// height the node has no block or proof for is an error in its place, a
+    /// failure of a whole batch is the last item.
+    pub fn get_blocks_with_proofs(
+        &self,
+        heights: Range<BlockNumber>,
+        batch_size: usize,
+    ) -> BlocksWithProofs<'_, T> {
+        BlocksWithProofs {
+            client: self,
+            heights,
+            batch_size: batch_size.max(1) as u64,
+            fetched: VecDeque::new(),
+        }
+    }
+
+    /// Reserve `count` consecutive request ids and return the first.
+    fn take_ids(&self, count: u64) -> u64 {
+        let id = self.next_id.get();
+        self.next_id.set(id.wrapping_add(count));
+        id
+    }
+
+    pub fn block_number(&self) -> Result<BlockNumber, ClientError<T::Error>> {
+        let number: U256 = self.call("eth_blockNumber", json!([]))?;
+        try_into_u64(number).map_err(|e| ClientError::InvalidResponse(e.to_string()))
+    }
+
+    pub fn get_block_by_number(
+        &self,
+        number: BlockNumber,
+    ) -> Result<Option<AxonBlock>, ClientError<T::Error>> {
+        self.call("axon_getBlockById", json!([quantity(number)]))
+    }
+
+    pub fn get_proof_by_number(
+        &self,
+        number: BlockNumber,
+    ) -> Result<Option<Proof>, ClientError<T::Error>> {
+        self.call("axon_getProofByNumber", json!([quantity(number)]))
+    }
+
+    pub fn get_current_metadata(&self) -> Result<Metadata, ClientError<T::Error>> {
+        self.call("axon_getCurrentMetadata", json!([]))
+    }
+
+    pub fn get_metadata_by_number(
+        &self,
+        number: BlockNumber,
+    ) -> Result<Option<Metadata>, ClientError<T::Error>> {
+        self.call("axon_getMetadataByNumber", json!([quantity(number)]))
+    }
+
+    pub fn get_ckb_related_info(&self) -> Result<CkbRelatedInfo, ClientError<T::Error>> {
+        self.call("axon_getCkbRelatedInfo", json!([]))
+    }
+
+    pub fn get_transaction_receipt(
+        &self,
+        hash: H256,
+    ) -> Result<Option<TransactionReceipt>, ClientError<T::Error>> {
+        self.call("eth_getTransactionReceipt", json!([hash]))
+    }
+
+    /// `eth_getProof` of `address` and its `storage_keys` at block `number`.
+    pub fn get_proof(
+        &self,
+        address: H160,
+        storage_keys: &[H256],
+        number: BlockNumber,
+    ) -> Result<EIP1186ProofResponse, ClientError<T::Error>> {
+        self.call("eth_getProof", json!([address, storage_keys, quantity(number)]))
+    }
+}
+
+/// The iterator of [`Client::get_blocks_with_proofs`].
+pub struct BlocksWithProofs<'a, T: Transport> {
+    client:     &'a Client<T>,
+    // The heights not fetched yet.
+    heights:    Range<BlockNumber>,
+    batch_size: u64,
+    fetched:    VecDeque<BlockWithProof<T::Error>>,
+}
+
+type BlockWithProof<E> = Result<(AxonBlock, Proof), ClientError<E>>;
+
+impl<'a, T: Transport> BlocksWithProofs<'a, T> {
+    fn fetch(&mut self) {
+        let count = (self.heights.end - self.heights.start).min(self.batch_size);
+        let heights = self.heights.start..self.heights.start + count;
+        self.heights.start = heights.end;
+        let calls = heights
+            .clone()
+            .flat_map(|number| {
+                [
+                    ("axon_getBlockById", json!([quantity(number)])),
+                    ("axon_getProofByNumber", json!([quantity(number)])),
+                ]
+            })
+            .collect::<Vec<_>>();
+
+        match self.client.batch::<Value>(&calls) {
+            Ok(results) => {
+                let mut results = results.into_iter();
+                for number in heights {
+                    let (block, proof) = (results.next(), results.next());
+                    self.fetched.push_back(block_with_proof(
+                        number,
+                        block.expect("a result per call"),
+                        proof.expect("a result per call"),
+                    ));
+                }
+            }
+            Err(e) => {
+                self.heights.start = self.heights.end;
+                self.fetched.push_back(Err(e));
+            }
+        }
+    }
+}
+
+impl<'a, T: Transport> Iterator for BlocksWithProofs<'a, T> {
+    type Item = BlockWithProof<T::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.fetched.is_empty() && !self.heights.is_empty() {
+            self.fetch();
+        }
+        self.fetched.pop_front()
+    }
+}
+
+fn block_with_proof<E>(
+    number: BlockNumber,
+    block: Result<Value, ClientError<E>>,
+    proof: Result<Value, ClientError<E>>,
+) -> BlockWithProof<E> {
+    let block: Option<AxonBlock> = from_value(block?)?;
+    let proof: Option<Proof> = from_value(proof?)?;
+    match (block, proof) {
+        (Some(block), Some(proof))
+            if block.header.number == number && proof.number == number =>
+        {
+            Ok((block, proof))
+        }
+        (Some(_), Some(_)) => Err(ClientError::InvalidResponse(format!(
+            "block or proof of another height than {}",
+            number
+        ))),
+        _ => Err(ClientError::InvalidResponse(format!("no block or proof of {}", number))),
+    }
+}
+
+fn request(id: u64, method: &str, params: Value) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "method": method,
+        "params": params,
+    })
+}
+
+fn check_error<E>(response: &Value) -> Result<(), ClientError<E>> {
+    match response.get("error").filter(|e| !e.is_null()) {
+        Some(error) => Err(ClientError::Rpc {
+            code:    error.get("code").and_then(Value::as_i64).unwrap_or_default(),
+            message: error
+                .get("message")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// The result of the response to request `id`.
+fn parse_response<R: DeserializeOwned, E>(
+    mut response: Value,
+    id: u64,
+) -> Result<R, ClientError<E>> {
+    check_error(&response)?;
+    if response.get("id") != Some(&json!(id)) {
+        return Err(ClientError::InvalidResponse(format!("expect id {}", id)));
+    }
+    let result = response
+        .get_mut("result")
+        .map(Value::take)
+        .ok_or_else(|| ClientError::InvalidResponse("missing result".to_string()))?;
+    from_value(result)
+}
+
+fn from_value<R: DeserializeOwned, E>(value: Value) -> Result<R, ClientError<E>> {
+    serde_json::from_value(value).map_err(|e| ClientError::InvalidResponse(e.to_string()))
+}
+
+fn quantity(number: BlockNumber) -> String {
+    format!("0x{:x}", number)
+}
+
+/// How a [`RetryTransport`] retries a failing endpoint.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Sends to one endpoint before falling back to the next, at least one.
+    pub attempts:        u32,
+    /// The wait after the first failed send, doubled after each further one.
+    pub initial_backoff: Duration,
+    pub max_backoff:     Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            attempts:        3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff:     Duration::from_secs(2),
+        }
+    }
+}
+
+/// Retries a request that fails to send, with exponential backoff, then
+/// falls back to the next endpoint in order. Once an endpoint answers, later
+/// requests start from it rather than from the first. Only transport errors
+/// are retried, a JSON-RPC error is an answer and is returned as it is.
+///
+/// How long one send may take is up to the endpoint transports, such as the
+/// timeout of [`ReqwestTransport::new`]. A request is given up after every
+/// endpoint failed [`RetryPolicy::attempts`] times, with the error of the
+/// last send.
+pub struct RetryTransport<T> {
+    endpoints: Vec<T>,
+    policy:    RetryPolicy,
+    current:   Cell<usize>,
+    sleep:     fn(Duration),
+}
+
+impl<T: Transport> RetryTransport<T> {
+    pub fn new(endpoint: T, policy: RetryPolicy) -> Self {
+        RetryTransport {
+            endpoints: vec![endpoint],
+            policy,
+            current: Cell::new(0),
+            sleep: std::thread::sleep,
+        }
+    }
+
+    /// Fall back to `endpoint` after the ones added before it.
+    pub fn fallback(mut self, endpoint: T) -> Self {
+        self.endpoints.push(endpoint);
+        self
+    }
+
+    /// Wait out backoffs with `sleep` instead of [`std::thread::sleep`], for
+    /// targets without threads.
+    pub fn with_sleep(mut self, sleep: fn(Duration)) -> Self {
+        self.sleep = sleep;
+        self
+    }
+
+    pub fn endpoints(&self) -> &[T] {
+        &self.endpoints
+    }
+}
+
+impl<T: Transport> Transport for RetryTransport<T> {
+    type Error = T::Error;
+
+    fn send(&self, request: Value) -> Result<Value, T::Error> {
+        let start = self.current.get();
+        let mut last_error = None;
+        for i in 0..self.endpoints.len() {
+            let index = (start + i) % self.endpoints.len();
+            let mut backoff = self.policy.initial_backoff;
+            for attempt in 0..self.policy.attempts.max(1) {
+                if attempt > 0 {
+                    (self.sleep)(backoff);
+                    backoff = backoff.saturating_mul(2).min(self.policy.max_backoff);
+                }
+                match self.endpoints[index].send(request.clone()) {
+                    Ok(response) => {
+                        self.current.set(index);
+                        return Ok(response);
+                    }
+                    Err(e) => last_error = Some(e),
+                }
+            }
+        }
+        Err(last_error.expect("at least one endpoint and one attempt"))
+    }
+}
+
+/// Posts each request to an HTTP endpoint with a blocking `reqwest` client.
+/// Only plain HTTP is compiled in, enable one of reqwest's TLS features in
+/// the depending crate for HTTPS endpoints.
+#[cfg(feature = "reqwest")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "reqwest")))]
+#[derive(Clone, Debug)]
+pub struct ReqwestTransport {
+    client: reqwest::blocking::Client,
+    url:    String,
+}
+
+#[cfg(feature = "reqwest")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "reqwest")))]
+impl ReqwestTransport {
+    /// A transport to `url` giving up on a request after `timeout`.
+    pub fn new(url: impl Into<String>, timeout: Duration) -> Result<Self, reqwest::Error> {
+        let client = reqwest::blocking::Client::builder().timeout(timeout).build()?;
+        Ok(ReqwestTransport::with_client(client, url))
+    }
+
+    /// A transport to `url` sending through `client` as configured.
+    pub fn with_client(client: reqwest::blocking::Client, url: impl Into<String>) -> Self {
+        ReqwestTransport {
+            client,
+            url: url.into(),
+        }
+    }
+}
+
+#[cfg(feature = "reqwest")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "reqwest")))]
+impl Transport for ReqwestTransport {
+    type Error = reqwest::Error;
+
+    fn send(&self, request: Value) -> Result<Value, reqwest::Error> {
+        self.client
+            .post(self.url.as_str())
+            .json(&request)
+            .send()?
+            .error_for_status()?
+            .json()
+    }
+}
+
+/// Posts each request to an HTTP endpoint with a `hyper` client, driven by a
+/// runtime of its own so that it can be called from synchronous code. The
+/// connector is `hyper`'s plain HTTP one unless a client built on another,
+/// for instance a TLS connector, is given to [`HyperTransport::with_client`].
+#[cfg(feature = "hyper")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "hyper")))]
+pub struct HyperTransport<C = hyper::client::HttpConnector> {
+    runtime: tokio::runtime::Runtime,
+    client:  hyper::Client<C>,
+    uri:     hyper::Uri,
+    timeout: Duration,
+}
+
+#[cfg(feature = "hyper")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "hyper")))]
+#[derive(Debug)]
+pub enum HyperError {
+    InvalidUri(hyper::http::uri::InvalidUri),
+    /// The runtime could not be started.
+    Io(std::io::Error),
+    Http(hyper::Error),
+    /// The endpoint answered with a status other than success.
+    Status(hyper::StatusCode),
+    Json(serde_json::Error),
+    /// No response within the timeout.
+    Timeout,
+}
+
+#[cfg(feature = "hyper")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "hyper")))]
+impl Display for HyperError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HyperError::InvalidUri(e) => write!(f, "Invalid uri: {}", e),
+            HyperError::Io(e) => write!(f, "Io error: {}", e),
+            HyperError::Http(e) => write!(f, "Http error: {}", e),
+            HyperError::Status(status) => write!(f, "Http status {}", status),
+            HyperError::Json(e) => write!(f, "Json error: {}", e),
+            HyperError::Timeout => write!(f, "Request timed out"),
+        }
+    }
+}
+
+#[cfg(feature = "hyper")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "hyper")))]
+impl std::error::Error for HyperError {}
+
+#[cfg(feature = "hyper")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "hyper")))]
+impl HyperTransport {
+    /// A plain HTTP transport to `uri` giving up on a request after
+    /// `timeout`.
+    pub fn new(uri: &str, timeout: Duration) -> Result<Self, HyperError> {
+        HyperTransport::with_client(hyper::Client::new(), uri, timeout)
+    }
+}
+
+#[cfg(feature = "hyper")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "hyper")))]
+impl<C> HyperTransport<C>
+where
+    C: hyper::client::connect::Connect + Clone + Send + Sync + 'static,
+{
+    pub fn with_client(
+        client: hyper::Client<C>,
+        uri: &str,
+        timeout: Duration,
+    ) -> Result<Self, HyperError> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(HyperError::Io)?;
+        Ok(HyperTransport {
+            runtime,
+            client,
+            uri: uri.parse().map_err(HyperError::InvalidUri)?,
+            timeout,
+        })
+    }
+}
+
+#[cfg(feature = "hyper")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "hyper")))]
+impl<C> Transport for HyperTransport<C>
+where
+    C: hyper::client::connect::Connect + Clone + Send + Sync + 'static,
+{
+    type Error = HyperError;
+
+    fn send(&self, request: Value) -> Result<Value, HyperError> {
+        let body = serde_json::to_vec(&request).map_err(HyperError::Json)?;
+        let request = hyper::Request::post(self.uri.clone())
+            .header(hyper::header::CONTENT_TYPE, "application/json")
+            .body(hyper::Body::from(body))
+            .expect("a request of a valid uri and header");
+
+        self.runtime.block_on(async {
+            let exchange = async {
+                let response = self.client.request(request).await.map_err(HyperError::Http)?;
+                if !response.status().is_success() {
+                    return Err(HyperError::Status(response.status()));
+                }
+                let body = hyper::body::to_bytes(response.into_body())
+                    .await
+                    .map_err(HyperError::Http)?;
+                serde_json::from_slice(&body).map_err(HyperError::Json)
+            };
+            tokio::time::timeout(self.timeout, exchange)
+                .await
+                .map_err(|_| HyperError::Timeout)?
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::{Cell, RefCell};
+    #[cfg(any(feature = "reqwest", feature = "hyper"))]
+    use std::net::TcpListener;
+    #[cfg(any(feature = "reqwest", feature = "hyper"))]
+    use std::thread::JoinHandle;
+
+    use super::*;
+    use crate::types::tests::{mock_header, mock_validator};
+    use crate::types::MetadataVersion;
+
+    #[test]
+    fn test_typed_call() {
+        let block = AxonBlock {
+            header:    mock_header(),
+            tx_hashes: vec![H256::repeat_byte(1)],
+        };
+        let requests = RefCell::new(Vec::new());
+        let client = Client::new(|request: Value| {
+            let result = match request["method"].as_str() {
+                Some("axon_getBlockById") => json!(block),
+                _ => Value::Null,
+            };
+            let response = json!({"jsonrpc": "2.0", "id": request["id"], "result": result});
+            requests.borrow_mut().push(request);
+            Ok::<_, String>(response)
+        });
+
+        assert_eq!(client.get_block_by_number(0x1b4).unwrap(), Some(block.clone()));
+        assert_eq!(client.get_proof_by_number(1).unwrap(), None);
+        let requests = requests.borrow();
+        assert_eq!(requests[0]["params"], json!(["0x1b4"]));
+        assert_eq!(requests[0]["jsonrpc"], "2.0");
+        assert_ne!(requests[0]["id"], requests[1]["id"]);
+    }
+
+    #[test]
+    fn test_axon_methods() {
+        let metadata = Metadata {
+            version: MetadataVersion::new(101, 200),
+            epoch: 1,
+            verifier_list: vec![mock_validator(1), mock_validator(2)],
+            ..Default::default()
+        };
+        let proof = Proof {
+            number: 0x64,
+            block_hash: H256::repeat_byte(1),
+            ..Default::default()
+        };
+        let requests = RefCell::new(Vec::new());
+        let client = Client::new(|request: Value| {
+            let result = match (request["method"].as_str().unwrap(), &request["params"]) {
+                ("axon_getCurrentMetadata", params) if *params == json!([]) => json!(metadata),
+                ("axon_getMetadataByNumber", params) if *params == json!(["0x64"]) => {
+                    json!(metadata)
+                }
+                ("axon_getProofByNumber", params) if *params == json!(["0x64"]) => json!(proof),
+                ("axon_getCkbRelatedInfo", params) if *params == json!([]) => json!({
+                    "metadata_type_id": H256::repeat_byte(1),
+                    "checkpoint_type_id": H256::repeat_byte(2),
+                    "xudt_args": H256::repeat_byte(3),
+                    "stake_smt_type_id": H256::repeat_byte(4),
+                    "delegate_smt_type_id": H256::repeat_byte(5),
+                    "reward_smt_type_id": H256::repeat_byte(6),
+                }),
+                _ => Value::Null,
+            };
+            requests.borrow_mut().push(request["method"].clone());
+            Ok::<_, String>(json!({"jsonrpc": "2.0", "id": request["id"], "result": result}))
+        });
+
+        assert_eq!(client.get_current_metadata().unwrap(), metadata);
+        assert_eq!(client.get_metadata_by_number(0x64).unwrap(), Some(metadata.clone()));
+        assert_eq!(client.get_metadata_by_number(0x65).unwrap(), None);
+        assert_eq!(client.get_proof_by_number(0x64).unwrap(), Some(proof.clone()));
+        let info = client.get_ckb_related_info().unwrap();
+        assert_eq!(info.metadata_type_id, H256::repeat_byte(1));
+        assert_eq!(info.reward_smt_type_id, H256::repeat_byte(6));
+        assert_eq!(requests.borrow().len(), 5);
+    }
+
+    #[test]
+    fn test_error_response() {
+        let client = Client::new(|request: Value| {
+            Ok::<_, String>(json!({
+                "jsonrpc": "2.0",
+                "id": request["id"],
+                "error": {"code": -32601, "message": "method not found"},
+            }))
+        });
+        match client.get_current_metadata() {
+            Err(ClientError::Rpc { code, message }) => {
+                assert_eq!(code, -32601);
+                assert_eq!(message, "method not found");
+            }
+            other => panic!("unexpected {:?}", other.map(|m| m.epoch)),
+        }
+
+        let client = Client::new(|_: Value| Err::<Value, _>("refused"));
+        assert!(matches!(client.block_number(), Err(ClientError::Transport("refused"))));
+        let client = Client::new(|_: Value| Ok::<_, String>(json!({"id": 7, "result": "0x1"})));
+        assert!(matches!(client.block_number(), Err(ClientError::InvalidResponse(_))));
+        let client = Client::new(|request: Value| {
+            Ok::<_, String>(json!({"id": request["id"], "result": "0x10000000000000000"}))
+        });
+        assert!(matches!(client.block_number(), Err(ClientError::InvalidResponse(_))));
+    }
+
+    #[test]
+    fn test_batch() {
+        let client = Client::new(|request: Value| {
+            let mut responses = request
+                .as_array()
+                .unwrap()
+                .iter()
+                .filter_map(|call| match call["params"][0].as_str() {
+                    Some("0x1") => Some(json!({"id": call["id"], "result": "0x1b4"})),
+                    Some("0x2") => Some(json!({
+                        "id": call["id"],
+                        "error": {"code": -32000, "message": "pruned"},
+                    })),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            responses.reverse();
+            Ok::<_, String>(Value::Array(responses))
+        });
+
+        let calls = ["0x2", "0x1", "0x3"].map(|n| ("eth_getBalance", json!([n])));
+        let results = client.batch::<U256>(&calls).unwrap();
+        assert!(matches!(&results[0], Err(ClientError::Rpc { code: -32000, .. })));
+        assert_eq!(results[1].as_ref().unwrap(), &U256::from(0x1b4));
+        assert!(matches!(results[2], Err(ClientError::InvalidResponse(_))));
+        assert!(client.batch::<Value>(&[]).unwrap().is_empty());
+
+        let client = Client::new(|_: Value| {
+            Ok::<_, String>(json!({"id": null, "error": {"code": -32600, "message": "no batch"}}))
+        });
+        assert!(matches!(
+            client.batch::<Value>(&calls),
+            Err(ClientError::Rpc { code: -32600, .. })
+        ));
+    }
+
+    #[test]
+    fn test_get_blocks_with_proofs() {
+        let batches = RefCell::new(Vec::new());
+        let client = Client::new(|request: Value| {
+            let calls = request.as_array().unwrap();
+            batches.borrow_mut().push(calls.len());
+            let responses = calls.iter().map(|call| {
+                let number = u64::from_str_radix(&call["params"][0].as_str().unwrap()[2..], 16);
+                let number = number.unwrap();
+                let result = match call["method"].as_str() {
+                    Some("axon_getBlockById") => {
+                        let mut header = mock_header();
+                        header.number = number;
+                        json!(AxonBlock {
+                            header,
+                            tx_hashes: vec![],
+                        })
+                    }
+                    Some("axon_getProofByNumber") if number != 4 => json!(Proof {
+                        number,
+                        ..Default::default()
+                    }),
+                    _ => Value::Null,
+                };
+                json!({"jsonrpc": "2.0", "id": call["id"], "result": result})
+            });
+            Ok::<_, String>(Value::Array(responses.collect()))
+        });
+
+        let mut pairs = client.get_blocks_with_proofs(1..6, 2);
+        assert!(batches.borrow().is_empty());
+        let (block, proof) = pairs.next().unwrap().unwrap();
+        assert_eq!((block.header.number, proof.number), (1, 1));
+        assert_eq!(pairs.next().unwrap().unwrap().0.header.number, 2);
+        // Nothing more is fetched until the first two are consumed.
+        assert_eq!(*batches.borrow(), vec![4]);
+
+        assert_eq!(pairs.next().unwrap().unwrap().1.number, 3);
+        assert!(matches!(pairs.next(), Some(Err(ClientError::InvalidResponse(_)))));
+        assert_eq!(pairs.next().unwrap().unwrap().0.header.number, 5);
+        assert!(pairs.next().is_none());
+        assert_eq!(*batches.borrow(), vec![4, 4, 2]);
+
+        let client = Client::new(|_: Value| Err::<Value, _>("refused"));
+        let mut pairs = client.get_blocks_with_proofs(1..6, 2);
+        assert!(matches!(pairs.next(), Some(Err(ClientError::Transport("refused")))));
+        assert!(pairs.next().is_none());
+    }
+
+    std::thread_local! {
+        static SLEPT: RefCell<Vec<Duration>> = const { RefCell::new(Vec::new()) };
+    }
+
+    #[test]
+    fn test_retry_transport() {
+        type Endpoint = Box<dyn Fn(Value) -> Result<Value, String>>;
+        let failures = std::rc::Rc::new(Cell::new(0));
+        let flaky = |name: &'static str, fail: usize| -> Endpoint {
+            let failures = failures.clone();
+            let sent = Cell::new(0);
+            Box::new(move |_| {
+                sent.set(sent.get() + 1);
+                if sent.get() <= fail {
+                    failures.set(failures.get() + 1);
+                    return Err(name.to_string());
+                }
+                Ok(json!(name))
+            })
+        };
+        let policy = RetryPolicy {
+            attempts:        3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff:     Duration::from_millis(150),
+        };
+        let transport = RetryTransport::new(flaky("primary", 4), policy)
+            .fallback(flaky("backup", 1))
+            .with_sleep(|d| SLEPT.with(|slept| slept.borrow_mut().push(d)));
+
+        // Three failed sends to the primary, one to the backup.
+        assert_eq!(transport.send(json!({})).unwrap(), json!("backup"));
+        assert_eq!(failures.get(), 4);
+        let slept = SLEPT.with(|slept| slept.take());
+        assert_eq!(slept, [100, 150, 100].map(Duration::from_millis));
+        // The backup answered, so it is tried first from now on.
+        assert_eq!(transport.send(json!({})).unwrap(), json!("backup"));
+        assert_eq!(failures.get(), 4);
+
+        let transport = RetryTransport::new(flaky("primary", 9), RetryPolicy::default())
+            .fallback(flaky("backup", 9))
+            .with_sleep(|_| {});
+        assert_eq!(transport.send(json!({})), Err("backup".to_string()));
+    }
+
+    /// Answer one HTTP request on a local port with `status` and `body`. The
+    /// handle returns the body of the request.
+    #[cfg(any(feature = "reqwest", feature = "hyper"))]
+    fn serve_once(status: &'static str, body: &'static str) -> (String, JoinHandle<String>) {
+        use std::io::{BufRead, BufReader, Read, Write};
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let handle = std::thread::spawn(move || {
+            let mut reader = BufReader::new(listener.accept().unwrap().0);
+            let mut len = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line == "\r\n" {
+                    break;
+                }
+                if let Some(value) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                    len = value.trim().parse().unwrap();
+                }
+            }
+            let mut request = vec![0; len];
+            reader.read_exact(&mut request).unwrap();
+            write!(
+                reader.get_mut(),
+                "HTTP/1.1 {}\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            )
+            .unwrap();
+            String::from_utf8(request).unwrap()
+        });
+        (url, handle)
+    }
+
+    #[cfg(any(feature = "reqwest", feature = "hyper"))]
+    fn check_http_transport<T: Transport>(new: impl Fn(&str) -> T)
+    where
+        T::Error: fmt::Debug,
+    {
+        let (url, server) = serve_once("200 OK", r#"{"jsonrpc":"2.0","id":1,"result":"0x1b4"}"#);
+        assert_eq!(Client::new(new(&url)).block_number().unwrap(), 0x1b4);
+        let request: Value = serde_json::from_str(&server.join().unwrap()).unwrap();
+        assert_eq!(request["method"], "eth_blockNumber");
+
+        let (url, server) = serve_once("500 Internal Server Error", "{}");
+        let client = Client::new(new(&url));
+        assert!(matches!(client.block_number(), Err(ClientError::Transport(_))));
+        server.join().unwrap();
+
+        // Accepted by the backlog and never answered.
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = Client::new(new(&format!("http://{}", listener.local_addr().unwrap())));
+        assert!(matches!(client.block_number(), Err(ClientError::Transport(_))));
+    }
+
+    #[cfg(feature = "reqwest")]
+    #[test]
+    fn test_reqwest_transport() {
+        check_http_transport(|url| {
+            ReqwestTransport::new(url, Duration::from_millis(500)).unwrap()
+        });
+    }
+
+    #[cfg(feature = "hyper")]
+    #[test]
+    fn test_hyper_transport() {
+        check_http_transport(|url| HyperTransport::new(url, Duration::from_millis(500)).unwrap());
+        let (url, server) = serve_once("200 OK", "not json");
+        let client = Client::new(HyperTransport::new(&url, Duration::from_secs(5)).unwrap());
+        assert!(matches!(
+            client.block_number(),
+            Err(ClientError::Transport(HyperError::Json(_)))
+        ));
+        server.join().unwrap();
+    }
+}
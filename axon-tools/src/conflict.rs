@@ -0,0 +1,166 @@
+//! Detection of conflicting finalized blocks. Two valid proofs for different
+//! blocks at the same height mean that a quorum of validators signed twice,
+//! which a light client must treat as a safety failure rather than a reorg.
+
+use ethereum_types::H256;
+
+use crate::types::{AxonBlock, Proof, ValidatorExtend};
+use crate::Error;
+
+/// Outcome of [`detect_conflict`] for two verified block and proof pairs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Conflict {
+    /// The blocks are at different heights, so they cannot conflict.
+    DifferentHeight { a: u64, b: u64 },
+    /// Both proofs finalize the same block, possibly in different rounds.
+    SameBlock { number: u64, hash: H256 },
+    /// Two different blocks are finalized at the same height.
+    Conflicting {
+        number:  u64,
+        a_hash:  H256,
+        a_round: u64,
+        b_hash:  H256,
+        b_round: u64,
+    },
+}
+
+impl Conflict {
+    pub fn is_conflicting(&self) -> bool {
+        matches!(self, Conflict::Conflicting { .. })
+    }
+}
+
+/// Verify both pairs against `validator_list` and report whether they
+/// finalize different blocks at the same height. `previous_state_roots` holds
+/// the parent state root of `a` and `b` respectively, since conflicting
+/// blocks need not share a parent. A pair that fails verification is an
+/// error, as an unproven block is not evidence of anything.
+pub fn detect_conflict(
+    a: &(AxonBlock, Proof),
+    b: &(AxonBlock, Proof),
+    previous_state_roots: (H256, H256),
+    validator_list: &[ValidatorExtend],
+) -> Result<Conflict, Error> {
+    let (a_state_root, b_state_root) = previous_state_roots;
+    for ((block, proof), state_root) in [(a, a_state_root), (b, b_state_root)] {
+        crate::verify_proof(block, state_root, validator_list, proof)?;
+    }
+
+    let ((_, a_proof), (_, b_proof)) = (a, b);
+    if a_proof.number != b_proof.number {
+        return Ok(Conflict::DifferentHeight {
+            a: a_proof.number,
+            b: b_proof.number,
+        });
+    }
+
+    if a_proof.block_hash == b_proof.block_hash {
+        return Ok(Conflict::SameBlock {
+            number: a_proof.number,
+            hash:   a_proof.block_hash,
+        });
+    }
+
+    Ok(Conflict::Conflicting {
+        number:  a_proof.number,
+        a_hash:  a_proof.block_hash,
+        a_round: a_proof.round,
+        b_hash:  b_proof.block_hash,
+        b_round: b_proof.round,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use blst::min_pk::{AggregateSignature, SecretKey};
+    use bytes::Bytes;
+
+    use super::*;
+    use crate::consts::BLS_DST;
+    use crate::keccak_256;
+    use crate::proof::{precommit_vote, vote_hash};
+    use crate::static_verify::proposal_rlp;
+    use crate::types::tests::{mock_header, mock_validator};
+    use crate::types::Hex;
+
+    fn block(number: u64) -> AxonBlock {
+        let mut header = mock_header();
+        header.number = number;
+        AxonBlock {
+            header,
+            tx_hashes: vec![],
+        }
+    }
+
+    /// `block` with a proof signed in `round` by the first `sks.len()`
+    /// validators.
+    fn finalize(block: &AxonBlock, round: u64, sks: &[SecretKey]) -> (AxonBlock, Proof) {
+        let mut proof = Proof {
+            number: block.header.number,
+            round,
+            block_hash: H256(keccak_256(&proposal_rlp(block, H256::zero()).unwrap())),
+            bitmap: Bytes::from(vec![0b1111_0000u8 << (4 - sks.len())]),
+            ..Default::default()
+        };
+        let message = vote_hash(&precommit_vote(&proof));
+        let sigs = sks
+            .iter()
+            .map(|sk| sk.sign(&message, BLS_DST.as_bytes(), &[]))
+            .collect::<Vec<_>>();
+        let sigs = sigs.iter().collect::<Vec<_>>();
+        let sig = AggregateSignature::aggregate(&sigs, true).unwrap().to_signature();
+        proof.signature = Bytes::from(sig.to_bytes().to_vec());
+        (block.clone(), proof)
+    }
+
+    #[test]
+    fn test_detect_conflict() {
+        let sks = (1..=4u8)
+            .map(|i| SecretKey::key_gen(&[i; 32], &[]).unwrap())
+            .collect::<Vec<_>>();
+        // `pub_key` orders the validators as `sks`.
+        let validators = sks
+            .iter()
+            .zip(1..=4u8)
+            .map(|(sk, i)| ValidatorExtend {
+                bls_pub_key: Hex::encode(sk.sk_to_pk().to_bytes()),
+                ..mock_validator(i)
+            })
+            .collect::<Vec<_>>();
+        let roots = (H256::zero(), H256::zero());
+
+        let a = finalize(&block(10), 0, &sks);
+        let b = finalize(&block(10), 1, &sks);
+        let conflict = detect_conflict(&a, &b, roots, &validators).unwrap();
+        assert!(conflict.is_conflicting());
+        assert_eq!(conflict, Conflict::Conflicting {
+            number:  10,
+            a_hash:  a.1.block_hash,
+            a_round: 0,
+            b_hash:  b.1.block_hash,
+            b_round: 1,
+        });
+
+        let a_again = finalize(&a.0, 2, &sks);
+        let conflict = detect_conflict(&a, &a_again, roots, &validators).unwrap();
+        assert!(!conflict.is_conflicting());
+        assert_eq!(conflict, Conflict::SameBlock {
+            number: 10,
+            hash:   a.1.block_hash,
+        });
+
+        let c = finalize(&block(11), 0, &sks);
+        assert_eq!(
+            detect_conflict(&a, &c, roots, &validators).unwrap(),
+            Conflict::DifferentHeight { a: 10, b: 11 }
+        );
+
+        // A block signed by a single validator proves nothing.
+        let unproven = finalize(&block(10), 0, &sks[..1]);
+        assert!(matches!(
+            detect_conflict(&a, &unproven, roots, &validators),
+            Err(Error::NotEnoughSignatures)
+        ));
+        assert!(detect_conflict(&unproven, &a, roots, &validators).is_err());
+    }
+}
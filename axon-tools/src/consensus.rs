@@ -0,0 +1,293 @@
+//! Overlord consensus messages other than the precommit `Vote` already used by
+//! proof verification. The RLP layout of each type is the one Overlord puts on
+//! the wire, so captured consensus traffic can be decoded with this crate.
+
+use alloc::vec::Vec;
+
+use bytes::Bytes;
+use rlp::{Decodable, DecoderError, Encodable, Rlp, RlpStream};
+
+use crate::types::{Vote, VoteType};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SignedVote {
+    pub signature: Bytes,
+    pub vote:      Vote,
+    pub voter:     Bytes,
+}
+
+impl Encodable for SignedVote {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(3)
+            .append(&self.signature.to_vec())
+            .append(&self.vote)
+            .append(&self.voter.to_vec());
+    }
+}
+
+impl Decodable for SignedVote {
+    fn decode(r: &Rlp) -> Result<Self, DecoderError> {
+        Ok(SignedVote {
+            signature: Bytes::from(r.val_at::<Vec<u8>>(0)?),
+            vote:      r.val_at(1)?,
+            voter:     Bytes::from(r.val_at::<Vec<u8>>(2)?),
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AggregatedSignature {
+    pub signature:      Bytes,
+    pub address_bitmap: Bytes,
+}
+
+impl Encodable for AggregatedSignature {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(2)
+            .append(&self.signature.to_vec())
+            .append(&self.address_bitmap.to_vec());
+    }
+}
+
+impl Decodable for AggregatedSignature {
+    fn decode(r: &Rlp) -> Result<Self, DecoderError> {
+        Ok(AggregatedSignature {
+            signature:      Bytes::from(r.val_at::<Vec<u8>>(0)?),
+            address_bitmap: Bytes::from(r.val_at::<Vec<u8>>(1)?),
+        })
+    }
+}
+
+/// A quorum certificate of prevotes or precommits.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AggregatedVote {
+    pub signature:  AggregatedSignature,
+    pub vote_type:  VoteType,
+    pub height:     u64,
+    pub round:      u64,
+    pub block_hash: Bytes,
+    pub leader:     Bytes,
+}
+
+impl Encodable for AggregatedVote {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(6)
+            .append(&self.signature)
+            .append(&self.vote_type)
+            .append(&self.height)
+            .append(&self.round)
+            .append(&self.block_hash.to_vec())
+            .append(&self.leader.to_vec());
+    }
+}
+
+impl Decodable for AggregatedVote {
+    fn decode(r: &Rlp) -> Result<Self, DecoderError> {
+        Ok(AggregatedVote {
+            signature:  r.val_at(0)?,
+            vote_type:  r.val_at(1)?,
+            height:     r.val_at(2)?,
+            round:      r.val_at(3)?,
+            block_hash: Bytes::from(r.val_at::<Vec<u8>>(4)?),
+            leader:     Bytes::from(r.val_at::<Vec<u8>>(5)?),
+        })
+    }
+}
+
+/// Proof of lock on a block: the round it was locked in and the prevote QC
+/// that caused the lock.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PoLC {
+    pub lock_round: u64,
+    pub lock_votes: AggregatedVote,
+}
+
+impl Encodable for PoLC {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(2)
+            .append(&self.lock_round)
+            .append(&self.lock_votes);
+    }
+}
+
+impl Decodable for PoLC {
+    fn decode(r: &Rlp) -> Result<Self, DecoderError> {
+        Ok(PoLC {
+            lock_round: r.val_at(0)?,
+            lock_votes: r.val_at(1)?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AggregatedChoke {
+    pub height:    u64,
+    pub round:     u64,
+    pub signature: Bytes,
+    pub voters:    Vec<Bytes>,
+}
+
+impl Encodable for AggregatedChoke {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        let voters = self.voters.iter().map(|v| v.to_vec()).collect::<Vec<_>>();
+        s.begin_list(4)
+            .append(&self.height)
+            .append(&self.round)
+            .append(&self.signature.to_vec())
+            .append_list::<Vec<u8>, _>(&voters);
+    }
+}
+
+impl Decodable for AggregatedChoke {
+    fn decode(r: &Rlp) -> Result<Self, DecoderError> {
+        let voters: Vec<Vec<u8>> = r.list_at(3)?;
+        Ok(AggregatedChoke {
+            height:    r.val_at(0)?,
+            round:     r.val_at(1)?,
+            signature: Bytes::from(r.val_at::<Vec<u8>>(2)?),
+            voters:    voters.into_iter().map(Bytes::from).collect(),
+        })
+    }
+}
+
+/// The certificate a node carries in its choke to justify the round it is in.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum UpdateFrom {
+    PrevoteQC(AggregatedVote),
+    PrecommitQC(AggregatedVote),
+    ChokeQC(AggregatedChoke),
+}
+
+impl Encodable for UpdateFrom {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        match self {
+            UpdateFrom::PrevoteQC(qc) => s.begin_list(2).append(&0u8).append(qc),
+            UpdateFrom::PrecommitQC(qc) => s.begin_list(2).append(&1u8).append(qc),
+            UpdateFrom::ChokeQC(qc) => s.begin_list(2).append(&2u8).append(qc),
+        };
+    }
+}
+
+impl Decodable for UpdateFrom {
+    fn decode(r: &Rlp) -> Result<Self, DecoderError> {
+        match r.val_at::<u8>(0)? {
+            0 => Ok(UpdateFrom::PrevoteQC(r.val_at(1)?)),
+            1 => Ok(UpdateFrom::PrecommitQC(r.val_at(1)?)),
+            2 => Ok(UpdateFrom::ChokeQC(r.val_at(1)?)),
+            _ => Err(DecoderError::Custom("Invalid update from type")),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Choke {
+    pub height: u64,
+    pub round:  u64,
+    pub from:   UpdateFrom,
+}
+
+impl Encodable for Choke {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(3)
+            .append(&self.height)
+            .append(&self.round)
+            .append(&self.from);
+    }
+}
+
+impl Decodable for Choke {
+    fn decode(r: &Rlp) -> Result<Self, DecoderError> {
+        Ok(Choke {
+            height: r.val_at(0)?,
+            round:  r.val_at(1)?,
+            from:   r.val_at(2)?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SignedChoke {
+    pub signature: Bytes,
+    pub choke:     Choke,
+    pub address:   Bytes,
+}
+
+impl Encodable for SignedChoke {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(3)
+            .append(&self.signature.to_vec())
+            .append(&self.choke)
+            .append(&self.address.to_vec());
+    }
+}
+
+impl Decodable for SignedChoke {
+    fn decode(r: &Rlp) -> Result<Self, DecoderError> {
+        Ok(SignedChoke {
+            signature: Bytes::from(r.val_at::<Vec<u8>>(0)?),
+            choke:     r.val_at(1)?,
+            address:   Bytes::from(r.val_at::<Vec<u8>>(2)?),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn random_bytes(len: usize) -> Bytes {
+        (0..len).map(|_| rand::random()).collect::<Vec<u8>>().into()
+    }
+
+    fn random_qc() -> AggregatedVote {
+        AggregatedVote {
+            signature:  AggregatedSignature {
+                signature:      random_bytes(96),
+                address_bitmap: random_bytes(1),
+            },
+            vote_type:  VoteType::Prevote,
+            height:     rand::random(),
+            round:      rand::random(),
+            block_hash: random_bytes(32),
+            leader:     random_bytes(20),
+        }
+    }
+
+    #[test]
+    fn test_aggregated_vote_codec() {
+        let qc = random_qc();
+        let raw = rlp::encode(&qc);
+        let decoded: overlord::types::AggregatedVote = rlp::decode(&raw).unwrap();
+        assert_eq!(qc.height, decoded.height);
+        assert_eq!(qc.round, decoded.round);
+        assert_eq!(qc.block_hash, decoded.block_hash);
+        assert_eq!(qc.leader, decoded.leader);
+        assert_eq!(qc.signature.signature, decoded.signature.signature);
+        assert_eq!(rlp::decode::<AggregatedVote>(&raw).unwrap(), qc);
+    }
+
+    #[test]
+    fn test_choke_codec() {
+        let choke = Choke {
+            height: rand::random(),
+            round:  rand::random(),
+            from:   UpdateFrom::ChokeQC(AggregatedChoke {
+                height:    rand::random(),
+                round:     rand::random(),
+                signature: random_bytes(96),
+                voters:    vec![random_bytes(20), random_bytes(20)],
+            }),
+        };
+        let raw = rlp::encode(&choke);
+        let decoded: overlord::types::Choke = rlp::decode(&raw).unwrap();
+        assert_eq!(choke.height, decoded.height);
+        assert_eq!(choke.round, decoded.round);
+        assert_eq!(rlp::decode::<Choke>(&raw).unwrap(), choke);
+
+        let polc = PoLC {
+            lock_round: rand::random(),
+            lock_votes: random_qc(),
+        };
+        assert_eq!(rlp::decode::<PoLC>(&rlp::encode(&polc)).unwrap(), polc);
+    }
+}
@@ -1,4 +1,4 @@
-use ethereum_types::H160;
+use ethereum_types::{H160, H256};
 
 pub const METADATA_CONTRACT_ADDRESS: H160 = H160([
     0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
@@ -14,3 +14,46 @@ pub const IMAGE_CELL_CONTRACT_ADDRESS: H160 = H160([
     0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
     0xff, 0xff, 0xff, 0x03,
 ]);
+
+/// Keccak-256 of the empty byte string, the code hash of accounts without code.
+pub const KECCAK_EMPTY: H256 = H256([
+    0xc5, 0xd2, 0x46, 0x01, 0x86, 0xf7, 0x23, 0x3c, 0x92, 0x7e, 0x7d, 0xb2, 0xdc, 0xc7, 0x03, 0xc0,
+    0xe5, 0x00, 0xb6, 0x53, 0xca, 0x82, 0x27, 0x3b, 0x7b, 0xfa, 0xd8, 0x04, 0x5d, 0x85, 0xa4, 0x70,
+]);
+
+/// Keccak-256 of the RLP empty list, the ommers hash of a block without uncles.
+pub const EMPTY_UNCLE_HASH: H256 = H256([
+    0x1d, 0xcc, 0x4d, 0xe8, 0xde, 0xc7, 0x5d, 0x7a, 0xab, 0x85, 0xb5, 0x67, 0xb6, 0xcc, 0xd4, 0x1a,
+    0xd3, 0x12, 0x45, 0x1b, 0x94, 0x8a, 0x74, 0x13, 0xf0, 0xa1, 0x42, 0xfd, 0x40, 0xd4, 0x93, 0x47,
+]);
+
+/// Root of an empty Merkle Patricia trie.
+pub const EMPTY_TRIE_ROOT: H256 = H256([
+    0x56, 0xe8, 0x1f, 0x17, 0x1b, 0xcc, 0x55, 0xa6, 0xff, 0x83, 0x45, 0xe6, 0x92, 0xc0, 0xf8, 0x6e,
+    0x5b, 0x48, 0xe0, 0x1b, 0x99, 0x6c, 0xad, 0xc0, 0x01, 0x62, 0x2f, 0xb5, 0xe3, 0x63, 0xb4, 0x21,
+]);
+
+/// The BLS domain separation tag validators sign votes with.
+pub const BLS_DST: &str = "BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RONUL";
+
+/// Accepted lengths of a BLS signature in G2, compressed and uncompressed.
+pub const BLS_SIGNATURE_LENS: [usize; 2] = [96, 192];
+
+/// Accepted lengths of a BLS public key in G1, compressed and uncompressed.
+pub const BLS_PUBLIC_KEY_LENS: [usize; 2] = [48, 96];
+
+/// The longest bitmap a proof may carry, enough for 256 validators.
+pub const MAX_BITMAP_LEN: usize = 32;
+
+/// Rounds above this are rejected as nonsensical by `Proof::validate`.
+pub const MAX_PROOF_ROUND: u64 = u32::MAX as u64;
+
+/// The base fee of every Axon block, in wei, and the floor of the base fee
+/// under `BaseFeeRule::Eip1559`.
+pub const MIN_BASE_FEE_PER_GAS: u64 = 0x539;
+
+/// EIP-1559 bound on the base fee change between two blocks.
+pub const BASE_FEE_MAX_CHANGE_DENOMINATOR: u64 = 8;
+
+/// EIP-1559 ratio between the gas limit and the gas target.
+pub const ELASTICITY_MULTIPLIER: u64 = 2;
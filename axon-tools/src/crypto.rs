@@ -0,0 +1,64 @@
+//! The signature scheme behind proof verification. Axon validators sign with
+//! BLS12-381 in the min-pk setting, [`BlsMinPk`], but deployments trying
+//! another curve or a threshold scheme can implement [`ConsensusCrypto`] and
+//! still reuse the proposal, vote and quorum checks of this crate through
+//! [`crate::verify_proof_with_crypto`].
+
+use crate::consts::BLS_DST;
+use crate::proof::{check_public_key_length, check_signature_length};
+use crate::{verify_aggregate_signature, Error};
+
+pub trait ConsensusCrypto {
+    /// Cheap format checks of the proof signature and of every validator's
+    /// public key, run before anything is hashed so that malformed input is
+    /// reported as such. An invalid key is reported with its index in
+    /// `pubkeys`. Accepts everything by default.
+    fn check_lengths(&self, _signature: &[u8], _pubkeys: &[&[u8]]) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Verify that `signature` is an aggregate over `message` by exactly the
+    /// holders of `pubkeys`. An invalid key should be reported as
+    /// `Error::InvalidPublicKey` with its index in `pubkeys`.
+    fn verify_aggregate(
+        &self,
+        message: &[u8],
+        signature: &[u8],
+        pubkeys: &[&[u8]],
+    ) -> Result<(), Error>;
+
+    /// The domain separation tag if this is BLS12-381 min-pk as Axon signs
+    /// it, under that tag. A [`crate::validator_set::ValidatorSet`] then
+    /// verifies with the keys it parsed once rather than calling
+    /// [`ConsensusCrypto::verify_aggregate`]. `None` by default.
+    fn bls_dst(&self) -> Option<&[u8]> {
+        None
+    }
+}
+
+/// BLS12-381 with public keys in G1, as Axon validators sign.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BlsMinPk;
+
+impl ConsensusCrypto for BlsMinPk {
+    fn check_lengths(&self, signature: &[u8], pubkeys: &[&[u8]]) -> Result<(), Error> {
+        check_signature_length(signature)?;
+        for (index, pubkey) in pubkeys.iter().enumerate() {
+            check_public_key_length(index, pubkey)?;
+        }
+        Ok(())
+    }
+
+    fn verify_aggregate(
+        &self,
+        message: &[u8],
+        signature: &[u8],
+        pubkeys: &[&[u8]],
+    ) -> Result<(), Error> {
+        verify_aggregate_signature(message, signature, pubkeys)
+    }
+
+    fn bls_dst(&self) -> Option<&[u8]> {
+        Some(BLS_DST.as_bytes())
+    }
+}
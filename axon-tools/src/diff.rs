@@ -0,0 +1,189 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::types::{AxonHeader, Hex, ValidatorExtend};
+#[cfg(feature = "proof")]
+use crate::types::Proposal;
+
+/// A single field whose value differs between the two compared items. The
+/// values are rendered with their `Debug` representation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FieldDiff {
+    pub field: &'static str,
+    pub left:  String,
+    pub right: String,
+}
+
+macro_rules! diff_fields {
+    ($diffs: expr, $left: expr, $right: expr, $($field: ident),+) => {
+        $(
+            if $left.$field != $right.$field {
+                $diffs.push(FieldDiff {
+                    field: stringify!($field),
+                    left:  alloc::format!("{:?}", $left.$field),
+                    right: alloc::format!("{:?}", $right.$field),
+                });
+            }
+        )+
+    };
+}
+
+/// Compare two headers field by field and return every mismatch in
+/// declaration order.
+pub fn diff_headers(left: &AxonHeader, right: &AxonHeader) -> Vec<FieldDiff> {
+    let mut diffs = Vec::new();
+    diff_fields!(
+        diffs,
+        left,
+        right,
+        version,
+        prev_hash,
+        proposer,
+        state_root,
+        transactions_root,
+        signed_txs_hash,
+        receipts_root,
+        log_bloom,
+        timestamp,
+        number,
+        gas_used,
+        gas_limit,
+        extra_data,
+        base_fee_per_gas,
+        proof,
+        call_system_script_count,
+        chain_id
+    );
+    diffs
+}
+
+/// Compare a header with a proposal, e.g. one decoded from another
+/// implementation, on the fields that make up the proposal hash. This is the
+/// first thing to look at when `verify_proof` returns
+/// `InvalidProofBlockHash`.
+#[cfg(feature = "proof")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "proof")))]
+pub fn diff_header_proposal(header: &AxonHeader, proposal: &Proposal) -> Vec<FieldDiff> {
+    let mut diffs = Vec::new();
+    diff_fields!(
+        diffs,
+        header,
+        proposal,
+        version,
+        prev_hash,
+        proposer,
+        transactions_root,
+        signed_txs_hash,
+        timestamp,
+        number,
+        gas_limit,
+        extra_data,
+        base_fee_per_gas,
+        proof,
+        chain_id,
+        call_system_script_count
+    );
+    diffs
+}
+
+/// A validator present in both sets whose weights changed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WeightChange {
+    pub pub_key:            Hex,
+    pub old_propose_weight: u32,
+    pub new_propose_weight: u32,
+    pub old_vote_weight:    u32,
+    pub new_vote_weight:    u32,
+}
+
+/// How a validator set changed between two epochs. Each list is in bitmap
+/// order, i.e. sorted by public key.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ValidatorSetDiff {
+    pub added:          Vec<ValidatorExtend>,
+    pub removed:        Vec<ValidatorExtend>,
+    pub weight_changes: Vec<WeightChange>,
+}
+
+impl ValidatorSetDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.weight_changes.is_empty()
+    }
+
+    /// The number of validators that joined or left.
+    pub fn churn(&self) -> usize {
+        self.added.len() + self.removed.len()
+    }
+}
+
+/// Compare two validator sets, matching validators by public key. The input
+/// order does not matter.
+pub fn diff_validators(old: &[ValidatorExtend], new: &[ValidatorExtend]) -> ValidatorSetDiff {
+    let mut old = old.to_vec();
+    let mut new = new.to_vec();
+    old.sort();
+    new.sort();
+
+    let mut diff = ValidatorSetDiff::default();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        match (old.get(i), new.get(j)) {
+            (Some(o), Some(n)) if o.cmp(n).is_eq() => {
+                if o.propose_weight != n.propose_weight || o.vote_weight != n.vote_weight {
+                    diff.weight_changes.push(WeightChange {
+                        pub_key:            o.pub_key.clone(),
+                        old_propose_weight: o.propose_weight,
+                        new_propose_weight: n.propose_weight,
+                        old_vote_weight:    o.vote_weight,
+                        new_vote_weight:    n.vote_weight,
+                    });
+                }
+                i += 1;
+                j += 1;
+            }
+            (Some(o), Some(n)) if o < n => {
+                diff.removed.push(o.clone());
+                i += 1;
+            }
+            (Some(o), None) => {
+                diff.removed.push(o.clone());
+                i += 1;
+            }
+            (_, Some(n)) => {
+                diff.added.push(n.clone());
+                j += 1;
+            }
+            (None, None) => unreachable!(),
+        }
+    }
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::tests::mock_validator;
+
+    fn validator(seed: u8, vote_weight: u32) -> ValidatorExtend {
+        ValidatorExtend {
+            vote_weight,
+            ..mock_validator(seed)
+        }
+    }
+
+    #[test]
+    fn test_diff_validators() {
+        let old = vec![validator(3, 1), validator(1, 1), validator(2, 1)];
+        let new = vec![validator(4, 1), validator(2, 5), validator(1, 1)];
+
+        let diff = diff_validators(&old, &new);
+        assert_eq!(diff.added, vec![validator(4, 1)]);
+        assert_eq!(diff.removed, vec![validator(3, 1)]);
+        assert_eq!(diff.weight_changes.len(), 1);
+        assert_eq!(diff.weight_changes[0].pub_key, Hex::encode([2u8; 33]));
+        assert_eq!(diff.weight_changes[0].new_vote_weight, 5);
+        assert_eq!(diff.churn(), 2);
+
+        assert!(diff_validators(&old, &old).is_empty());
+    }
+}
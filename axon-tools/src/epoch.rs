@@ -0,0 +1,60 @@
+//! Conversions between block numbers, epochs and metadata versions.
+//!
+//! Axon's genesis metadata covers blocks `1..=epoch_len` as epoch 0, so epoch
+//! `e` covers `e * epoch_len + 1..=(e + 1) * epoch_len`. The genesis block
+//! itself is counted in epoch 0. All helpers panic if `epoch_len` is zero.
+
+use crate::types::{BlockNumber, MetadataVersion};
+
+/// The epoch a block belongs to.
+pub fn epoch_of(number: BlockNumber, epoch_len: u64) -> u64 {
+    number.saturating_sub(1) / epoch_len
+}
+
+/// The first block of an epoch.
+pub fn first_block_of_epoch(epoch: u64, epoch_len: u64) -> BlockNumber {
+    epoch * epoch_len + 1
+}
+
+/// The last block of an epoch.
+pub fn last_block_of_epoch(epoch: u64, epoch_len: u64) -> BlockNumber {
+    (epoch + 1) * epoch_len
+}
+
+/// The metadata version in effect during an epoch.
+pub fn metadata_version_of_epoch(epoch: u64, epoch_len: u64) -> MetadataVersion {
+    MetadataVersion::new(
+        first_block_of_epoch(epoch, epoch_len),
+        last_block_of_epoch(epoch, epoch_len),
+    )
+}
+
+/// Whether a block is the last one of its epoch, i.e. the next block is
+/// verified against a new metadata.
+pub fn is_last_block_of_epoch(number: BlockNumber, epoch_len: u64) -> bool {
+    number != 0 && number == last_block_of_epoch(epoch_of(number, epoch_len), epoch_len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_epoch_arithmetic() {
+        let len = 100;
+        assert_eq!(epoch_of(0, len), 0);
+        assert_eq!(epoch_of(1, len), 0);
+        assert_eq!(epoch_of(100, len), 0);
+        assert_eq!(epoch_of(101, len), 1);
+        assert_eq!(first_block_of_epoch(1, len), 101);
+        assert_eq!(last_block_of_epoch(1, len), 200);
+        assert!(metadata_version_of_epoch(2, len).contains(250));
+        assert!(is_last_block_of_epoch(200, len));
+        assert!(!is_last_block_of_epoch(0, len));
+
+        for number in 1..1000 {
+            let epoch = epoch_of(number, len);
+            assert!(metadata_version_of_epoch(epoch, len).contains(number));
+        }
+    }
+}
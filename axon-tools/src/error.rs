@@ -1,4 +1,4 @@
-use std::fmt::{self, Display};
+use core::fmt::{self, Display};
 
 #[allow(dead_code)]
 #[derive(Debug)]
@@ -7,6 +7,51 @@ pub enum Error {
     NotEnoughSignatures,
     VerifyMptProof,
     HexPrefix,
+    InvalidProposer,
+    TooManyValidators,
+    BufferTooSmall,
+    ParentHashMismatch,
+    ReceiptBlockMismatch,
+    ReceiptsRootMismatch,
+    LogBloomMismatch,
+    /// The receipt at `index` does not convert to a consensus receipt.
+    InvalidReceipt {
+        index: usize,
+        error: crate::types::TypesError,
+    },
+    DuplicateValidator(ethereum_types::H160),
+    InvalidSignatureLength(usize),
+    /// The signers hold less than the required vote weight.
+    NotEnoughVoteWeight {
+        signed:   u64,
+        required: u64,
+    },
+    /// The validator at `index` is not after the previous one in the order
+    /// the list was expected in.
+    ValidatorsOutOfOrder {
+        index: usize,
+    },
+    /// No metadata covers the block at this height.
+    MetadataNotFound(u64),
+    /// More than one metadata covers the block at this height.
+    OverlappingMetadata(u64),
+    /// The block gas limit does not fit in the `u64` the proposal encodes.
+    GasLimitOverflow,
+    /// The bitmap is not `ceil(validator_count / 8)` bytes long.
+    BitmapLengthMismatch {
+        expect: usize,
+        actual: usize,
+    },
+    /// The bitmap sets a bit past the last validator.
+    BitmapTrailingBits,
+    /// The `index`-th public key is neither a compressed nor an uncompressed
+    /// BLS public key.
+    InvalidPublicKeyLength {
+        index: usize,
+        len:   usize,
+    },
+    /// A value is longer than the fixed capacity it is copied into.
+    CapacityExceeded,
 
     #[cfg(feature = "hex")]
     #[cfg_attr(doc_cfg, doc(cfg(feature = "hex")))]
@@ -16,8 +61,25 @@ pub enum Error {
     #[cfg_attr(doc_cfg, doc(cfg(feature = "proof")))]
     Bls(blst::BLST_ERROR),
 
+    /// The `index`-th of the public keys passed to
+    /// `verify_aggregate_signature` is invalid.
     #[cfg(feature = "proof")]
     #[cfg_attr(doc_cfg, doc(cfg(feature = "proof")))]
+    InvalidPublicKey {
+        index: usize,
+        error: blst::BLST_ERROR,
+    },
+
+    /// The BLS public key of the validator at `address` is invalid.
+    #[cfg(feature = "proof")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "proof")))]
+    InvalidValidatorKey {
+        address: ethereum_types::H160,
+        error:   blst::BLST_ERROR,
+    },
+
+    #[cfg(feature = "trie")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "trie")))]
     Trie(cita_trie::TrieError),
 }
 
@@ -37,8 +99,8 @@ impl From<blst::BLST_ERROR> for Error {
     }
 }
 
-#[cfg(feature = "proof")]
-#[cfg_attr(doc_cfg, doc(cfg(feature = "proof")))]
+#[cfg(feature = "trie")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "trie")))]
 impl From<cita_trie::TrieError> for Error {
     fn from(e: cita_trie::TrieError) -> Self {
         Self::Trie(e)
@@ -52,11 +114,54 @@ impl Display for Error {
             Error::NotEnoughSignatures => write!(f, "Not enough signatures"),
             Error::VerifyMptProof => write!(f, "Verify mpt proof"),
             Error::HexPrefix => write!(f, "Hex prefix"),
+            Error::InvalidProposer => write!(f, "Proposer is not a validator"),
+            Error::TooManyValidators => write!(f, "Too many validators"),
+            Error::BufferTooSmall => write!(f, "Buffer too small"),
+            Error::ParentHashMismatch => write!(f, "Parent header hash mismatch"),
+            Error::ReceiptBlockMismatch => write!(f, "Receipt of another block"),
+            Error::ReceiptsRootMismatch => write!(f, "Receipts root mismatch"),
+            Error::LogBloomMismatch => write!(f, "Log bloom mismatch"),
+            Error::InvalidReceipt { index, error } => {
+                write!(f, "Invalid receipt at index {}: {}", index, error)
+            }
+            Error::DuplicateValidator(address) => write!(f, "Duplicate validator {:?}", address),
+            Error::InvalidSignatureLength(len) => {
+                write!(f, "Invalid signature length {}, expect 96 or 192", len)
+            }
+            Error::NotEnoughVoteWeight { signed, required } => {
+                write!(f, "Not enough vote weight {}, expect {}", signed, required)
+            }
+            Error::ValidatorsOutOfOrder { index } => {
+                write!(f, "Validator at index {} is out of order", index)
+            }
+            Error::MetadataNotFound(number) => write!(f, "No metadata covers block {}", number),
+            Error::OverlappingMetadata(number) => {
+                write!(f, "Several metadata cover block {}", number)
+            }
+            Error::GasLimitOverflow => write!(f, "Gas limit overflows u64"),
+            Error::BitmapLengthMismatch { expect, actual } => {
+                write!(f, "Bitmap length {}, expect {}", actual, expect)
+            }
+            Error::BitmapTrailingBits => write!(f, "Bitmap sets bits past the last validator"),
+            Error::InvalidPublicKeyLength { index, len } => write!(
+                f,
+                "Invalid public key length {} at index {}, expect 48 or 96",
+                len, index
+            ),
+            Error::CapacityExceeded => write!(f, "Value exceeds its fixed capacity"),
             #[cfg(feature = "hex")]
             Error::Hex(e) => write!(f, "Hex error: {:?}", e),
             #[cfg(feature = "proof")]
             Error::Bls(e) => write!(f, "Bls error: {:?}", e),
             #[cfg(feature = "proof")]
+            Error::InvalidPublicKey { index, error } => {
+                write!(f, "Invalid public key at index {}: {:?}", index, error)
+            }
+            #[cfg(feature = "proof")]
+            Error::InvalidValidatorKey { address, error } => {
+                write!(f, "Invalid public key of validator {:?}: {:?}", address, error)
+            }
+            #[cfg(feature = "trie")]
             Error::Trie(e) => write!(f, "Trie error: {:?}", e),
         }
     }
@@ -0,0 +1,114 @@
+//! An Ethereum view of `AxonHeader` for EVM tooling that identifies blocks by
+//! `keccak(rlp(header))` in the London layout. The fields are mapped as
+//! follows:
+//!
+//! | Ethereum           | Axon                                      |
+//! |--------------------|-------------------------------------------|
+//! | `parentHash`       | `prev_hash`                               |
+//! | `ommersHash`       | `EMPTY_UNCLE_HASH`, Axon has no uncles    |
+//! | `beneficiary`      | `proposer`                                |
+//! | `stateRoot`        | `state_root`                              |
+//! | `transactionsRoot` | `transactions_root`                       |
+//! | `receiptsRoot`     | `receipts_root`                           |
+//! | `logsBloom`        | `log_bloom`                               |
+//! | `difficulty`       | `0`, Axon has no proof of work            |
+//! | `number`           | `number`                                  |
+//! | `gasLimit`         | `gas_limit`                               |
+//! | `gasUsed`          | `gas_used`                                |
+//! | `timestamp`        | `timestamp`                               |
+//! | `extraData`        | the RLP list of `extra_data`              |
+//! | `mixHash`          | zero                                      |
+//! | `nonce`            | zero                                      |
+//! | `baseFeePerGas`    | `base_fee_per_gas`                        |
+//!
+//! `version`, `signed_txs_hash`, `proof`, `call_system_script_count` and
+//! `chain_id` have no Ethereum counterpart and are left out, so the hash of
+//! this view is not the Axon block hash and must not be used to check a
+//! proof.
+
+use ethereum_types::{H256, H64, U256};
+use rlp::{Encodable, RlpStream};
+
+use crate::consts::EMPTY_UNCLE_HASH;
+use crate::types::AxonHeader;
+
+/// Encodes the wrapped header in the Ethereum layout described above.
+#[derive(Clone, Copy, Debug)]
+pub struct EthHeaderView<'a>(pub &'a AxonHeader);
+
+impl<'a> EthHeaderView<'a> {
+    pub fn new(header: &'a AxonHeader) -> Self {
+        EthHeaderView(header)
+    }
+
+    /// `keccak(rlp(header))` of the Ethereum layout.
+    #[cfg(feature = "hash")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "hash")))]
+    pub fn hash(&self) -> H256 {
+        H256(crate::keccak_256(&self.rlp_bytes()))
+    }
+}
+
+impl Encodable for EthHeaderView<'_> {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        let header = self.0;
+        s.begin_list(16)
+            .append(&header.prev_hash)
+            .append(&EMPTY_UNCLE_HASH)
+            .append(&header.proposer)
+            .append(&header.state_root)
+            .append(&header.transactions_root)
+            .append(&header.receipts_root)
+            .append(&header.log_bloom)
+            .append(&U256::zero())
+            .append(&header.number)
+            .append(&header.gas_limit)
+            .append(&header.gas_used)
+            .append(&header.timestamp)
+            .append(&rlp::encode_list(&header.extra_data).to_vec())
+            .append(&H256::zero())
+            .append(&H64::zero())
+            .append(&header.base_fee_per_gas);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rlp::Rlp;
+
+    use super::*;
+    use crate::types::tests::mock_header;
+    use crate::types::ExtraData;
+
+    #[test]
+    fn test_eth_header_layout() {
+        let mut header = mock_header();
+        header.extra_data = vec![ExtraData {
+            inner: vec![1, 2, 3].into(),
+        }];
+        let bytes = EthHeaderView::new(&header).rlp_bytes();
+        let rlp = Rlp::new(&bytes);
+
+        assert_eq!(rlp.item_count().unwrap(), 16);
+        assert_eq!(rlp.val_at::<H256>(0).unwrap(), header.prev_hash);
+        assert_eq!(rlp.val_at::<H256>(1).unwrap(), EMPTY_UNCLE_HASH);
+        assert_eq!(rlp.val_at::<u64>(8).unwrap(), header.number);
+        assert_eq!(rlp.val_at::<U256>(15).unwrap(), header.base_fee_per_gas);
+
+        let extra_data = rlp.val_at::<Vec<u8>>(12).unwrap();
+        assert_eq!(rlp::decode_list::<ExtraData>(&extra_data), header.extra_data);
+    }
+
+    #[cfg(feature = "hash")]
+    #[test]
+    fn test_eth_hash_ignores_axon_only_fields() {
+        let header = mock_header();
+        let mut other = header.clone();
+        other.chain_id += 1;
+        other.proof.round += 1;
+        assert_eq!(EthHeaderView(&header).hash(), EthHeaderView(&other).hash());
+
+        other.number += 1;
+        assert_ne!(EthHeaderView(&header).hash(), EthHeaderView(&other).hash());
+    }
+}
@@ -0,0 +1,284 @@
+//! Why [`crate::verify_proof`] rejects a proof, in terms someone debugging
+//! a relayer can act on: the two hashes that differ, the quorum that was
+//! missed, the validator whose key is bad. Each step of verification is
+//! replayed in order and the first failure is explained.
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use bit_vec::BitVec;
+use blst::min_pk::PublicKey;
+use ethereum_types::{H160, H256};
+
+use crate::proof::{
+    check_public_key_length, check_signature_length, precommit_vote, vote_hash, weigh_signers,
+};
+use crate::static_verify::proposal_rlp;
+use crate::types::{find_duplicate_validator, AxonBlock, Proof, ValidatorExtend};
+use crate::validator_set::ValidatorOrder;
+use crate::{
+    check_bitmap, keccak_256, quorum_weight, signers_from_bitmap, verify_aggregate_signature,
+    Error, WeightedQuorum,
+};
+
+#[derive(Debug)]
+pub enum FailureReason {
+    /// The proposal rebuilt from the block does not hash to the one the
+    /// proof commits to. The block, the previous state root or the proof is
+    /// not the one expected.
+    ProposalHashMismatch {
+        computed:  H256,
+        committed: H256,
+    },
+    /// Fewer validators signed than [`crate::verify_proof`] requires.
+    /// `quorum` is the vote weight behind the signers, to tell whether
+    /// [`crate::verify_proof_weighted`] would have accepted them.
+    InsufficientQuorum {
+        signers:  u64,
+        required: u64,
+        quorum:   WeightedQuorum,
+    },
+    /// The BLS public key of the validator at `index`, in bitmap order, is
+    /// malformed or not a valid point.
+    InvalidPublicKey {
+        index:   usize,
+        address: H160,
+        error:   Error,
+    },
+    /// Everything checks out but the aggregate signature.
+    InvalidSignature(Error),
+    /// The input is malformed in a way the error describes.
+    Malformed(Error),
+}
+
+impl fmt::Display for FailureReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FailureReason::ProposalHashMismatch { computed, committed } => write!(
+                f,
+                "proposal hash mismatch: computed {:?}, proof commits to {:?}",
+                computed, committed
+            ),
+            FailureReason::InsufficientQuorum {
+                signers,
+                required,
+                quorum,
+            } => write!(
+                f,
+                "insufficient quorum: {} signers, {} required, {} of {} vote weight",
+                signers, required, quorum.signed_weight, quorum.total_weight
+            ),
+            FailureReason::InvalidPublicKey {
+                index,
+                address,
+                error,
+            } => write!(
+                f,
+                "invalid public key at index {} ({:?}): {}",
+                index, address, error
+            ),
+            FailureReason::InvalidSignature(e) => write!(f, "invalid signature: {}", e),
+            FailureReason::Malformed(e) => write!(f, "malformed input: {}", e),
+        }
+    }
+}
+
+/// Explain why [`crate::verify_proof`] rejects `proof` for `block`, `None`
+/// if it accepts it.
+pub fn explain_proof_failure(
+    block: &AxonBlock,
+    previous_state_root: H256,
+    proof: &Proof,
+    validator_list: &[ValidatorExtend],
+) -> Option<FailureReason> {
+    let computed = match proposal_rlp(block, previous_state_root) {
+        Ok(rlp) => H256(keccak_256(&rlp)),
+        Err(e) => return Some(FailureReason::Malformed(e)),
+    };
+    if computed != proof.block_hash {
+        return Some(FailureReason::ProposalHashMismatch {
+            computed,
+            committed: proof.block_hash,
+        });
+    }
+
+    if let Err(e) = check_signature_length(&proof.signature) {
+        return Some(FailureReason::Malformed(e));
+    }
+    for (index, v) in validator_list.iter().enumerate() {
+        if let Err(error) = check_public_key_length(index, &v.bls_pub_key) {
+            return Some(FailureReason::InvalidPublicKey {
+                index,
+                address: v.address,
+                error,
+            });
+        }
+    }
+    if let Err(e) = check_bitmap(proof, validator_list.len()) {
+        return Some(FailureReason::Malformed(e));
+    }
+    if let Some(address) = find_duplicate_validator(validator_list) {
+        return Some(FailureReason::Malformed(Error::DuplicateValidator(address)));
+    }
+    if let Err(e) = ValidatorOrder::PubKey.check_sorted(validator_list) {
+        return Some(FailureReason::Malformed(e));
+    }
+
+    let signers = signers_from_bitmap(proof, validator_list);
+    let required = quorum_weight(validator_list.len() as u64);
+    if (signers.len() as u64) < required {
+        return Some(FailureReason::InsufficientQuorum {
+            signers: signers.len() as u64,
+            required,
+            quorum: weigh_signers(&signers, validator_list),
+        });
+    }
+
+    // Only the signers' keys are parsed during verification.
+    let bits = BitVec::from_bytes(&proof.bitmap);
+    for (index, v) in validator_list.iter().enumerate().filter(|(i, _)| bits[*i]) {
+        if let Err(e) = PublicKey::key_validate(&v.bls_pub_key) {
+            return Some(FailureReason::InvalidPublicKey {
+                index,
+                address: v.address,
+                error: Error::Bls(e),
+            });
+        }
+    }
+
+    let pks = signers.iter().map(|v| &v.bls_pub_key[..]).collect::<Vec<_>>();
+    let message = vote_hash(&precommit_vote(proof));
+    verify_aggregate_signature(&message, &proof.signature, &pks)
+        .err()
+        .map(FailureReason::InvalidSignature)
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::*;
+    use crate::types::tests::{mock_header, mock_validator, random_bytes};
+
+    #[test]
+    fn test_explain_proof_failure() {
+        let validators = (1..=4u8).map(mock_validator).collect::<Vec<_>>();
+        let block = AxonBlock {
+            header:    mock_header(),
+            tx_hashes: vec![],
+        };
+        let mut proof = Proof {
+            number:     block.header.number,
+            block_hash: H256(rand::random()),
+            signature:  random_bytes(96),
+            bitmap:     Bytes::from_static(&[0b1100_0000]),
+            ..Default::default()
+        };
+
+        let reason = explain_proof_failure(&block, H256::zero(), &proof, &validators);
+        let computed = H256(keccak_256(&proposal_rlp(&block, H256::zero()).unwrap()));
+        match reason {
+            Some(FailureReason::ProposalHashMismatch {
+                computed: c,
+                committed,
+            }) => assert_eq!((c, committed), (computed, proof.block_hash)),
+            other => panic!("unexpected {:?}", other),
+        }
+
+        proof.block_hash = computed;
+        let reason = explain_proof_failure(&block, H256::zero(), &proof, &validators);
+        match reason {
+            Some(FailureReason::InsufficientQuorum {
+                signers,
+                required,
+                quorum,
+            }) => {
+                assert_eq!((signers, required), (2, 3));
+                assert_eq!((quorum.signed_weight, quorum.total_weight), (2, 4));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+
+        // All keys are invalid points, the first signer is reported.
+        proof.bitmap = Bytes::from_static(&[0b0111_0000]);
+        let reason = explain_proof_failure(&block, H256::zero(), &proof, &validators).unwrap();
+        assert!(reason.to_string().starts_with("invalid public key at index 1"));
+        match reason {
+            FailureReason::InvalidPublicKey { index, address, .. } => {
+                assert_eq!((index, address), (1, H160::repeat_byte(2)))
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+
+        proof.bitmap = Bytes::from_static(&[0b1111_1000]);
+        let reason = explain_proof_failure(&block, H256::zero(), &proof, &validators);
+        assert!(matches!(
+            reason,
+            Some(FailureReason::Malformed(Error::BitmapTrailingBits))
+        ));
+
+        let mut reversed = validators;
+        reversed.reverse();
+        proof.bitmap = Bytes::from_static(&[0b1110_0000]);
+        let reason = explain_proof_failure(&block, H256::zero(), &proof, &reversed);
+        assert!(matches!(
+            reason,
+            Some(FailureReason::Malformed(Error::ValidatorsOutOfOrder { index: 1 }))
+        ));
+    }
+
+    #[test]
+    fn test_explain_signature() {
+        use blst::min_pk::{AggregateSignature, SecretKey};
+
+        use crate::consts::BLS_DST;
+        use crate::types::Hex;
+
+        let sks = (1..=4u8)
+            .map(|i| SecretKey::key_gen(&[i; 32], &[]).unwrap())
+            .collect::<Vec<_>>();
+        // `pub_key` orders the validators as `sks`.
+        let validators = sks
+            .iter()
+            .zip(1..=4u8)
+            .map(|(sk, i)| ValidatorExtend {
+                bls_pub_key: Hex::encode(sk.sk_to_pk().to_bytes()),
+                ..mock_validator(i)
+            })
+            .collect::<Vec<_>>();
+        let block = AxonBlock {
+            header:    mock_header(),
+            tx_hashes: vec![],
+        };
+        let mut proof = Proof {
+            number:     block.header.number,
+            block_hash: H256(keccak_256(&proposal_rlp(&block, H256::zero()).unwrap())),
+            bitmap:     Bytes::from_static(&[0b1110_0000]),
+            ..Default::default()
+        };
+        // The first three validators' signature of the precommit of `proof`.
+        let sign = |proof: &Proof| {
+            let message = vote_hash(&precommit_vote(proof));
+            let sigs = sks[..3]
+                .iter()
+                .map(|sk| sk.sign(&message, BLS_DST.as_bytes(), &[]))
+                .collect::<Vec<_>>();
+            let sigs = sigs.iter().collect::<Vec<_>>();
+            let sig = AggregateSignature::aggregate(&sigs, true).unwrap().to_signature();
+            Bytes::from(sig.to_bytes().to_vec())
+        };
+
+        proof.signature = sign(&proof);
+        assert!(explain_proof_failure(&block, H256::zero(), &proof, &validators).is_none());
+        assert!(crate::verify_proof(&block, H256::zero(), &validators, &proof).is_ok());
+
+        let other_round = Proof {
+            round: proof.round + 1,
+            ..proof.clone()
+        };
+        proof.signature = sign(&other_round);
+        let reason = explain_proof_failure(&block, H256::zero(), &proof, &validators);
+        assert!(matches!(reason, Some(FailureReason::InvalidSignature(_))));
+        assert!(reason.unwrap().to_string().starts_with("invalid signature"));
+    }
+}
@@ -0,0 +1,150 @@
+use ethereum_types::U256;
+
+use crate::consts::{BASE_FEE_MAX_CHANGE_DENOMINATOR, ELASTICITY_MULTIPLIER, MIN_BASE_FEE_PER_GAS};
+use crate::types::AxonHeader;
+
+/// The fee paid by a transaction, `gas_used * effective_gas_price`, or `None`
+/// on overflow.
+pub fn tx_fee(gas_used: U256, effective_gas_price: U256) -> Option<U256> {
+    gas_used.checked_mul(effective_gas_price)
+}
+
+/// The price per gas actually paid by a transaction,
+/// `min(max_fee_per_gas, base_fee + max_priority_fee_per_gas)`. For legacy
+/// transactions pass the gas price as both fee caps.
+pub fn effective_gas_price(
+    max_fee_per_gas: U256,
+    max_priority_fee_per_gas: U256,
+    base_fee_per_gas: U256,
+) -> U256 {
+    max_fee_per_gas.min(base_fee_per_gas.saturating_add(max_priority_fee_per_gas))
+}
+
+/// Add a transaction fee to a running total, or `None` on overflow.
+pub fn add_fee(total: U256, fee: U256) -> Option<U256> {
+    total.checked_add(fee)
+}
+
+/// Sum the fees of a block, or `None` if the total overflows.
+pub fn total_fees<I: IntoIterator<Item = U256>>(fees: I) -> Option<U256> {
+    fees.into_iter().try_fold(U256::zero(), add_fee)
+}
+
+/// Sum `gas_used * effective_gas_price` over `(gas_used, price)` pairs, or
+/// `None` if any product or the total overflows.
+pub fn total_tx_fees<I: IntoIterator<Item = (U256, U256)>>(txs: I) -> Option<U256> {
+    txs.into_iter()
+        .try_fold(U256::zero(), |total, (gas_used, price)| {
+            add_fee(total, tx_fee(gas_used, price)?)
+        })
+}
+
+/// How the base fee changes from a block to the next.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BaseFeeRule {
+    /// Every block charges [`MIN_BASE_FEE_PER_GAS`], as Axon does.
+    #[default]
+    Constant,
+    /// The EIP-1559 update rule, never lower than [`MIN_BASE_FEE_PER_GAS`],
+    /// for chains that adjust their base fee to the gas used.
+    Eip1559,
+}
+
+/// The base fee of the block following `parent` on Axon, whose base fee is
+/// constant.
+pub fn next_base_fee(parent: &AxonHeader) -> U256 {
+    next_base_fee_with(parent, BaseFeeRule::Constant)
+}
+
+/// The base fee of the block following `parent` under `rule`.
+pub fn next_base_fee_with(parent: &AxonHeader, rule: BaseFeeRule) -> U256 {
+    match rule {
+        BaseFeeRule::Constant => U256::from(MIN_BASE_FEE_PER_GAS),
+        BaseFeeRule::Eip1559 => eip1559_base_fee(parent),
+    }
+}
+
+fn eip1559_base_fee(parent: &AxonHeader) -> U256 {
+    let parent_base_fee = parent.base_fee_per_gas;
+    let gas_target = parent.gas_limit / ELASTICITY_MULTIPLIER;
+    let min_base_fee = U256::from(MIN_BASE_FEE_PER_GAS);
+
+    if gas_target.is_zero() || parent.gas_used == gas_target {
+        return parent_base_fee.max(min_base_fee);
+    }
+
+    let next = if parent.gas_used > gas_target {
+        let delta = parent_base_fee.saturating_mul(parent.gas_used - gas_target)
+            / gas_target
+            / BASE_FEE_MAX_CHANGE_DENOMINATOR;
+        parent_base_fee.saturating_add(delta.max(U256::one()))
+    } else {
+        let delta = parent_base_fee.saturating_mul(gas_target - parent.gas_used)
+            / gas_target
+            / BASE_FEE_MAX_CHANGE_DENOMINATOR;
+        parent_base_fee.saturating_sub(delta)
+    };
+
+    next.max(min_base_fee)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fee_overflow() {
+        assert_eq!(
+            tx_fee(U256::from(21000), U256::from(1337)),
+            Some(U256::from(21000 * 1337))
+        );
+        assert_eq!(tx_fee(U256::MAX, U256::from(2)), None);
+        assert_eq!(total_fees(vec![U256::MAX, U256::one()]), None);
+        assert_eq!(
+            total_tx_fees(vec![
+                (U256::from(2), U256::from(3)),
+                (U256::from(4), U256::from(5))
+            ]),
+            Some(U256::from(26))
+        );
+    }
+
+    #[test]
+    fn test_effective_gas_price() {
+        let base_fee = U256::from(1337);
+        assert_eq!(
+            effective_gas_price(U256::from(2000), U256::from(10), base_fee),
+            U256::from(1347)
+        );
+        assert_eq!(
+            effective_gas_price(U256::from(1340), U256::from(10), base_fee),
+            U256::from(1340)
+        );
+        assert_eq!(
+            effective_gas_price(U256::from(1500), U256::from(1500), base_fee),
+            U256::from(1500)
+        );
+    }
+
+    #[test]
+    fn test_next_base_fee() {
+        let mut parent = crate::types::tests::mock_header();
+        parent.gas_limit = U256::from(30_000_000);
+        parent.base_fee_per_gas = U256::from(1_000_000_000u64);
+        let eip1559 = |parent: &AxonHeader| next_base_fee_with(parent, BaseFeeRule::Eip1559);
+
+        parent.gas_used = U256::from(15_000_000);
+        assert_eq!(eip1559(&parent), U256::from(1_000_000_000u64));
+
+        parent.gas_used = U256::from(30_000_000);
+        assert_eq!(eip1559(&parent), U256::from(1_125_000_000u64));
+        assert_eq!(next_base_fee(&parent), U256::from(0x539));
+
+        parent.gas_used = U256::zero();
+        assert_eq!(eip1559(&parent), U256::from(875_000_000u64));
+        assert_eq!(next_base_fee(&parent), U256::from(0x539));
+
+        parent.base_fee_per_gas = U256::from(MIN_BASE_FEE_PER_GAS);
+        assert_eq!(eip1559(&parent), U256::from(MIN_BASE_FEE_PER_GAS));
+    }
+}
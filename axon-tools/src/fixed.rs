@@ -0,0 +1,218 @@
+//! Proof verification for targets without a heap. Validator lists, bitmaps
+//! and keys are `heapless::Vec`s whose capacities are const generics, and the
+//! block is given as the RLP of its proposal, the bytes the proof commits to,
+//! so that nothing is allocated from the inputs to the pairing check. The
+//! crate still links `alloc`, such a target can supply a global allocator
+//! that fails every request.
+//!
+//! The fixed types are built from the usual ones where a heap is available,
+//! with [`fixed_validators`] and [`FixedProof::try_from`].
+
+use ethereum_types::{H160, H256};
+use heapless::Vec;
+
+use crate::consts::{BLS_PUBLIC_KEY_LENS, BLS_SIGNATURE_LENS};
+use crate::proof::{check_bitmap_bytes, check_public_key_length, check_signature_length};
+use crate::static_verify::{aggregate_signers, verify_precommit};
+use crate::types::{Proof, ValidatorExtend};
+use crate::{check_quorum, keccak_256, Error};
+
+/// The longest BLS public key, uncompressed.
+pub const MAX_BLS_PUBLIC_KEY_LEN: usize = BLS_PUBLIC_KEY_LENS[1];
+/// The longest BLS signature, uncompressed.
+pub const MAX_SIGNATURE_LEN: usize = BLS_SIGNATURE_LENS[1];
+/// The longest secp256k1 public key, uncompressed.
+pub const MAX_PUB_KEY_LEN: usize = 65;
+
+/// The parts of a [`ValidatorExtend`] verification reads.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FixedValidator {
+    pub bls_pub_key: Vec<u8, MAX_BLS_PUBLIC_KEY_LEN>,
+    /// Orders the validators in the bitmap, as `ValidatorExtend::pub_key`.
+    pub pub_key:     Vec<u8, MAX_PUB_KEY_LEN>,
+    pub address:     H160,
+}
+
+impl TryFrom<&ValidatorExtend> for FixedValidator {
+    type Error = Error;
+
+    fn try_from(v: &ValidatorExtend) -> Result<Self, Error> {
+        Ok(FixedValidator {
+            bls_pub_key: Vec::from_slice(&v.bls_pub_key).map_err(|_| Error::CapacityExceeded)?,
+            pub_key:     Vec::from_slice(&v.pub_key).map_err(|_| Error::CapacityExceeded)?,
+            address:     v.address,
+        })
+    }
+}
+
+/// Copy `validators` into a list of at most `N` fixed validators, in the
+/// same order.
+pub fn fixed_validators<const N: usize>(
+    validators: &[ValidatorExtend],
+) -> Result<Vec<FixedValidator, N>, Error> {
+    if validators.len() > N {
+        return Err(Error::TooManyValidators);
+    }
+
+    validators.iter().map(FixedValidator::try_from).collect()
+}
+
+/// A [`Proof`] whose bitmap holds at most `BITMAP` bytes, enough for
+/// `8 * BITMAP` validators.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FixedProof<const BITMAP: usize> {
+    pub number:     u64,
+    pub round:      u64,
+    pub block_hash: H256,
+    pub signature:  Vec<u8, MAX_SIGNATURE_LEN>,
+    pub bitmap:     Vec<u8, BITMAP>,
+}
+
+impl<const BITMAP: usize> TryFrom<&Proof> for FixedProof<BITMAP> {
+    type Error = Error;
+
+    fn try_from(proof: &Proof) -> Result<Self, Error> {
+        Ok(FixedProof {
+            number:     proof.number,
+            round:      proof.round,
+            block_hash: proof.block_hash,
+            signature:  Vec::from_slice(&proof.signature)
+                .map_err(|_| Error::InvalidSignatureLength(proof.signature.len()))?,
+            bitmap:     Vec::from_slice(&proof.bitmap).map_err(|_| Error::CapacityExceeded)?,
+        })
+    }
+}
+
+/// Same checks as [`crate::verify_proof`], for the block whose proposal
+/// encodes to `proposal`. The validator list must be sorted by `pub_key`, as
+/// [`crate::types::Metadata::to_validators`] returns it.
+pub fn verify_proof_fixed<const N: usize, const BITMAP: usize>(
+    proposal: &[u8],
+    validator_list: &Vec<FixedValidator, N>,
+    proof: &FixedProof<BITMAP>,
+) -> Result<(), Error> {
+    if keccak_256(proposal) != proof.block_hash.0 {
+        return Err(Error::InvalidProofBlockHash);
+    }
+
+    check_signature_length(&proof.signature)?;
+    for (index, v) in validator_list.iter().enumerate() {
+        check_public_key_length(index, &v.bls_pub_key)?;
+    }
+    check_bitmap_bytes(&proof.bitmap, validator_list.len())?;
+    for (i, v) in validator_list.iter().enumerate() {
+        let duplicate = validator_list[..i].iter().any(|prev| {
+            prev.address == v.address
+                || prev.pub_key == v.pub_key
+                || prev.bls_pub_key == v.bls_pub_key
+        });
+        if duplicate {
+            return Err(Error::DuplicateValidator(v.address));
+        }
+    }
+    let misplaced = validator_list
+        .windows(2)
+        .position(|w| w[0].pub_key >= w[1].pub_key);
+    if let Some(i) = misplaced {
+        return Err(Error::ValidatorsOutOfOrder { index: i + 1 });
+    }
+
+    let signers = validator_list
+        .iter()
+        .map(|v| (v.address, &v.bls_pub_key[..]));
+    let (aggregate, signer_count) = aggregate_signers(&proof.bitmap, signers)?;
+    check_quorum(signer_count, validator_list.len())?;
+    let aggregate = aggregate.ok_or(Error::NotEnoughSignatures)?;
+
+    verify_precommit(
+        proof.number,
+        proof.round,
+        proof.block_hash,
+        &proof.signature,
+        &aggregate,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use blst::min_pk::{AggregateSignature, SecretKey};
+    use bytes::Bytes;
+    use rlp::Encodable;
+
+    use super::*;
+    use crate::consts::BLS_DST;
+    use crate::proof::{precommit_vote, vote_hash};
+    use crate::types::tests::{mock_header, mock_validator};
+    use crate::types::{AxonBlock, Hex, Proposal};
+    use crate::{verify_proof, verify_proof_static};
+
+    #[test]
+    fn test_verify_proof_fixed() {
+        let sks = (1..=4u8)
+            .map(|i| SecretKey::key_gen(&[i; 32], &[]).unwrap())
+            .collect::<alloc::vec::Vec<_>>();
+        // `pub_key` orders the validators as `sks`.
+        let validators = sks
+            .iter()
+            .zip(1..=4u8)
+            .map(|(sk, i)| ValidatorExtend {
+                bls_pub_key: Hex::encode(sk.sk_to_pk().to_bytes()),
+                ..mock_validator(i)
+            })
+            .collect::<alloc::vec::Vec<_>>();
+        let block = AxonBlock {
+            header:    mock_header(),
+            tx_hashes: vec![],
+        };
+        let proposal = Proposal::from_block(&block, H256::zero()).unwrap().rlp_bytes();
+        let mut proof = Proof {
+            number:     block.header.number,
+            block_hash: H256(keccak_256(&proposal)),
+            bitmap:     Bytes::from_static(&[0b1101_0000]),
+            ..Default::default()
+        };
+        let message = vote_hash(&precommit_vote(&proof));
+        let sigs = [0, 1, 3]
+            .map(|i| sks[i].sign(&message, BLS_DST.as_bytes(), &[]))
+            .to_vec();
+        let sigs = sigs.iter().collect::<alloc::vec::Vec<_>>();
+        let sig = AggregateSignature::aggregate(&sigs, true).unwrap().to_signature();
+        proof.signature = Bytes::from(sig.to_bytes().to_vec());
+
+        let fixed_list = fixed_validators::<4>(&validators).unwrap();
+        let fixed_proof = FixedProof::<1>::try_from(&proof).unwrap();
+        assert!(verify_proof_fixed(&proposal, &fixed_list, &fixed_proof).is_ok());
+        assert!(verify_proof(&block, H256::zero(), &validators, &proof).is_ok());
+        let res = verify_proof_static::<4>(&block, H256::zero(), &validators, &proof, &mut [0; 1024]);
+        assert!(res.is_ok());
+
+        assert!(matches!(
+            verify_proof_fixed(&proposal[1..], &fixed_list, &fixed_proof),
+            Err(Error::InvalidProofBlockHash)
+        ));
+        let mut forged = fixed_proof.clone();
+        forged.bitmap[0] = 0b1110_0000;
+        assert!(verify_proof_fixed(&proposal, &fixed_list, &forged).is_err());
+        forged.bitmap[0] = 0b1100_0000;
+        assert!(matches!(
+            verify_proof_fixed(&proposal, &fixed_list, &forged),
+            Err(Error::NotEnoughSignatures)
+        ));
+        let mut reversed = fixed_list.clone();
+        reversed.reverse();
+        assert!(matches!(
+            verify_proof_fixed(&proposal, &reversed, &fixed_proof),
+            Err(Error::ValidatorsOutOfOrder { index: 1 })
+        ));
+
+        assert!(matches!(
+            fixed_validators::<3>(&validators),
+            Err(Error::TooManyValidators)
+        ));
+        proof.bitmap = Bytes::from_static(&[0xff, 0xff]);
+        assert!(matches!(
+            FixedProof::<1>::try_from(&proof),
+            Err(Error::CapacityExceeded)
+        ));
+    }
+}
@@ -0,0 +1,94 @@
+use crate::types::{BlockNumber, H256};
+
+bitflags::bitflags! {
+    /// The Axon hardforks known to this crate, one bit each.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+    pub struct HardforkFlags: u64 {
+        const ANDROMEDA = 0b1;
+    }
+}
+
+/// A hardfork activation as stored in the first entry of a header's
+/// `extra_data`: the flags enabled from `block_number` on.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "impl-rlp",
+    derive(rlp_derive::RlpEncodable, rlp_derive::RlpDecodable)
+)]
+#[cfg_attr(feature = "impl-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HardforkInfoInner {
+    #[cfg_attr(feature = "impl-serde", serde(alias = "blockNumber"))]
+    pub block_number: BlockNumber,
+    pub flags:        H256,
+}
+
+impl HardforkInfoInner {
+    pub fn new(block_number: BlockNumber, flags: HardforkFlags) -> Self {
+        HardforkInfoInner {
+            block_number,
+            flags: H256::from_low_u64_be(flags.bits()),
+        }
+    }
+
+    /// The known hardforks set in `flags`, unknown bits are dropped.
+    pub fn hardfork_flags(&self) -> HardforkFlags {
+        HardforkFlags::from_bits_truncate(self.flags.to_low_u64_be())
+    }
+}
+
+/// Whether `flag` is enabled at block `number` according to `schedule`. The
+/// latest activation at or before `number` is the one in effect.
+pub fn is_enabled(
+    flag: HardforkFlags,
+    number: BlockNumber,
+    schedule: &[HardforkInfoInner],
+) -> bool {
+    schedule
+        .iter()
+        .filter(|info| info.block_number <= number)
+        .max_by_key(|info| info.block_number)
+        .map(|info| info.hardfork_flags().contains(flag))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ExtraDataBuilder, TypesError};
+    use bytes::Bytes;
+
+    #[test]
+    fn test_is_enabled() {
+        let schedule = vec![
+            HardforkInfoInner::new(100, HardforkFlags::ANDROMEDA),
+            HardforkInfoInner::new(0, HardforkFlags::empty()),
+        ];
+        assert!(!is_enabled(HardforkFlags::ANDROMEDA, 99, &schedule));
+        assert!(is_enabled(HardforkFlags::ANDROMEDA, 100, &schedule));
+        assert!(!is_enabled(HardforkFlags::ANDROMEDA, 100, &[]));
+    }
+
+    #[test]
+    fn test_hardfork_info_from_header() {
+        let info = HardforkInfoInner::new(100, HardforkFlags::ANDROMEDA);
+        let mut header = crate::types::tests::mock_header();
+        header.extra_data = ExtraDataBuilder::new()
+            .hardfork(&info)
+            .custom(Bytes::from_static(b"axon"))
+            .build()
+            .unwrap();
+        assert_eq!(header.hardfork_info().unwrap(), Some(info));
+        assert_eq!(header.custom_entries().len(), 1);
+        assert_eq!(
+            header.custom_entries()[0].inner,
+            Bytes::from_static(b"axon")
+        );
+
+        header.extra_data = ExtraDataBuilder::new().build().unwrap();
+        assert_eq!(header.hardfork_info().unwrap(), None);
+        assert!(header.custom_entries().is_empty());
+
+        let res = ExtraDataBuilder::new().custom(Bytes::from_static(b"axon")).build();
+        assert!(matches!(res, Err(TypesError::MissingHardforkEntry)));
+    }
+}
@@ -1,3 +1,6 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
 use tiny_keccak::{Hasher, Keccak};
 
 #[cfg(feature = "hash")]
@@ -10,9 +13,104 @@ pub fn keccak_256(data: &[u8]) -> [u8; 32] {
     ret
 }
 
+/// Hash the concatenation of `parts` without building it, e.g. a domain
+/// prefix followed by a payload.
+#[cfg(feature = "hash")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "hash")))]
+pub fn keccak_256_concat(parts: &[&[u8]]) -> [u8; 32] {
+    keccak_256_iter(parts)
+}
+
+/// Same as [`keccak_256_concat`] for any sequence of byte slices.
+#[cfg(feature = "hash")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "hash")))]
+pub fn keccak_256_iter<I>(parts: I) -> [u8; 32]
+where
+    I: IntoIterator,
+    I::Item: AsRef<[u8]>,
+{
+    let mut ret = [0u8; 32];
+    let mut hasher = Keccak::v256();
+    for part in parts {
+        hasher.update(part.as_ref());
+    }
+    hasher.finalize(&mut ret);
+    ret
+}
+
+/// Below this many inputs per thread, spawning costs more than it saves.
+#[cfg(feature = "hash")]
+const MIN_BATCH_PER_THREAD: usize = 256;
+
+/// Hash every item in `items`, returning the digests in input order. Large
+/// batches, such as the transactions of a full block, are split across the
+/// available cores, batches of fewer than twice `MIN_BATCH_PER_THREAD` items
+/// are hashed on the calling thread.
+#[cfg(feature = "hash")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "hash")))]
+pub fn keccak_256_batch<T: AsRef<[u8]> + Sync>(items: &[T]) -> Vec<[u8; 32]> {
+    let threads = thread_count(items.len(), MIN_BATCH_PER_THREAD);
+    keccak_256_batch_on(items, threads)
+}
+
+fn keccak_256_batch_on<T: AsRef<[u8]> + Sync>(items: &[T], threads: usize) -> Vec<[u8; 32]> {
+    let mut ret = vec![[0u8; 32]; items.len()];
+    for_each_chunk(items, &mut ret, threads, |_, input, output| {
+        for (item, digest) in input.iter().zip(output) {
+            *digest = keccak_256(item.as_ref());
+        }
+    });
+    ret
+}
+
+/// The threads to split `len` items over so that each gets at least
+/// `min_per_thread` of them, at most one per available core.
+#[cfg(feature = "std")]
+pub(crate) fn thread_count(len: usize, min_per_thread: usize) -> usize {
+    std::thread::available_parallelism()
+        .map(usize::from)
+        .unwrap_or(1)
+        .min(len / min_per_thread.max(1))
+        .max(1)
+}
+
+/// Without `std` there are no threads, everything runs on the calling one.
+#[cfg(not(feature = "std"))]
+pub(crate) fn thread_count(_len: usize, _min_per_thread: usize) -> usize {
+    1
+}
+
+/// Split `items` and `out` into `threads` contiguous chunks and call `f` on
+/// each pair of chunks on a thread of its own, with the index of the first
+/// item of the chunk. With a single thread, or without `std`, `f` is called
+/// once on the calling thread. `out` is as long as `items`.
+#[cfg_attr(not(feature = "std"), allow(unused_variables))]
+pub(crate) fn for_each_chunk<T, R, F>(items: &[T], out: &mut [R], threads: usize, f: F)
+where
+    T: Sync,
+    R: Send,
+    F: Fn(usize, &[T], &mut [R]) + Sync,
+{
+    #[cfg(feature = "std")]
+    if threads > 1 && items.len() > 1 {
+        let chunk_size = items.len().div_ceil(threads);
+        return std::thread::scope(|s| {
+            let chunks = items.chunks(chunk_size).zip(out.chunks_mut(chunk_size));
+            for (i, (input, output)) in chunks.enumerate() {
+                let f = &f;
+                s.spawn(move || f(i * chunk_size, input, output));
+            }
+        });
+    }
+
+    f(0, items, out)
+}
+
+#[cfg(feature = "trie")]
 #[derive(Default)]
 pub(crate) struct InnerKeccak;
 
+#[cfg(feature = "trie")]
 impl cita_trie::Hasher for InnerKeccak {
     const LENGTH: usize = 32;
 
@@ -20,3 +118,45 @@ impl cita_trie::Hasher for InnerKeccak {
         keccak_256(data).to_vec()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keccak_256_batch() {
+        for len in [0, 1, 3 * MIN_BATCH_PER_THREAD + 1] {
+            let items = (0..len).map(|i: usize| i.to_be_bytes()).collect::<Vec<_>>();
+            let expect = items.iter().map(|item| keccak_256(item)).collect::<Vec<_>>();
+            assert_eq!(keccak_256_batch(&items), expect);
+            for threads in [1, 3, 8] {
+                assert_eq!(keccak_256_batch_on(&items, threads), expect);
+            }
+        }
+        assert_eq!(thread_count(2 * MIN_BATCH_PER_THREAD - 1, MIN_BATCH_PER_THREAD), 1);
+    }
+
+    #[test]
+    fn test_for_each_chunk() {
+        let items = (0..10).collect::<Vec<usize>>();
+        for threads in [1, 3, 4, 20] {
+            let mut out = vec![0; items.len()];
+            for_each_chunk(&items, &mut out, threads, |start, input, output| {
+                for (j, (item, o)) in input.iter().zip(output).enumerate() {
+                    assert_eq!(*item, start + j);
+                    *o = item * 2;
+                }
+            });
+            assert_eq!(out, items.iter().map(|i| i * 2).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn test_keccak_256_concat() {
+        let (prefix, payload) = (b"AXON_CROSSCHAIN".as_slice(), [7u8; 100]);
+        let expect = keccak_256(&[prefix, &payload].concat());
+        assert_eq!(keccak_256_concat(&[prefix, &payload]), expect);
+        assert_eq!(keccak_256_iter([prefix, &payload[..40], &payload[40..]]), expect);
+        assert_eq!(keccak_256_concat(&[]), keccak_256(&[]));
+    }
+}
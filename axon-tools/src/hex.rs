@@ -1,5 +1,58 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
 use crate::Error;
 
+/// What to do with hex strings of odd length.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OddLengthPolicy {
+    /// Fail with `InvalidLength`, the canonical form for byte strings.
+    #[default]
+    Reject,
+    /// Prepend a `0` nibble, so `0x123` parses as `[0x01, 0x23]`. This is the
+    /// canonical form for quantities, which are never zero padded.
+    LeftPad,
+}
+
+/// What to tolerate when parsing hex strings. The default matches
+/// `Hex::from_str`: `0x` prefix required, either case, even length.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HexParseOptions {
+    pub allow_uppercase:      bool,
+    pub allow_missing_prefix: bool,
+    pub odd_length:           OddLengthPolicy,
+}
+
+impl HexParseOptions {
+    pub const PERMISSIVE: Self = HexParseOptions {
+        allow_uppercase:      true,
+        allow_missing_prefix: true,
+        odd_length:           OddLengthPolicy::LeftPad,
+    };
+
+    /// `0x` prefixed quantities as used by JSON-RPC, e.g. `0x1b4`.
+    pub const QUANTITY: Self = HexParseOptions {
+        allow_uppercase:      true,
+        allow_missing_prefix: false,
+        odd_length:           OddLengthPolicy::LeftPad,
+    };
+    /// Lowercase, `0x` prefixed, even length only.
+    pub const STRICT: Self = HexParseOptions {
+        allow_uppercase:      false,
+        allow_missing_prefix: false,
+        odd_length:           OddLengthPolicy::Reject,
+    };
+}
+
+impl Default for HexParseOptions {
+    fn default() -> Self {
+        HexParseOptions {
+            allow_uppercase: true,
+            ..Self::STRICT
+        }
+    }
+}
+
 pub fn hex_encode<T: AsRef<[u8]>>(src: T) -> String {
     faster_hex::hex_string(src.as_ref())
 }
@@ -16,8 +69,294 @@ pub fn hex_decode(src: &str) -> Result<Vec<u8>, Error> {
     };
 
     let src = src.as_bytes();
-    let mut ret = vec![0u8; src.len() / 2];
+    let mut ret = alloc::vec![0u8; src.len() / 2];
     faster_hex::hex_decode(src, &mut ret)?;
 
     Ok(ret)
 }
+
+/// Decode a hex string according to `options`.
+pub fn hex_decode_with(src: &str, options: HexParseOptions) -> Result<Vec<u8>, Error> {
+    let src = match src.strip_prefix("0x") {
+        Some(src) => src,
+        None if options.allow_missing_prefix => src,
+        None => return Err(Error::HexPrefix),
+    };
+
+    if !options.allow_uppercase && src.bytes().any(|b| b.is_ascii_uppercase()) {
+        return Err(faster_hex::Error::InvalidChar.into());
+    }
+
+    if src.len().is_multiple_of(2) {
+        return decode_digits(src.as_bytes());
+    }
+
+    match options.odd_length {
+        OddLengthPolicy::Reject => Err(faster_hex::Error::InvalidLength(src.len()).into()),
+        OddLengthPolicy::LeftPad => decode_digits(alloc::format!("0{}", src).as_bytes()),
+    }
+}
+
+// Decode hex digits whose prefix, if any, is already stripped, so that a
+// second `0x` is rejected as digits rather than stripped again.
+fn decode_digits(digits: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut ret = alloc::vec![0u8; digits.len() / 2];
+    faster_hex::hex_decode(digits, &mut ret)?;
+    Ok(ret)
+}
+
+/// The value of an ASCII hex digit, either case.
+pub(crate) fn hex_nibble(c: u8) -> Result<u8, Error> {
+    match c {
+        b'0'..=b'9' => Ok(c - b'0'),
+        b'a'..=b'f' => Ok(c - b'a' + 10),
+        b'A'..=b'F' => Ok(c - b'A' + 10),
+        _ => Err(faster_hex::Error::InvalidChar.into()),
+    }
+}
+
+/// Deserialize a string and hand it to `f` without first copying it into a
+/// `String`. Borrowed input such as `serde_json::from_str` is read in place,
+/// other deserializers lend their scratch buffer.
+#[cfg(feature = "impl-serde")]
+pub(crate) fn deserialize_str_with<'de, D, T, M, F>(deserializer: D, f: F) -> Result<T, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    M: core::fmt::Display,
+    F: FnOnce(&str) -> Result<T, M>,
+{
+    struct StrVisitor<F>(F);
+
+    impl<'de, T, M, F> serde::de::Visitor<'de> for StrVisitor<F>
+    where
+        M: core::fmt::Display,
+        F: FnOnce(&str) -> Result<T, M>,
+    {
+        type Value = T;
+
+        fn expecting(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+            f.write_str("a hex string")
+        }
+
+        fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<T, E> {
+            (self.0)(v).map_err(E::custom)
+        }
+    }
+
+    deserializer.deserialize_str(StrVisitor(f))
+}
+
+/// Serde adapter accepting any hex string `HexParseOptions::PERMISSIVE`
+/// does, for `#[serde(deserialize_with =
+/// "axon_tools::hex::permissive::deserialize")]` on `Hex` or `Bytes` fields.
+#[cfg(feature = "impl-serde")]
+pub mod permissive {
+    use serde::Deserializer;
+
+    use super::{deserialize_str_with, HexParseOptions};
+    use crate::types::Hex;
+
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: From<Hex>,
+    {
+        deserialize_str_with(deserializer, |s| {
+            Hex::from_str_with(s, HexParseOptions::PERMISSIVE).map(Into::into)
+        })
+    }
+}
+
+/// Serde adapter for `U256` fields accepting either `0x` prefixed hex or a
+/// decimal string, as some explorers and databases export. Values wider than
+/// 256 bits are rejected rather than truncated.
+#[cfg(feature = "impl-serde")]
+pub mod dec_or_hex {
+    use ethereum_types::U256;
+    use serde::Deserializer;
+
+    use super::{deserialize_str_with, hex_decode_with, HexParseOptions};
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<U256, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserialize_str_with(deserializer, from_str)
+    }
+
+    fn from_str(s: &str) -> Result<U256, String> {
+        if s.starts_with("0x") {
+            return from_hex_str(s);
+        }
+
+        if s.is_empty() {
+            return Err("Empty decimal string".to_string());
+        }
+        U256::from_dec_str(s).map_err(|e| format!("Invalid decimal {}: {:?}", s, e))
+    }
+
+    fn from_hex_str(s: &str) -> Result<U256, String> {
+        if s.len() == 2 {
+            return Err("Empty hex string".to_string());
+        }
+
+        let bytes = hex_decode_with(s, HexParseOptions::QUANTITY).map_err(|e| e.to_string())?;
+        let len = bytes.len().saturating_sub(32);
+        if bytes[..len].iter().any(|b| *b != 0) {
+            return Err(format!("Hex {} overflows U256", s));
+        }
+        Ok(U256::from_big_endian(&bytes[len..]))
+    }
+}
+
+/// Serde adapter for fixed-size byte arrays such as `[u8; 48]` BLS public
+/// keys, for `#[serde(with = "axon_tools::hex::fixed")]`. Serializes as `0x`
+/// prefixed lowercase hex and rejects input of any other length than `N`.
+#[cfg(feature = "impl-serde")]
+pub mod fixed {
+    use serde::{Deserializer, Serializer};
+
+    use super::{deserialize_str_with, hex_decode_with, hex_encode, HexParseOptions};
+
+    pub fn serialize<S, const N: usize>(bytes: &[u8; N], s: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        s.serialize_str(&format!("0x{}", hex_encode(bytes)))
+    }
+
+    pub fn deserialize<'de, D, const N: usize>(deserializer: D) -> Result<[u8; N], D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserialize_str_with(deserializer, |s| {
+            let bytes = hex_decode_with(s, HexParseOptions::default()).map_err(|e| e.to_string())?;
+            let len = bytes.len();
+            bytes
+                .try_into()
+                .map_err(|_| format!("Expect {} bytes, get {}", N, len))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hex_decode_with() {
+        let strict = HexParseOptions::STRICT;
+        assert_eq!(hex_decode_with("0xabcd", strict).unwrap(), vec![0xab, 0xcd]);
+        assert!(hex_decode_with("0xABCD", strict).is_err());
+        assert!(hex_decode_with("abcd", strict).is_err());
+        assert!(hex_decode_with("0xabc", strict).is_err());
+        assert!(hex_decode_with("0x0xabcd", strict).is_err());
+        assert!(hex_decode_with("0x0x", strict).is_err());
+
+        assert_eq!(
+            hex_decode_with("0xABCD", HexParseOptions::default()).unwrap(),
+            vec![0xab, 0xcd]
+        );
+
+        let permissive = HexParseOptions::PERMISSIVE;
+        assert_eq!(hex_decode_with("ABCD", permissive).unwrap(), vec![
+            0xab, 0xcd
+        ]);
+        assert_eq!(hex_decode_with("0x123", permissive).unwrap(), vec![
+            0x01, 0x23
+        ]);
+        assert!(hex_decode_with("0x0x123", permissive).is_err());
+        assert!(hex_decode_with("0x0x12", HexParseOptions::QUANTITY).is_err());
+        assert_eq!(hex_decode_with("0x", strict).unwrap(), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn test_odd_length_policy() {
+        // Byte strings are canonically even length, as `Hex::from_str` expects,
+        // while quantities are not zero padded.
+        assert!(hex_decode_with("0x123", HexParseOptions::default()).is_err());
+        assert_eq!(
+            hex_decode_with("0x123", HexParseOptions::QUANTITY).unwrap(),
+            vec![0x01, 0x23]
+        );
+        assert_eq!(hex_decode_with("0x1", HexParseOptions::QUANTITY).unwrap(), vec![
+            0x01
+        ]);
+        assert!(hex_decode_with("123", HexParseOptions::QUANTITY).is_err());
+
+        // Padding never changes even length input.
+        for options in [HexParseOptions::STRICT, HexParseOptions::QUANTITY] {
+            assert_eq!(hex_decode_with("0x0123", options).unwrap(), vec![
+                0x01, 0x23
+            ]);
+        }
+    }
+
+    #[cfg(feature = "impl-serde")]
+    #[test]
+    fn test_permissive_deserialize() {
+        #[derive(serde::Deserialize)]
+        struct Config {
+            #[serde(deserialize_with = "permissive::deserialize")]
+            key: bytes::Bytes,
+        }
+
+        let config: Config = serde_json::from_str(r#"{"key": "ABC"}"#).unwrap();
+        assert_eq!(config.key.as_ref(), &[0x0a, 0xbc]);
+    }
+
+    #[cfg(feature = "impl-serde")]
+    #[test]
+    fn test_dec_or_hex_deserialize() {
+        use ethereum_types::U256;
+
+        #[derive(serde::Deserialize)]
+        struct Fee {
+            #[serde(deserialize_with = "dec_or_hex::deserialize")]
+            base_fee: U256,
+        }
+
+        let parse = |s: &str| {
+            let json = format!(r#"{{"base_fee": "{}"}}"#, s);
+            serde_json::from_str::<Fee>(&json).map(|fee| fee.base_fee)
+        };
+        assert_eq!(parse("1000000000").unwrap(), U256::from(1_000_000_000u64));
+        assert_eq!(parse("0x3b9aca00").unwrap(), U256::from(1_000_000_000u64));
+        assert_eq!(parse("0x1").unwrap(), U256::one());
+        assert_eq!(parse(&U256::MAX.to_string()).unwrap(), U256::MAX);
+        assert_eq!(parse(&format!("0x00{}", "f".repeat(64))).unwrap(), U256::MAX);
+
+        // One past `U256::MAX`, in both forms.
+        let max_plus_one =
+            "115792089237316195423570985008687907853269984665640564039457584007913129639936";
+        assert!(parse(max_plus_one).is_err());
+        assert!(parse(&format!("0x1{}", "0".repeat(64))).is_err());
+
+        assert!(parse("").is_err());
+        assert!(parse("0x").is_err());
+        assert!(parse("-1").is_err());
+        assert!(parse("12a").is_err());
+    }
+
+    #[cfg(feature = "impl-serde")]
+    #[test]
+    fn test_fixed_serde() {
+        #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+        struct Key {
+            #[serde(with = "fixed")]
+            bls_pub_key: [u8; 48],
+        }
+
+        let key = Key {
+            bls_pub_key: [0xab; 48],
+        };
+        let json = serde_json::to_string(&key).unwrap();
+        assert_eq!(json, format!(r#"{{"bls_pub_key":"0x{}"}}"#, "ab".repeat(48)));
+        assert_eq!(serde_json::from_str::<Key>(&json).unwrap(), key);
+
+        let short = format!(r#"{{"bls_pub_key":"0x{}"}}"#, "ab".repeat(47));
+        assert!(serde_json::from_str::<Key>(&short).is_err());
+        let long = format!(r#"{{"bls_pub_key":"0x{}"}}"#, "ab".repeat(96));
+        assert!(serde_json::from_str::<Key>(&long).is_err());
+    }
+}
@@ -0,0 +1,230 @@
+//! A structured summary of a block for explorers and debugging tools. Nothing
+//! is verified here: anomalies that verification would reject are listed as
+//! warnings so that a report can be rendered for any block.
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use bit_vec::BitVec;
+use ethereum_types::{H160, U256};
+
+use crate::check_bitmap;
+use crate::hardfork::HardforkInfoInner;
+use crate::types::{try_into_u64, AxonBlock, BlockNumber, Metadata, MetadataVersion, Proof};
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "impl-serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "impl-serde", serde(rename_all = "camelCase"))]
+pub struct InspectionReport {
+    pub number:               BlockNumber,
+    pub round:                u64,
+    pub tx_count:             usize,
+    pub proposer:             H160,
+    pub hardfork:             Option<HardforkInfoInner>,
+    /// Signers in bitmap order.
+    pub signers:              Vec<H160>,
+    /// Validators that did not sign, in bitmap order.
+    pub absentees:            Vec<H160>,
+    pub signed_weight:        u64,
+    pub total_weight:         u64,
+    pub has_quorum:           bool,
+    pub gas_used:             U256,
+    pub gas_limit:            U256,
+    /// `gas_used / gas_limit`, 0 if the limit is zero.
+    pub gas_usage:            f64,
+    pub epoch:                u64,
+    pub metadata_version:     MetadataVersion,
+    /// The blocks after this one still verified by the same metadata.
+    pub blocks_left_in_epoch: u64,
+    pub warnings:             Vec<&'static str>,
+}
+
+/// Summarize `block`, finalized by `proof`, against the `metadata` expected
+/// to cover it.
+pub fn inspect(block: &AxonBlock, proof: &Proof, metadata: &Metadata) -> InspectionReport {
+    let header = &block.header;
+    let mut warnings = Vec::new();
+
+    let mut validators = metadata.verifier_list.clone();
+    validators.sort();
+    let bits = BitVec::from_bytes(&proof.bitmap);
+    let (mut signers, mut absentees) = (Vec::new(), Vec::new());
+    let (mut signed_weight, mut total_weight) = (0u64, 0u64);
+    for (i, v) in validators.iter().enumerate() {
+        total_weight += u64::from(v.vote_weight);
+        if bits.get(i).unwrap_or(false) {
+            signed_weight += u64::from(v.vote_weight);
+            signers.push(v.address);
+        } else {
+            absentees.push(v.address);
+        }
+    }
+
+    if check_bitmap(proof, validators.len()).is_err() {
+        warnings.push("bitmap does not match the validator set");
+    }
+    if proof.number != header.number {
+        warnings.push("proof is for another height");
+    }
+    if !validators.iter().any(|v| v.address == header.proposer) {
+        warnings.push("proposer is not a validator");
+    }
+    if !metadata.version.contains(header.number) {
+        warnings.push("block is outside the metadata version");
+    }
+    if header.gas_used > header.gas_limit {
+        warnings.push("gas used exceeds gas limit");
+    }
+    let hardfork = header.hardfork_info().unwrap_or_else(|_| {
+        warnings.push("hardfork entry does not decode");
+        None
+    });
+
+    let gas_usage = match saturating_u64(header.gas_limit) {
+        0 => 0.0,
+        limit => saturating_u64(header.gas_used) as f64 / limit as f64,
+    };
+
+    InspectionReport {
+        number: header.number,
+        round: proof.round,
+        tx_count: block.tx_hashes.len(),
+        proposer: header.proposer,
+        hardfork,
+        signers,
+        absentees,
+        signed_weight,
+        total_weight,
+        has_quorum: crate::has_quorum(signed_weight, total_weight),
+        gas_used: header.gas_used,
+        gas_limit: header.gas_limit,
+        gas_usage,
+        epoch: metadata.epoch,
+        metadata_version: metadata.version,
+        blocks_left_in_epoch: metadata.version.end.saturating_sub(header.number),
+        warnings,
+    }
+}
+
+fn saturating_u64(value: U256) -> u64 {
+    try_into_u64(value).unwrap_or(u64::MAX)
+}
+
+impl fmt::Display for InspectionReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "block #{} round {}, {} txs, proposer {:?}",
+            self.number, self.round, self.tx_count, self.proposer
+        )?;
+        match &self.hardfork {
+            Some(info) => {
+                let names = info
+                    .hardfork_flags()
+                    .iter_names()
+                    .map(|(name, _)| name)
+                    .collect::<Vec<_>>();
+                writeln!(f, "hardfork: {:?} from block {}", names, info.block_number)?
+            }
+            None => writeln!(f, "hardfork: none")?,
+        }
+        writeln!(
+            f,
+            "signers: {}/{} validators, weight {}/{}, quorum {}",
+            self.signers.len(),
+            self.signers.len() + self.absentees.len(),
+            self.signed_weight,
+            self.total_weight,
+            if self.has_quorum { "reached" } else { "missed" }
+        )?;
+        writeln!(
+            f,
+            "gas: {} / {} ({:.1}%)",
+            self.gas_used,
+            self.gas_limit,
+            self.gas_usage * 100.0
+        )?;
+        writeln!(
+            f,
+            "epoch {}, metadata version {}..={}, {} blocks left",
+            self.epoch,
+            self.metadata_version.start,
+            self.metadata_version.end,
+            self.blocks_left_in_epoch
+        )?;
+        for warning in self.warnings.iter() {
+            writeln!(f, "warning: {}", warning)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use ethereum_types::H256;
+
+    use super::*;
+    use crate::hardfork::HardforkFlags;
+    use crate::types::tests::{mock_header, mock_validator};
+    use crate::types::{ExtraDataBuilder, MetadataBuilder, ValidatorExtend};
+
+    fn validator(vote_weight: u8) -> ValidatorExtend {
+        ValidatorExtend {
+            vote_weight: vote_weight.into(),
+            ..mock_validator(vote_weight)
+        }
+    }
+
+    #[test]
+    fn test_inspect() {
+        let metadata = MetadataBuilder::new()
+            .version(1, 100)
+            .epoch(0)
+            .validator(validator(1))
+            .validator(validator(2))
+            .validator(validator(3))
+            .build();
+        let mut validators = metadata.verifier_list.clone();
+        validators.sort();
+
+        let mut header = mock_header();
+        header.number = 40;
+        header.proposer = validators[0].address;
+        header.gas_used = 25.into();
+        header.gas_limit = 100.into();
+        header.extra_data = ExtraDataBuilder::new()
+            .hardfork(&HardforkInfoInner::new(10, HardforkFlags::ANDROMEDA))
+            .build()
+            .unwrap();
+        let block = AxonBlock {
+            header,
+            tx_hashes: vec![H256(rand::random())],
+        };
+        let proof = Proof {
+            number: 40,
+            bitmap: Bytes::from_static(&[0b0110_0000]),
+            ..Default::default()
+        };
+
+        let report = inspect(&block, &proof, &metadata);
+        assert_eq!(report.signers, vec![validators[1].address, validators[2].address]);
+        assert_eq!(report.absentees, vec![validators[0].address]);
+        let signed_weight = validators[1].vote_weight + validators[2].vote_weight;
+        assert_eq!(report.signed_weight, u64::from(signed_weight));
+        assert_eq!(report.total_weight, 6);
+        assert_eq!(report.gas_usage, 0.25);
+        assert_eq!(report.blocks_left_in_epoch, 60);
+        assert_eq!(report.hardfork.as_ref().unwrap().block_number, 10);
+        assert!(report.warnings.is_empty());
+        assert!(report.to_string().contains("ANDROMEDA"));
+
+        let proof = Proof {
+            number: 41,
+            bitmap: Bytes::from_static(&[0b0110_0000, 0]),
+            ..Default::default()
+        };
+        let report = inspect(&block, &proof, &metadata);
+        assert_eq!(report.warnings.len(), 2);
+    }
+}
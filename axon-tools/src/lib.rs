@@ -1,25 +1,109 @@
+#![cfg_attr(not(feature = "std"), no_std)]
 #![cfg_attr(doc_cfg, feature(doc_cfg))]
 
 extern crate alloc;
 
+pub mod asset;
+#[cfg(feature = "impl-serde")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "impl-serde")))]
+pub mod canonical;
+#[cfg(feature = "ckb")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "ckb")))]
+pub mod ckb;
+#[cfg(all(feature = "impl-serde", feature = "std"))]
+#[cfg_attr(doc_cfg, doc(cfg(all(feature = "impl-serde", feature = "std"))))]
+pub mod client;
+#[cfg(feature = "proof")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "proof")))]
+pub mod consensus;
+#[cfg(feature = "proof")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "proof")))]
+pub mod conflict;
+#[cfg(feature = "proof")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "proof")))]
+pub mod crypto;
+pub mod diff;
+pub mod epoch;
 mod error;
+#[cfg(feature = "impl-rlp")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "impl-rlp")))]
+pub mod eth_header;
+#[cfg(feature = "proof")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "proof")))]
+pub mod explain;
+pub mod fee;
+#[cfg(feature = "heapless")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "heapless")))]
+pub mod fixed;
+pub mod hardfork;
 #[cfg(feature = "hash")]
 pub mod hash;
 #[cfg(feature = "hex")]
 #[cfg_attr(doc_cfg, doc(cfg(feature = "hex")))]
 pub mod hex;
 #[cfg(feature = "proof")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "proof")))]
+pub mod inspect;
+pub mod metrics;
+#[cfg(feature = "proof")]
 mod proof;
+pub mod proposer;
+pub mod receipt;
+#[cfg(feature = "proof")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "proof")))]
+pub mod schedule;
+#[cfg(feature = "impl-serde")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "impl-serde")))]
+pub mod rpc;
+#[cfg(feature = "proof")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "proof")))]
+pub mod snapshot;
+#[cfg(feature = "ssz")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "ssz")))]
+pub mod ssz;
+#[cfg(feature = "proof")]
+mod static_verify;
+pub mod timing;
+#[cfg(feature = "proof")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "proof")))]
+pub mod trust;
 pub mod types;
+#[cfg(feature = "proof")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "proof")))]
+pub mod validator_set;
 
 pub use error::Error;
 
 #[cfg(feature = "proof")]
 #[cfg_attr(doc_cfg, doc(cfg(feature = "proof")))]
-pub use proof::{verify_proof, verify_trie_proof};
+pub use proof::{
+    check_aggregate_signature, check_aggregate_signature_with, check_bitmap, check_lengths,
+    check_proposal_hash, check_proposer, check_quorum, check_weighted_quorum, find_invalid_keys,
+    has_quorum, quorum_weight, select_metadata, select_signers, signed_power, signers_from_bitmap,
+    total_propose_weight, total_vote_weight, trace_verification, verify_aggregate_signature,
+    verify_proof, verify_proof_weighted, verify_proof_with_crypto, verify_proof_with_metadata,
+    verify_proof_with_metrics, verify_proof_with_parent, verify_vote_signature, VerificationTrace,
+    VerifyReport, WeightedQuorum,
+};
+
+#[cfg(feature = "trie")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "trie")))]
+pub use proof::{verify_trie_proof, verify_trie_proofs};
+
+#[cfg(feature = "proof")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "proof")))]
+pub use explain::{explain_proof_failure, FailureReason};
+
+#[cfg(feature = "proof")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "proof")))]
+pub use static_verify::verify_proof_static;
 
 #[cfg(feature = "hash")]
 #[cfg_attr(doc_cfg, doc(cfg(feature = "hash")))]
-pub use hash::keccak_256;
+pub use hash::{keccak_256, keccak_256_batch, keccak_256_concat, keccak_256_iter};
+
+#[cfg(all(feature = "trie", feature = "impl-serde"))]
+#[cfg_attr(doc_cfg, doc(cfg(all(feature = "trie", feature = "impl-serde"))))]
+pub use proof::verify_eth_get_proof;
 
 pub mod consts;
@@ -0,0 +1,40 @@
+//! Hooks for observing proof verification. Every method defaults to a no-op,
+//! so an implementation only overrides what it exports.
+
+use core::time::Duration;
+
+pub trait Metrics {
+    /// A reading of a monotonic clock, from any fixed starting point, the
+    /// verify duration is measured with. `None`, the default, skips the
+    /// measurement, so no clock is read on targets without one, such as wasm.
+    fn now(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Time spent in one call to the verifier, whatever the outcome. Only
+    /// observed when [`Metrics::now`] returns a reading.
+    fn observe_verify_duration(&self, _duration: Duration) {}
+
+    /// A proof passed the quorum check but its aggregate signature did not
+    /// verify.
+    fn inc_signature_failure(&self) {}
+
+    /// The number of signers of a proof against the size of the validator set.
+    fn observe_quorum(&self, _signers: usize, _validators: usize) {}
+}
+
+/// Metrics that record nothing, used by [`crate::verify_proof`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoopMetrics;
+
+impl Metrics for NoopMetrics {}
+
+/// Run `verify`, observing its duration if `metrics` reads a clock.
+pub(crate) fn timed<T>(metrics: &dyn Metrics, verify: impl FnOnce() -> T) -> T {
+    let start = metrics.now();
+    let res = verify();
+    if let (Some(start), Some(end)) = (start, metrics.now()) {
+        metrics.observe_verify_duration(end.saturating_sub(start));
+    }
+    res
+}
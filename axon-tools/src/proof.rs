@@ -1,17 +1,40 @@
 use alloc::vec::Vec;
+#[cfg(feature = "trie")]
+use core::sync::atomic::{AtomicUsize, Ordering};
 
 use bit_vec::BitVec;
 use blst::min_pk::{AggregatePublicKey, PublicKey, Signature};
 use blst::BLST_ERROR;
 use bytes::Bytes;
-use ethereum_types::H256;
+use ethereum_types::{H160, H256};
+#[cfg(feature = "impl-serde")]
+use ethereum_types::U256;
 use rlp::Encodable;
+#[cfg(feature = "impl-serde")]
+use rlp::RlpStream;
 
-use crate::types::{AxonBlock, Proof, Proposal, ValidatorExtend, Vote};
-use crate::{error::Error, hash::InnerKeccak, keccak_256};
-
-const DST: &str = "BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RONUL";
+use crate::consts::{BLS_DST, BLS_PUBLIC_KEY_LENS, BLS_SIGNATURE_LENS};
+#[cfg(feature = "impl-serde")]
+use crate::consts::{EMPTY_TRIE_ROOT, KECCAK_EMPTY};
+use crate::crypto::{BlsMinPk, ConsensusCrypto};
+use crate::metrics::{timed, Metrics, NoopMetrics};
+use crate::schedule::QuorumRule;
+use crate::static_verify::proposal_rlp;
+#[cfg(feature = "impl-serde")]
+use crate::rpc::EIP1186ProofResponse;
+use crate::types::{
+    find_duplicate_validator, AxonBlock, AxonHeader, BlockNumber, Metadata, Proof, Validator,
+    ValidatorExtend, Vote, VoteType,
+};
+use crate::validator_set::ValidatorOrder;
+#[cfg(feature = "parallel")]
+use crate::hash::thread_count;
+#[cfg(feature = "trie")]
+use crate::hash::{for_each_chunk, InnerKeccak};
+use crate::{error::Error, keccak_256};
 
+#[cfg(feature = "trie")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "trie")))]
 pub fn verify_trie_proof(
     root: H256,
     key: &[u8],
@@ -22,48 +45,709 @@ pub fn verify_trie_proof(
     Ok(value)
 }
 
+/// Verify a batch of `(key, proof)` pairs against one root, returning the
+/// proven values in input order. On failure the error of the first failing
+/// pair is returned, whether or not the `parallel` feature is enabled.
+#[cfg(feature = "trie")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "trie")))]
+pub fn verify_trie_proofs<K: AsRef<[u8]> + Sync>(
+    root: H256,
+    items: &[(K, Vec<Vec<u8>>)],
+) -> Result<Vec<Option<Vec<u8>>>, Error> {
+    #[cfg(feature = "parallel")]
+    let threads = thread_count(items.len(), 1);
+    #[cfg(not(feature = "parallel"))]
+    let threads = 1;
+    verify_trie_proofs_on(root, items, threads)
+}
+
+#[cfg(feature = "trie")]
+fn verify_trie_proofs_on<K: AsRef<[u8]> + Sync>(
+    root: H256,
+    items: &[(K, Vec<Vec<u8>>)],
+    threads: usize,
+) -> Result<Vec<Option<Vec<u8>>>, Error> {
+    let first_failure = AtomicUsize::new(usize::MAX);
+    let mut values = vec![None; items.len()];
+    for_each_chunk(items, &mut values, threads, |start, input, output| {
+        for (j, ((key, proof), value)) in input.iter().zip(output).enumerate() {
+            let index = start + j;
+            // A pair before this one already failed, its error wins.
+            if index > first_failure.load(Ordering::Relaxed) {
+                return;
+            }
+            match verify_trie_proof(root, key.as_ref(), proof.clone()) {
+                Ok(v) => *value = v,
+                Err(_) => {
+                    first_failure.fetch_min(index, Ordering::Relaxed);
+                    return;
+                }
+            }
+        }
+    });
+
+    // `Error` is not `Send`, so the failing pair is verified again here to
+    // recover its error.
+    match items.get(first_failure.into_inner()) {
+        Some((key, proof)) => Err(verify_trie_proof(root, key.as_ref(), proof.clone())
+            .err()
+            .unwrap_or(Error::VerifyMptProof)),
+        None => Ok(values),
+    }
+}
+
+/// Verify everything an `eth_getProof` response claims against a trusted state
+/// root: the account fields and every storage value. Absent accounts and zero
+/// storage values must be proven absent.
+#[cfg(all(feature = "trie", feature = "impl-serde"))]
+#[cfg_attr(doc_cfg, doc(cfg(all(feature = "trie", feature = "impl-serde"))))]
+pub fn verify_eth_get_proof(
+    state_root: H256,
+    response: &EIP1186ProofResponse,
+) -> Result<(), Error> {
+    let account_key = keccak_256(response.address.as_bytes());
+    let account_proof = response.account_proof.iter().map(|p| p.as_ref().to_vec());
+    let account = verify_trie_proof(state_root, &account_key, account_proof.collect())?;
+
+    let is_empty = response.nonce.is_zero()
+        && response.balance.is_zero()
+        && response.code_hash == KECCAK_EMPTY
+        && response.storage_hash == EMPTY_TRIE_ROOT;
+    let expect = if is_empty && account.is_none() {
+        None
+    } else {
+        let mut s = RlpStream::new_list(4);
+        s.append(&response.nonce)
+            .append(&response.balance)
+            .append(&response.storage_hash)
+            .append(&response.code_hash);
+        Some(s.out().to_vec())
+    };
+    if account != expect {
+        return Err(Error::VerifyMptProof);
+    }
+
+    for storage in response.storage_proof.iter() {
+        let mut slot = [0u8; 32];
+        storage.key.to_big_endian(&mut slot);
+        let proof = storage.proof.iter().map(|p| p.as_ref().to_vec()).collect();
+        let value = verify_trie_proof(response.storage_hash, &keccak_256(&slot), proof)?;
+
+        let expect = (storage.value != U256::zero()).then(|| rlp::encode(&storage.value).to_vec());
+        if value != expect {
+            return Err(Error::VerifyMptProof);
+        }
+    }
+
+    Ok(())
+}
+
+/// Verify that `proof` finalizes `block` with a quorum of `validator_list`,
+/// reporting who signed it. The quorum is more than two thirds of the
+/// validators, see [`verify_proof_weighted`] to count their vote weight
+/// instead. The proposer is not checked, call [`check_proposer`] as well to
+/// reject blocks proposed by a non-validator.
+///
+/// The bitmap is read against the validators sorted by `pub_key`, the
+/// canonical order of [`ValidatorOrder::PubKey`], which
+/// [`Metadata::to_validators`] returns. A `validator_list` in any other order
+/// fails with `Error::ValidatorsOutOfOrder` rather than being sorted. To
+/// verify bitmaps built in another order, use a
+/// [`ValidatorSet`](crate::validator_set::ValidatorSet).
 pub fn verify_proof(
-    block: AxonBlock,
+    block: &AxonBlock,
+    previous_state_root: H256,
+    validator_list: &[ValidatorExtend],
+    proof: &Proof,
+) -> Result<VerifyReport, Error> {
+    verify_proof_with_metrics(
+        block,
+        previous_state_root,
+        validator_list,
+        proof,
+        &NoopMetrics,
+    )
+}
+
+/// Same as [`verify_proof`] with the validators of the metadata in `metadata`
+/// whose version covers the block.
+pub fn verify_proof_with_metadata(
+    block: &AxonBlock,
+    previous_state_root: H256,
+    metadata: &[Metadata],
+    proof: &Proof,
+) -> Result<VerifyReport, Error> {
+    let metadata = select_metadata(metadata, block.header.number)?;
+    verify_proof(
+        block,
+        previous_state_root,
+        &metadata.to_validators(),
+        proof,
+    )
+}
+
+/// The one metadata in `metadata` whose version covers block `number`.
+/// Versions include both ends: the last block of an epoch is verified by
+/// that epoch's metadata, not by the next one, however early the next one
+/// was appended.
+pub fn select_metadata(metadata: &[Metadata], number: BlockNumber) -> Result<&Metadata, Error> {
+    let mut covering = metadata.iter().filter(|m| m.version.contains(number));
+    match (covering.next(), covering.next()) {
+        (Some(metadata), None) => Ok(metadata),
+        (None, _) => Err(Error::MetadataNotFound(number)),
+        (Some(_), Some(_)) => Err(Error::OverlappingMetadata(number)),
+    }
+}
+
+/// Same as [`verify_proof`] with the previous state root taken from `parent`,
+/// which must be the header `block` builds on.
+pub fn verify_proof_with_parent(
+    block: &AxonBlock,
+    parent: &AxonHeader,
+    validator_list: &[ValidatorExtend],
+    proof: &Proof,
+) -> Result<VerifyReport, Error> {
+    if keccak_256(&parent.rlp_bytes()) != block.header.prev_hash.0 {
+        return Err(Error::ParentHashMismatch);
+    }
+
+    verify_proof(block, parent.state_root, validator_list, proof)
+}
+
+/// Same as [`verify_proof`], reporting to `metrics` along the way.
+pub fn verify_proof_with_metrics(
+    block: &AxonBlock,
+    previous_state_root: H256,
+    validator_list: &[ValidatorExtend],
+    proof: &Proof,
+    metrics: &dyn Metrics,
+) -> Result<VerifyReport, Error> {
+    timed(metrics, || {
+        check_proof(
+            block,
+            previous_state_root,
+            validator_list,
+            proof,
+            metrics,
+            &BlsMinPk,
+            QuorumRule::SignerCount,
+        )
+    })
+}
+
+/// Same as [`verify_proof`] with the signatures checked by `crypto` instead
+/// of Axon's BLS scheme.
+pub fn verify_proof_with_crypto(
+    block: &AxonBlock,
+    previous_state_root: H256,
+    validator_list: &[ValidatorExtend],
+    proof: &Proof,
+    crypto: &dyn ConsensusCrypto,
+) -> Result<VerifyReport, Error> {
+    check_proof(
+        block,
+        previous_state_root,
+        validator_list,
+        proof,
+        &NoopMetrics,
+        crypto,
+        QuorumRule::SignerCount,
+    )
+}
+
+/// Same as [`verify_proof`] with the quorum counted in vote weight rather
+/// than in signers, for chains whose validators do not all weigh the same.
+///
+/// [`verify_proof`] keeps counting signers, which is the same as weighing
+/// them on chains where every validator has the same vote weight, as in
+/// Axon's default metadata. Weighing by default would reject every proof
+/// checked against validators whose weights were never filled in, such as
+/// lists built from [`ValidatorExtend::default`], whose total weight is zero.
+pub fn verify_proof_weighted(
+    block: &AxonBlock,
+    previous_state_root: H256,
+    validator_list: &[ValidatorExtend],
+    proof: &Proof,
+) -> Result<VerifyReport, Error> {
+    check_proof(
+        block,
+        previous_state_root,
+        validator_list,
+        proof,
+        &NoopMetrics,
+        &BlsMinPk,
+        QuorumRule::VoteWeight,
+    )
+}
+
+pub(crate) fn check_proof(
+    block: &AxonBlock,
+    previous_state_root: H256,
+    validator_list: &[ValidatorExtend],
+    proof: &Proof,
+    metrics: &dyn Metrics,
+    crypto: &dyn ConsensusCrypto,
+    quorum: QuorumRule,
+) -> Result<VerifyReport, Error> {
+    check_proposal_hash(block, previous_state_root, proof)?;
+    check_votes(
+        proof,
+        validator_list,
+        ValidatorOrder::PubKey,
+        None,
+        metrics,
+        crypto,
+        quorum,
+    )
+}
+
+/// The steps of [`check_proof`] after the proposal hash, against a
+/// `validator_list` expected in `order`. `keys` are the BLS public keys of
+/// `validator_list` already parsed, verified with instead of parsing
+/// `bls_pub_key` again when `crypto` is a BLS scheme.
+pub(crate) fn check_votes(
+    proof: &Proof,
+    validator_list: &[ValidatorExtend],
+    order: ValidatorOrder,
+    keys: Option<&[PublicKey]>,
+    metrics: &dyn Metrics,
+    crypto: &dyn ConsensusCrypto,
+    quorum: QuorumRule,
+) -> Result<VerifyReport, Error> {
+    let pks = validator_list.iter().map(|v| &v.bls_pub_key[..]).collect::<Vec<_>>();
+    crypto.check_lengths(&proof.signature, &pks)?;
+    check_bitmap(proof, validator_list.len())?;
+    if let Some(address) = find_duplicate_validator(validator_list) {
+        return Err(Error::DuplicateValidator(address));
+    }
+
+    order.check_sorted(validator_list)?;
+
+    let total = validator_list.len();
+    let signers = signers_from_bitmap(proof, validator_list);
+    metrics.observe_quorum(signers.len(), total);
+    let tally = match quorum {
+        QuorumRule::SignerCount => {
+            check_quorum(signers.len(), total)?;
+            weigh_signers(&signers, validator_list)
+        }
+        QuorumRule::VoteWeight => check_weighted_quorum(&signers, validator_list)?,
+    };
+
+    let bits = BitVec::from_bytes(&proof.bitmap);
+    let signer_indices = (0..total).filter(|i| bits[*i]).collect::<Vec<_>>();
+    let res = match (keys, crypto.bls_dst()) {
+        (Some(keys), Some(dst)) => {
+            let pks = signer_indices.iter().map(|i| &keys[*i]).collect::<Vec<_>>();
+            let message = vote_hash(&precommit_vote(proof));
+            verify_with_keys(&message, &proof.signature, &pks, dst)
+        }
+        _ => check_aggregate_signature_with(proof, &signers, crypto),
+    };
+    res.inspect_err(|_| metrics.inc_signature_failure())?;
+
+    Ok(VerifyReport {
+        // Checked equal to the hash of the rebuilt proposal.
+        proposal_hash: proof.block_hash,
+        signer_indices,
+        signers: signers.iter().map(|v| v.address).collect(),
+        quorum: tally,
+    })
+}
+
+/// What a successful verification found, for audit logs.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct VerifyReport {
+    /// The hash of the proposal rebuilt from the block.
+    pub proposal_hash:  H256,
+    /// The positions of the signers in the validator list sorted into bitmap
+    /// order.
+    pub signer_indices: Vec<usize>,
+    pub signers:        Vec<H160>,
+    pub quorum:         WeightedQuorum,
+}
+
+impl VerifyReport {
+    /// The fraction of the total vote weight that signed.
+    pub fn quorum_ratio(&self) -> f64 {
+        match self.quorum.total_weight {
+            0 => 0.0,
+            total => self.quorum.signed_weight as f64 / total as f64,
+        }
+    }
+}
+
+/// Step 1: rebuild the proposal from the block and check that its hash is the
+/// one the proof commits to.
+pub fn check_proposal_hash(
+    block: &AxonBlock,
     previous_state_root: H256,
-    validator_list: &mut [ValidatorExtend],
-    proof: Proof,
+    proof: &Proof,
 ) -> Result<(), Error> {
-    let raw_proposal = Proposal {
-        version:                  block.header.version,
-        prev_hash:                block.header.prev_hash,
-        proposer:                 block.header.proposer,
-        prev_state_root:          previous_state_root,
-        transactions_root:        block.header.transactions_root,
-        signed_txs_hash:          block.header.signed_txs_hash,
-        timestamp:                block.header.timestamp,
-        number:                   block.header.number,
-        gas_limit:                block.header.gas_limit,
-        extra_data:               block.header.extra_data,
-        base_fee_per_gas:         block.header.base_fee_per_gas,
-        proof:                    block.header.proof,
-        chain_id:                 block.header.chain_id,
-        call_system_script_count: block.header.call_system_script_count,
-        tx_hashes:                block.tx_hashes,
-    }
-    .rlp_bytes();
+    let raw_proposal = proposal_rlp(block, previous_state_root)?;
 
     if keccak_256(&raw_proposal) != proof.block_hash.0 {
         return Err(Error::InvalidProofBlockHash);
     }
 
-    let vote = Vote {
+    Ok(())
+}
+
+/// Check the byte lengths of the proof signature and of every validator's BLS
+/// public key, so that malformed input is reported as such rather than as an
+/// opaque blst decoding error. The index is the position in `validator_list`.
+pub fn check_lengths(proof: &Proof, validator_list: &[ValidatorExtend]) -> Result<(), Error> {
+    let pks = validator_list.iter().map(|v| &v.bls_pub_key[..]).collect::<Vec<_>>();
+    BlsMinPk.check_lengths(&proof.signature, &pks)
+}
+
+/// Check that the proof bitmap is exactly `ceil(validator_count / 8)` bytes
+/// long and sets no bit past the last validator. Such bits would otherwise be
+/// silently ignored.
+pub fn check_bitmap(proof: &Proof, validator_count: usize) -> Result<(), Error> {
+    check_bitmap_bytes(&proof.bitmap, validator_count)
+}
+
+pub(crate) fn check_bitmap_bytes(bitmap: &[u8], validator_count: usize) -> Result<(), Error> {
+    let expect = validator_count.div_ceil(8);
+    if bitmap.len() != expect {
+        return Err(Error::BitmapLengthMismatch {
+            expect,
+            actual: bitmap.len(),
+        });
+    }
+
+    let used_bits = validator_count % 8;
+    match bitmap.last() {
+        Some(last) if used_bits != 0 && last & (0xff >> used_bits) != 0 => {
+            Err(Error::BitmapTrailingBits)
+        }
+        _ => Ok(()),
+    }
+}
+
+pub(crate) fn check_signature_length(signature: &[u8]) -> Result<(), Error> {
+    if !BLS_SIGNATURE_LENS.contains(&signature.len()) {
+        return Err(Error::InvalidSignatureLength(signature.len()));
+    }
+    Ok(())
+}
+
+pub(crate) fn check_public_key_length(index: usize, pubkey: &[u8]) -> Result<(), Error> {
+    if !BLS_PUBLIC_KEY_LENS.contains(&pubkey.len()) {
+        return Err(Error::InvalidPublicKeyLength {
+            index,
+            len: pubkey.len(),
+        });
+    }
+    Ok(())
+}
+
+/// Step 2: pick the validators whose bit is set in the proof bitmap. The
+/// list must be sorted by `pub_key` as in [`verify_proof`], any other order
+/// fails with `Error::ValidatorsOutOfOrder`.
+pub fn select_signers<'a>(
+    proof: &Proof,
+    validator_list: &'a [ValidatorExtend],
+) -> Result<Vec<&'a ValidatorExtend>, Error> {
+    ValidatorOrder::PubKey.check_sorted(validator_list)?;
+    Ok(signers_from_bitmap(proof, validator_list))
+}
+
+/// Pick the validators whose bit is set in the proof bitmap. The list must
+/// already be in the order the bitmap was built against, no cryptographic
+/// check is performed.
+pub fn signers_from_bitmap<'a, T>(proof: &Proof, validator_list: &'a [T]) -> Vec<&'a T> {
+    let bit_map = BitVec::from_bytes(&proof.bitmap);
+    validator_list
+        .iter()
+        .zip(bit_map.iter())
+        .filter_map(|(v, bit)| bit.then_some(v))
+        .collect()
+}
+
+/// Sum the vote weights of `validators`, `None` on overflow.
+pub fn total_vote_weight<'a, I>(validators: I) -> Option<u64>
+where
+    I: IntoIterator<Item = &'a ValidatorExtend>,
+{
+    sum_weights(validators.into_iter().map(|v| v.vote_weight))
+}
+
+/// Sum the propose weights of `validators`, `None` on overflow.
+pub fn total_propose_weight<'a, I>(validators: I) -> Option<u64>
+where
+    I: IntoIterator<Item = &'a ValidatorExtend>,
+{
+    sum_weights(validators.into_iter().map(|v| v.propose_weight))
+}
+
+/// The vote weight that signed `proof`, the total vote weight of
+/// `validator_list` and the signed fraction, for charting participation
+/// without verifying anything. The list must already be in bitmap order.
+pub fn signed_power(proof: &Proof, validator_list: &[Validator]) -> (u64, u64, f64) {
+    let weight = |v: &Validator| u64::from(v.vote_weight);
+    let signed = signers_from_bitmap(proof, validator_list)
+        .into_iter()
+        .map(weight)
+        .sum::<u64>();
+    let total = validator_list.iter().map(weight).sum::<u64>();
+    let fraction = if total == 0 {
+        0.0
+    } else {
+        signed as f64 / total as f64
+    };
+    (signed, total, fraction)
+}
+
+fn sum_weights(mut weights: impl Iterator<Item = u32>) -> Option<u64> {
+    weights.try_fold(0u64, |total, weight| total.checked_add(weight.into()))
+}
+
+/// Step 3: check that more than two thirds of the validators signed.
+pub fn check_quorum(signer_count: usize, validator_count: usize) -> Result<(), Error> {
+    log::debug!(
+        "signer count: {}, validator len: {}",
+        signer_count,
+        validator_count
+    );
+
+    if !has_quorum(signer_count as u64, validator_count as u64) {
+        return Err(Error::NotEnoughSignatures);
+    }
+
+    Ok(())
+}
+
+/// The smallest signed weight, or signer count, that is more than two thirds
+/// of `total`.
+pub fn quorum_weight(total: u64) -> u64 {
+    (u128::from(total) * 2 / 3 + 1) as u64
+}
+
+/// Whether `signed` is more than two thirds of `total`, the threshold
+/// [`check_quorum`] applies.
+pub fn has_quorum(signed: u64, total: u64) -> bool {
+    u128::from(signed) * 3 > u128::from(total) * 2
+}
+
+/// The vote weight behind a proof, against the weight it needed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WeightedQuorum {
+    pub signed_weight:   u64,
+    pub total_weight:    u64,
+    /// [`quorum_weight`] of `total_weight`.
+    pub required_weight: u64,
+}
+
+impl WeightedQuorum {
+    pub fn is_reached(&self) -> bool {
+        self.signed_weight >= self.required_weight
+    }
+}
+
+/// Step 3, weighted: check that `signers` hold more than two thirds of the
+/// vote weight of `validator_list`.
+pub fn check_weighted_quorum(
+    signers: &[&ValidatorExtend],
+    validator_list: &[ValidatorExtend],
+) -> Result<WeightedQuorum, Error> {
+    let tally = weigh_signers(signers, validator_list);
+    log::debug!(
+        "signed weight: {}, total weight: {}",
+        tally.signed_weight,
+        tally.total_weight
+    );
+
+    if !tally.is_reached() {
+        return Err(Error::NotEnoughVoteWeight {
+            signed:   tally.signed_weight,
+            required: tally.required_weight,
+        });
+    }
+
+    Ok(tally)
+}
+
+pub(crate) fn weigh_signers(
+    signers: &[&ValidatorExtend],
+    validator_list: &[ValidatorExtend],
+) -> WeightedQuorum {
+    // Overflow is impossible in practice, and rounds towards no quorum.
+    let total_weight = total_vote_weight(validator_list).unwrap_or(u64::MAX);
+    WeightedQuorum {
+        signed_weight: total_vote_weight(signers.iter().copied()).unwrap_or(0),
+        total_weight,
+        required_weight: quorum_weight(total_weight),
+    }
+}
+
+/// Optional step: check that the block was proposed by a member of
+/// `validator_list`.
+pub fn check_proposer(block: &AxonBlock, validator_list: &[ValidatorExtend]) -> Result<(), Error> {
+    if validator_list
+        .iter()
+        .any(|v| v.address == block.header.proposer)
+    {
+        return Ok(());
+    }
+
+    Err(Error::InvalidProposer)
+}
+
+/// Step 4: verify the aggregated BLS signature of the signers over the
+/// precommit vote for the proof's block.
+pub fn check_aggregate_signature(proof: &Proof, signers: &[&ValidatorExtend]) -> Result<(), Error> {
+    check_aggregate_signature_with(proof, signers, &BlsMinPk)
+}
+
+/// Same as [`check_aggregate_signature`] with the signature checked by
+/// `crypto`.
+pub fn check_aggregate_signature_with(
+    proof: &Proof,
+    signers: &[&ValidatorExtend],
+    crypto: &dyn ConsensusCrypto,
+) -> Result<(), Error> {
+    let vote = precommit_vote(proof);
+    let pks = signers.iter().map(|v| &v.bls_pub_key[..]).collect::<Vec<_>>();
+    let res = crypto.verify_aggregate(&vote_hash(&vote), &proof.signature, &pks);
+    res.map_err(|e| match e {
+        Error::InvalidPublicKey { index, error } => Error::InvalidValidatorKey {
+            address: signers[index].address,
+            error,
+        },
+        e => e,
+    })
+}
+
+/// Check every BLS public key of `validator_list` the way verification does,
+/// returning the index, address and error of each invalid one. Useful to lint
+/// a metadata before any proof is verified against it.
+pub fn find_invalid_keys(validator_list: &[ValidatorExtend]) -> Vec<(usize, H160, BLST_ERROR)> {
+    validator_list
+        .iter()
+        .enumerate()
+        .filter_map(|(i, v)| {
+            PublicKey::key_validate(&v.bls_pub_key)
+                .err()
+                .map(|e| (i, v.address, e))
+        })
+        .collect()
+}
+
+/// Verify one validator's signature over `vote`, as gossiped before votes are
+/// aggregated.
+pub fn verify_vote_signature(vote: &Vote, signature: &[u8], pubkey: &[u8]) -> Result<(), Error> {
+    verify_aggregate_signature(&vote_hash(vote), signature, &[pubkey])
+}
+
+/// The precommit vote the signers of `proof` signed.
+pub(crate) fn precommit_vote(proof: &Proof) -> Vote {
+    Vote {
         height:     proof.number,
         round:      proof.round,
-        vote_type:  2u8,
+        vote_type:  VoteType::Precommit,
         block_hash: Bytes::from(proof.block_hash.0.to_vec()),
-    };
+    }
+}
+
+/// The message validators sign for `vote`.
+pub(crate) fn vote_hash(vote: &Vote) -> [u8; 32] {
+    keccak_256(rlp::encode(vote).as_ref())
+}
+
+/// The intermediate bytes of verifying a proof, for diffing against other
+/// verifier implementations step by step.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct VerificationTrace {
+    pub proposal_rlp:       Bytes,
+    pub proposal_hash:      H256,
+    pub vote_rlp:           Bytes,
+    pub vote_hash:          H256,
+    /// The BLS public keys of the signers, in bitmap order.
+    pub signer_pub_keys:    Vec<Bytes>,
+    /// The compressed aggregate of `signer_pub_keys`, empty if one of them is
+    /// not a valid key.
+    pub aggregated_pub_key: Bytes,
+}
+
+/// Record the bytes [`verify_proof`] hashes and aggregates for `block` and
+/// `proof`, checking nothing but that the block has a proposal to encode: a
+/// gas limit wider than a `u64` fails with `Error::GasLimitOverflow`, and
+/// `validator_list` must be sorted by `pub_key` as for [`select_signers`].
+pub fn trace_verification(
+    block: &AxonBlock,
+    previous_state_root: H256,
+    validator_list: &[ValidatorExtend],
+    proof: &Proof,
+) -> Result<VerificationTrace, Error> {
+    let proposal_rlp = Bytes::from(proposal_rlp(block, previous_state_root)?);
+    let vote_rlp = rlp::encode(&precommit_vote(proof)).freeze();
+    let signer_pub_keys = select_signers(proof, validator_list)?
+        .into_iter()
+        .map(|v| v.bls_pub_key.as_bytes())
+        .collect::<Vec<_>>();
+
+    let pks = signer_pub_keys
+        .iter()
+        .map(|pk| PublicKey::key_validate(&pk[..]))
+        .collect::<Result<Vec<_>, _>>();
+    let aggregated_pub_key = pks
+        .and_then(|pks| AggregatePublicKey::aggregate(&pks.iter().collect::<Vec<_>>(), false))
+        .map(|agg| Bytes::copy_from_slice(&agg.to_public_key().compress()))
+        .unwrap_or_default();
+
+    Ok(VerificationTrace {
+        proposal_hash: H256(keccak_256(&proposal_rlp)),
+        proposal_rlp,
+        vote_hash: H256(keccak_256(&vote_rlp)),
+        vote_rlp,
+        signer_pub_keys,
+        aggregated_pub_key,
+    })
+}
 
-    let hash_vote = keccak_256(rlp::encode(&vote).as_ref());
-    let pks = extract_pks(&proof, validator_list)?;
+/// Verify a BLS signature of `message` by all of `pubkeys` with the settings
+/// Axon validators sign with: min-pk, [`BLS_DST`], and group checks on both
+/// the keys and the signature.
+pub fn verify_aggregate_signature(
+    message: &[u8],
+    signature: &[u8],
+    pubkeys: &[&[u8]],
+) -> Result<(), Error> {
+    verify_aggregate_signature_with_dst(message, signature, pubkeys, BLS_DST.as_bytes())
+}
+
+pub(crate) fn verify_aggregate_signature_with_dst(
+    message: &[u8],
+    signature: &[u8],
+    pubkeys: &[&[u8]],
+    dst: &[u8],
+) -> Result<(), Error> {
+    check_signature_length(signature)?;
+    let pks = pubkeys
+        .iter()
+        .enumerate()
+        .map(|(index, pk)| {
+            check_public_key_length(index, pk)?;
+            PublicKey::key_validate(pk).map_err(|error| Error::InvalidPublicKey { index, error })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
     let pks = pks.iter().collect::<Vec<_>>();
-    let c_pk = PublicKey::from_aggregate(&AggregatePublicKey::aggregate(&pks, true)?);
-    let sig = Signature::from_bytes(&proof.signature)?;
-    let res = sig.verify(true, &hash_vote, DST.as_bytes(), &[], &c_pk, true);
+    verify_with_keys(message, signature, &pks, dst)
+}
+
+/// Verify `signature` against `pubkeys`, which must have been group checked,
+/// as `key_validate` does.
+pub(crate) fn verify_with_keys(
+    message: &[u8],
+    signature: &[u8],
+    pubkeys: &[&PublicKey],
+    dst: &[u8],
+) -> Result<(), Error> {
+    let c_pk = PublicKey::from_aggregate(&AggregatePublicKey::aggregate(pubkeys, false)?);
+    let sig = Signature::from_bytes(signature)?;
+    let res = sig.verify(true, message, dst, &[], &c_pk, true);
 
     if res == BLST_ERROR::BLST_SUCCESS {
         return Ok(());
@@ -72,33 +756,600 @@ pub fn verify_proof(
     Err(res.into())
 }
 
-fn extract_pks(
-    proof: &Proof,
-    validator_list: &mut [ValidatorExtend],
-) -> Result<Vec<PublicKey>, Error> {
-    validator_list.sort();
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let bit_map = BitVec::from_bytes(&proof.bitmap);
-    let mut pks = Vec::with_capacity(validator_list.len());
-    let mut count = 0usize;
+    #[test]
+    fn test_quorum_weight() {
+        assert_eq!(quorum_weight(0), 1);
+        assert_eq!(quorum_weight(3), 3);
+        assert_eq!(quorum_weight(4), 3);
+        assert_eq!(quorum_weight(100), 67);
+        assert_eq!(quorum_weight(u64::MAX), u64::MAX / 3 * 2 + 1);
+
+        for total in 0..50 {
+            for signed in 0..=total {
+                assert_eq!(has_quorum(signed, total), signed >= quorum_weight(total));
+                assert_eq!(
+                    has_quorum(signed, total),
+                    check_quorum(signed as usize, total as usize).is_ok()
+                );
+            }
+        }
+        assert!(has_quorum(u64::MAX, u64::MAX));
+    }
+
+    #[cfg(feature = "trie")]
+    #[test]
+    fn test_verify_trie_proofs() {
+        use std::sync::Arc;
+
+        use cita_trie::{MemoryDB, PatriciaTrie, Trie};
+
+        let mut trie = PatriciaTrie::new(Arc::new(MemoryDB::new(true)), Arc::new(InnerKeccak));
+        let keys = (0..20u64).map(|i| rlp::encode(&i).to_vec()).collect::<Vec<_>>();
+        for key in keys.iter() {
+            trie.insert(key.clone(), keccak_256(key).to_vec()).unwrap();
+        }
+        let root = H256::from_slice(&trie.root().unwrap());
+        let mut items = keys
+            .iter()
+            .map(|key| (key.clone(), trie.get_proof(key).unwrap()))
+            .collect::<Vec<_>>();
+        // A key proven absent.
+        let absent = rlp::encode(&100u64).to_vec();
+        items.push((absent.clone(), trie.get_proof(&absent).unwrap()));
 
-    for (v, bit) in validator_list.iter().zip(bit_map.iter()) {
-        if !bit {
-            continue;
+        let mut expect = keys.iter().map(|key| Some(keccak_256(key).to_vec())).collect::<Vec<_>>();
+        expect.push(None);
+        for threads in [1, 3, 8] {
+            assert_eq!(verify_trie_proofs_on(root, &items, threads).unwrap(), expect);
         }
+        assert_eq!(verify_trie_proofs(root, &items).unwrap(), expect);
 
-        pks.push(PublicKey::from_bytes(&v.bls_pub_key.as_bytes())?);
-        count += 1;
+        // A proof that does not lead to its key proves the key absent, which
+        // is not an error.
+        assert_eq!(verify_trie_proof(root, &absent, items[20].1.clone()).unwrap(), None);
+        assert_eq!(verify_trie_proof(root, &keys[5], items[6].1.clone()).unwrap(), None);
+
+        // The root node is tampered with in the proof of 5 and truncated in
+        // the one of 12.
+        items[5].1[0][10] ^= 1;
+        let half = items[12].1[0].len() / 2;
+        items[12].1[0].truncate(half);
+        assert!(verify_trie_proof(root, &keys[5], items[5].1.clone()).is_err());
+        assert!(verify_trie_proof(root, &keys[12], items[12].1.clone()).is_err());
+        for threads in [1, 3, 8] {
+            assert!(verify_trie_proofs_on(root, &items, threads).is_err());
+        }
+        assert!(verify_trie_proofs(H256::zero(), &items).is_err());
     }
 
-    log::debug!(
-        "extract_pks count: {}, validator len: {}",
-        count,
-        validator_list.len()
-    );
-    if count * 3 <= validator_list.len() * 2 {
-        return Err(Error::NotEnoughSignatures);
+    #[cfg(all(feature = "trie", feature = "impl-serde"))]
+    #[test]
+    fn test_verify_eth_get_proof() {
+        // The account at 0xff..01 in a state of four accounts, with slots 0, 1
+        // and 7 set. Slot 2 is proven empty.
+        let state_root = "0x294958681858cf0358817b74248ab6d3614e74a0e29db117aa51e5344d9e0ede";
+        let state_root: H256 = serde_json::from_value(state_root.into()).unwrap();
+        let response: EIP1186ProofResponse = serde_json::from_str(
+            r#"{
+            "address": "0xffffffffffffffffffffffffffffffffffffff01",
+            "balance": "0xde0b6b3a7640000",
+            "codeHash": "0x07ad118d6cc8642c86c03827f276d8b791a65e5c99a3845faf186be720a1455d",
+            "nonce": "0x1",
+            "storageHash": "0x2f95ecd85ffa58b688f86b1c03210f9d7d9094b071acd9129ce52e0cc80677eb",
+            "accountProof": [
+                "0xf871808080808080a05b087b61a2c78e97b39f6784ee1c688fe9c96058d2c09bf688cdc2656a34f85d8080808080a0e5b7bc85f55d602443d21a611f9986849cd2d8be17ca3fb9405e98febb637f7fa08ab5823eac84783bb226a08d1d965860cd838b7aa7b294e7605766519c8e6a3a808080",
+                "0xf871a03a54ef9abe2f30b878d95e93cf9f1400c5b787b6abeb0337e633516cd6867eaeb84ef84c01880de0b6b3a7640000a02f95ecd85ffa58b688f86b1c03210f9d7d9094b071acd9129ce52e0cc80677eba007ad118d6cc8642c86c03827f276d8b791a65e5c99a3845faf186be720a1455d"
+            ],
+            "storageProof": [
+                {
+                    "key": "0x0",
+                    "value": "0x2a",
+                    "proof": [
+                        "0xf8718080a0f73cea67884580eec8c3f6d0746360906cf897bf812183520e51b89a12166cfe80808080808080a0c5d54b915b56a888eee4e6eeb3141e778f9b674d1d322962eed900f02c29990aa0d8ced68182603b02289e8460a738cf03bc4c50bfbffb7c4f44466ff4827f16238080808080",
+                        "0xe2a0390decd9548b62a8d60345a988386fc84ba6bc95484008f6362f93160ef3e5632a"
+                    ]
+                },
+                {
+                    "key": "0x2",
+                    "value": "0x0",
+                    "proof": [
+                        "0xf8718080a0f73cea67884580eec8c3f6d0746360906cf897bf812183520e51b89a12166cfe80808080808080a0c5d54b915b56a888eee4e6eeb3141e778f9b674d1d322962eed900f02c29990aa0d8ced68182603b02289e8460a738cf03bc4c50bfbffb7c4f44466ff4827f16238080808080"
+                    ]
+                }
+            ]
+        }"#,
+        )
+        .unwrap();
+        assert!(verify_eth_get_proof(state_root, &response).is_ok());
+        assert!(verify_eth_get_proof(H256(rand::random()), &response).is_err());
+
+        let mut tampered = response.clone();
+        tampered.balance += U256::one();
+        assert!(matches!(
+            verify_eth_get_proof(state_root, &tampered),
+            Err(Error::VerifyMptProof)
+        ));
+
+        let mut tampered = response.clone();
+        tampered.storage_proof[0].value = U256::from(0x2b);
+        assert!(matches!(
+            verify_eth_get_proof(state_root, &tampered),
+            Err(Error::VerifyMptProof)
+        ));
+
+        // A value claimed for a slot the trie proves empty.
+        let mut tampered = response.clone();
+        tampered.storage_proof[1].value = U256::one();
+        assert!(verify_eth_get_proof(state_root, &tampered).is_err());
+
+        let mut tampered = response;
+        let mut node = tampered.account_proof[1].as_bytes().to_vec();
+        node[40] ^= 1;
+        tampered.account_proof[1] = crate::types::Hex::encode(node);
+        assert!(verify_eth_get_proof(state_root, &tampered).is_err());
+    }
+
+    #[test]
+    fn test_check_bitmap() {
+        let proof = |bitmap: &'static [u8]| Proof {
+            bitmap: Bytes::from_static(bitmap),
+            ..Default::default()
+        };
+        assert!(check_bitmap(&proof(&[0b1110_0000]), 3).is_ok());
+        assert!(check_bitmap(&proof(&[0xff]), 8).is_ok());
+        assert!(check_bitmap(&proof(&[0xff, 0b1000_0000]), 9).is_ok());
+        assert!(check_bitmap(&proof(&[]), 0).is_ok());
+
+        assert!(matches!(
+            check_bitmap(&proof(&[0b1111_0000]), 3),
+            Err(Error::BitmapTrailingBits)
+        ));
+        assert!(matches!(
+            check_bitmap(&proof(&[0xff, 0]), 8),
+            Err(Error::BitmapLengthMismatch { expect: 1, actual: 2 })
+        ));
+        assert!(matches!(
+            check_bitmap(&proof(&[0xff]), 9),
+            Err(Error::BitmapLengthMismatch { expect: 2, actual: 1 })
+        ));
     }
 
-    Ok(pks)
+    #[test]
+    fn test_check_weighted_quorum() {
+        let validator_list = [1, 1, 5]
+            .map(|vote_weight| ValidatorExtend {
+                vote_weight,
+                ..Default::default()
+            })
+            .to_vec();
+        let [light, other, heavy] = [&validator_list[0], &validator_list[1], &validator_list[2]];
+
+        assert!(matches!(
+            check_weighted_quorum(&[light, other], &validator_list),
+            Err(Error::NotEnoughVoteWeight { signed: 2, required: 5 })
+        ));
+        let tally = check_weighted_quorum(&[light, heavy], &validator_list).unwrap();
+        assert_eq!((tally.signed_weight, tally.total_weight), (6, 7));
+        assert_eq!(tally.required_weight, 5);
+    }
+
+    #[test]
+    fn test_check_proposer() {
+        use crate::types::tests::{mock_header, mock_validator};
+
+        let validator_list = (1..=3u8).map(mock_validator).collect::<Vec<_>>();
+        let mut block = AxonBlock {
+            header:    mock_header(),
+            tx_hashes: vec![],
+        };
+
+        block.header.proposer = validator_list[1].address;
+        assert!(check_proposer(&block, &validator_list).is_ok());
+
+        block.header.proposer = H160::repeat_byte(4);
+        assert!(matches!(
+            check_proposer(&block, &validator_list),
+            Err(Error::InvalidProposer)
+        ));
+        assert!(matches!(check_proposer(&block, &[]), Err(Error::InvalidProposer)));
+    }
+
+    #[test]
+    fn test_verify_proof_weighted() {
+        use blst::min_pk::{AggregateSignature, SecretKey};
+
+        use crate::types::tests::{mock_header, mock_validator};
+        use crate::types::Hex;
+
+        let sks = (1..=3u8)
+            .map(|i| SecretKey::key_gen(&[i; 32], &[]).unwrap())
+            .collect::<Vec<_>>();
+        let validator_list = sks
+            .iter()
+            .zip([(1u8, 1), (2, 1), (3, 5)])
+            .map(|(sk, (seed, vote_weight))| ValidatorExtend {
+                bls_pub_key: Hex::encode(sk.sk_to_pk().to_bytes()),
+                vote_weight,
+                ..mock_validator(seed)
+            })
+            .collect::<Vec<_>>();
+        let block = AxonBlock {
+            header:    mock_header(),
+            tx_hashes: vec![],
+        };
+        // Two of three signers, but six of seven in vote weight.
+        let mut proof = Proof {
+            number:     block.header.number,
+            block_hash: H256(keccak_256(&proposal_rlp(&block, H256::zero()).unwrap())),
+            bitmap:     Bytes::from_static(&[0b1010_0000]),
+            ..Default::default()
+        };
+        let message = vote_hash(&precommit_vote(&proof));
+        let sigs = [&sks[0], &sks[2]].map(|sk| sk.sign(&message, BLS_DST.as_bytes(), &[]));
+        let sig = AggregateSignature::aggregate(&[&sigs[0], &sigs[1]], true).unwrap();
+        proof.signature = Bytes::from(sig.to_signature().to_bytes().to_vec());
+
+        assert!(matches!(
+            verify_proof(&block, H256::zero(), &validator_list, &proof),
+            Err(Error::NotEnoughSignatures)
+        ));
+        let report = verify_proof_weighted(&block, H256::zero(), &validator_list, &proof);
+        let quorum = report.unwrap().quorum;
+        assert_eq!((quorum.signed_weight, quorum.total_weight), (6, 7));
+    }
+
+    #[test]
+    fn test_select_metadata() {
+        use crate::types::MetadataBuilder;
+
+        let epoch_0 = MetadataBuilder::new().version(1, 100).epoch(0).build();
+        let epoch_1 = MetadataBuilder::next_epoch(&epoch_0).unwrap().build();
+        let metadata = [epoch_1.clone(), epoch_0.clone()];
+
+        assert_eq!(select_metadata(&metadata, 1).unwrap(), &epoch_0);
+        assert_eq!(select_metadata(&metadata, 100).unwrap(), &epoch_0);
+        assert_eq!(select_metadata(&metadata, 101).unwrap(), &epoch_1);
+        assert!(matches!(
+            select_metadata(&metadata, 0),
+            Err(Error::MetadataNotFound(0))
+        ));
+        assert!(matches!(
+            select_metadata(&metadata, 201),
+            Err(Error::MetadataNotFound(201))
+        ));
+
+        let overlapping = MetadataBuilder::new().version(50, 150).build();
+        assert!(matches!(
+            select_metadata(&[epoch_0, overlapping], 60),
+            Err(Error::OverlappingMetadata(60))
+        ));
+    }
+
+    #[test]
+    fn test_signed_power() {
+        let validator_list = [1, 2, 3, 4]
+            .map(|vote_weight| Validator {
+                pub_key:        Bytes::new(),
+                propose_weight: 1,
+                vote_weight,
+            })
+            .to_vec();
+        let proof = Proof {
+            bitmap: Bytes::from_static(&[0b1010_0000]),
+            ..Default::default()
+        };
+        assert_eq!(signed_power(&proof, &validator_list), (4, 10, 0.4));
+        assert_eq!(signed_power(&proof, &[]), (0, 0, 0.0));
+    }
+
+    #[test]
+    fn test_verify_proof_with_parent() {
+        use crate::types::tests::mock_header;
+
+        let parent = mock_header();
+        let mut block = AxonBlock {
+            header:    mock_header(),
+            tx_hashes: Vec::new(),
+        };
+        let proof = block.header.proof.clone();
+        assert!(matches!(
+            verify_proof_with_parent(&block, &parent, &[], &proof),
+            Err(Error::ParentHashMismatch)
+        ));
+
+        block.header.prev_hash = H256(keccak_256(&parent.rlp_bytes()));
+        assert!(matches!(
+            verify_proof_with_parent(&block, &parent, &[], &proof),
+            Err(Error::InvalidProofBlockHash)
+        ));
+    }
+
+    #[test]
+    fn test_verify_aggregate_signature() {
+        use blst::min_pk::{AggregateSignature, SecretKey};
+
+        let message = keccak_256(b"checkpoint");
+        let sks = (1..=3u8)
+            .map(|i| SecretKey::key_gen(&[i; 32], &[]).unwrap())
+            .collect::<Vec<_>>();
+        let pks = sks.iter().map(|sk| sk.sk_to_pk().to_bytes()).collect::<Vec<_>>();
+        let pks = pks.iter().map(|pk| &pk[..]).collect::<Vec<_>>();
+        let sigs = sks
+            .iter()
+            .map(|sk| sk.sign(&message, BLS_DST.as_bytes(), &[]))
+            .collect::<Vec<_>>();
+        let sigs = sigs.iter().collect::<Vec<_>>();
+        let sig = AggregateSignature::aggregate(&sigs, true)
+            .unwrap()
+            .to_signature()
+            .to_bytes();
+
+        assert!(verify_aggregate_signature(&message, &sig, &pks).is_ok());
+        assert!(verify_aggregate_signature(&message, &sig, &pks[..2]).is_err());
+        assert!(verify_aggregate_signature(&[0u8; 32], &sig, &pks).is_err());
+    }
+
+    #[test]
+    fn test_find_invalid_keys() {
+        use blst::min_pk::SecretKey;
+
+        use crate::types::tests::{mock_validator, random_bytes};
+        use crate::types::Hex;
+
+        let valid = SecretKey::key_gen(&[1u8; 32], &[]).unwrap().sk_to_pk();
+        let validator = |seed: u8, bls_pub_key: &[u8]| ValidatorExtend {
+            bls_pub_key: Hex::encode(bls_pub_key),
+            ..mock_validator(seed)
+        };
+        let validator_list = vec![
+            validator(1, &valid.to_bytes()),
+            validator(2, &[0u8; 48]),
+            validator(3, &[1u8; 10]),
+        ];
+
+        let proof = Proof {
+            signature: random_bytes(96),
+            ..Default::default()
+        };
+        assert!(matches!(
+            check_lengths(&proof, &validator_list),
+            Err(Error::InvalidPublicKeyLength { index: 2, len: 10 })
+        ));
+        let proof = Proof {
+            signature: random_bytes(95),
+            ..Default::default()
+        };
+        assert!(matches!(
+            check_lengths(&proof, &validator_list[..2]),
+            Err(Error::InvalidSignatureLength(95))
+        ));
+
+        let invalid = find_invalid_keys(&validator_list);
+        assert_eq!(invalid.len(), 2);
+        assert_eq!(invalid[0].0, 1);
+        assert_eq!(invalid[1].1, validator_list[2].address);
+
+        let pks = validator_list.iter().map(|v| &v.bls_pub_key[..]).collect::<Vec<_>>();
+        assert!(matches!(
+            verify_aggregate_signature(&[0u8; 32], &[0u8; 96], &pks),
+            Err(Error::InvalidPublicKey { index: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_verify_vote_signature() {
+        use blst::min_pk::SecretKey;
+
+        let sk = SecretKey::key_gen(&[7u8; 32], &[]).unwrap();
+        let pk = sk.sk_to_pk().to_bytes();
+        let mut vote = Vote {
+            height:     10,
+            round:      0,
+            vote_type:  VoteType::Prevote,
+            block_hash: Bytes::from(vec![1u8; 32]),
+        };
+        let sig = sk
+            .sign(&vote_hash(&vote), BLS_DST.as_bytes(), &[])
+            .to_bytes();
+
+        assert!(verify_vote_signature(&vote, &sig, &pk).is_ok());
+        vote.vote_type = VoteType::Precommit;
+        assert!(verify_vote_signature(&vote, &sig, &pk).is_err());
+    }
+
+    #[test]
+    fn test_verify_proof_with_metrics() {
+        use core::cell::{Cell, RefCell};
+        use core::time::Duration;
+
+        use crate::types::tests::mock_header;
+
+        /// A clock that advances a millisecond at each reading, if any.
+        #[derive(Default)]
+        struct RecordingMetrics {
+            clock:     Option<Cell<u64>>,
+            durations: RefCell<Vec<Duration>>,
+        }
+
+        impl Metrics for RecordingMetrics {
+            fn now(&self) -> Option<Duration> {
+                let clock = self.clock.as_ref()?;
+                clock.set(clock.get() + 1);
+                Some(Duration::from_millis(clock.get()))
+            }
+
+            fn observe_verify_duration(&self, duration: Duration) {
+                self.durations.borrow_mut().push(duration);
+            }
+        }
+
+        let block = AxonBlock {
+            header:    mock_header(),
+            tx_hashes: vec![],
+        };
+        let proof = block.header.proof.clone();
+        let verify = |metrics: &RecordingMetrics| {
+            let res = verify_proof_with_metrics(&block, H256::zero(), &[], &proof, metrics);
+            assert!(matches!(res, Err(Error::InvalidProofBlockHash)));
+        };
+
+        let metrics = RecordingMetrics::default();
+        verify(&metrics);
+        assert!(metrics.durations.borrow().is_empty());
+
+        let metrics = RecordingMetrics {
+            clock: Some(Cell::new(0)),
+            ..Default::default()
+        };
+        verify(&metrics);
+        assert_eq!(*metrics.durations.borrow(), vec![Duration::from_millis(1)]);
+    }
+
+    #[test]
+    fn test_verify_proof_with_crypto() {
+        use crate::types::tests::{mock_header, mock_validator};
+        use crate::types::{Hex, Proposal};
+
+        /// Accepts an aggregate signature that is the concatenation of the
+        /// signers' keys.
+        struct ConcatCrypto;
+
+        impl ConsensusCrypto for ConcatCrypto {
+            fn verify_aggregate(
+                &self,
+                _message: &[u8],
+                signature: &[u8],
+                pubkeys: &[&[u8]],
+            ) -> Result<(), Error> {
+                if pubkeys.concat() != signature {
+                    return Err(Error::NotEnoughSignatures);
+                }
+                Ok(())
+            }
+        }
+
+        let mut validator_list = (1..=3u8)
+            .map(|i| ValidatorExtend {
+                bls_pub_key: Hex::encode([i; 4]),
+                ..mock_validator(i)
+            })
+            .collect::<Vec<_>>();
+        let block = AxonBlock {
+            header:    mock_header(),
+            tx_hashes: vec![],
+        };
+        let proposal = Proposal::from_block(&block, H256::zero()).unwrap();
+        let mut proof = Proof {
+            number:     block.header.number,
+            block_hash: H256(keccak_256(&proposal.rlp_bytes())),
+            signature:  Bytes::from([[1u8; 4], [2u8; 4], [3u8; 4]].concat()),
+            bitmap:     Bytes::from_static(&[0b1110_0000]),
+            ..Default::default()
+        };
+
+        let res = verify_proof_with_crypto(
+            &block,
+            H256::zero(),
+            &validator_list,
+            &proof,
+            &ConcatCrypto,
+        );
+        let report = res.unwrap();
+        assert_eq!(report.proposal_hash, proof.block_hash);
+        assert_eq!(report.signer_indices, vec![0, 1, 2]);
+        let addresses = validator_list.iter().map(|v| v.address).collect::<Vec<_>>();
+        assert_eq!(report.signers, addresses);
+        assert!(matches!(
+            verify_proof(&block, H256::zero(), &validator_list, &proof),
+            Err(Error::InvalidSignatureLength(12))
+        ));
+
+        // The list is checked to be in bitmap order, not sorted into it.
+        validator_list.swap(0, 2);
+        let res = verify_proof_with_crypto(
+            &block,
+            H256::zero(),
+            &validator_list,
+            &proof,
+            &ConcatCrypto,
+        );
+        assert!(matches!(res, Err(Error::ValidatorsOutOfOrder { index: 1 })));
+        validator_list.swap(0, 2);
+
+        proof.signature.truncate(8);
+        let res = verify_proof_with_crypto(
+            &block,
+            H256::zero(),
+            &validator_list,
+            &proof,
+            &ConcatCrypto,
+        );
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn test_trace_verification() {
+        use blst::min_pk::SecretKey;
+
+        use crate::types::tests::{mock_header, mock_validator};
+        use crate::types::Hex;
+
+        let pks = (1..=2u8)
+            .map(|i| SecretKey::key_gen(&[i; 32], &[]).unwrap().sk_to_pk())
+            .collect::<Vec<_>>();
+        let mut validator_list = pks
+            .iter()
+            .zip(1..=2u8)
+            .map(|(pk, i)| ValidatorExtend {
+                bls_pub_key: Hex::encode(pk.to_bytes()),
+                ..mock_validator(i)
+            })
+            .collect::<Vec<_>>();
+        let block = AxonBlock {
+            header:    mock_header(),
+            tx_hashes: vec![],
+        };
+        let proof = Proof {
+            number: block.header.number,
+            bitmap: Bytes::from_static(&[0b1100_0000]),
+            ..Default::default()
+        };
+
+        let trace = trace_verification(&block, H256::zero(), &validator_list, &proof).unwrap();
+        assert_eq!(trace.proposal_hash, H256(keccak_256(&trace.proposal_rlp)));
+        assert_eq!(rlp::decode::<Vote>(&trace.vote_rlp).unwrap(), precommit_vote(&proof));
+        assert_eq!(trace.vote_hash.0, vote_hash(&precommit_vote(&proof)));
+        assert_eq!(trace.signer_pub_keys.len(), 2);
+
+        let pks = pks.iter().collect::<Vec<_>>();
+        let expect = AggregatePublicKey::aggregate(&pks, false).unwrap();
+        assert_eq!(&trace.aggregated_pub_key[..], &expect.to_public_key().compress()[..]);
+
+        validator_list[0].bls_pub_key = Hex::encode([0u8; 48]);
+        let trace = trace_verification(&block, H256::zero(), &validator_list, &proof).unwrap();
+        assert!(trace.aggregated_pub_key.is_empty());
+
+        // The list is checked to be in bitmap order, not sorted into it.
+        validator_list.reverse();
+        assert!(matches!(
+            select_signers(&proof, &validator_list),
+            Err(Error::ValidatorsOutOfOrder { index: 1 })
+        ));
+        assert!(matches!(
+            trace_verification(&block, H256::zero(), &validator_list, &proof),
+            Err(Error::ValidatorsOutOfOrder { index: 1 })
+        ));
+        validator_list.reverse();
+
+        let mut block = block;
+        block.header.gas_limit = ethereum_types::U256::one() << 64;
+        assert!(matches!(
+            trace_verification(&block, H256::zero(), &validator_list, &proof),
+            Err(Error::GasLimitOverflow)
+        ));
+    }
 }
@@ -0,0 +1,241 @@
+//! The proposer Overlord expects at a height and round, from the validators
+//! sorted by `pub_key`. Overlord's default build rotates through them,
+//! ignoring weights. With its `random_leader` feature it draws the proposer
+//! at random, weighted by `propose_weight`, from a PCG generator seeded with
+//! `height + round`. A block whose proposer is not [`expected_proposer`] was
+//! proposed out of turn.
+
+use ethereum_types::H160;
+
+use crate::types::{Metadata, ValidatorExtend};
+
+/// How Overlord picks the proposer of a round.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ProposerSelection {
+    /// The default: `(height * p + round) % n` for `n` validators, where `p`
+    /// is the largest prime below `n`, or 1 if there is none.
+    #[default]
+    Rotation,
+    /// The `random_leader` feature: a draw weighted by `propose_weight`.
+    Random,
+}
+
+/// The address of the validator of `metadata` expected to propose block
+/// `height` in `round` under Overlord's default rotation, `None` if there is
+/// no validator.
+pub fn expected_proposer(height: u64, round: u64, metadata: &Metadata) -> Option<H160> {
+    expected_proposer_with(height, round, metadata, ProposerSelection::Rotation)
+}
+
+/// Same as [`expected_proposer`] for a chain whose Overlord picks proposers
+/// by `selection`. With [`ProposerSelection::Random`] it is also `None` if no
+/// validator has any propose weight.
+pub fn expected_proposer_with(
+    height: u64,
+    round: u64,
+    metadata: &Metadata,
+    selection: ProposerSelection,
+) -> Option<H160> {
+    let validators = metadata.to_validators();
+    let index = match selection {
+        ProposerSelection::Rotation => rotation_index(height, round, validators.len())?,
+        ProposerSelection::Random => proposer_index(height.wrapping_add(round), &validators)?,
+    };
+    Some(validators[index].address)
+}
+
+/// Overlord's `rotation_leader_index`. It wraps where Overlord's release
+/// build would.
+fn rotation_index(height: u64, round: u64, len: usize) -> Option<usize> {
+    if len == 0 {
+        return None;
+    }
+
+    let len = len as u64;
+    let prime = (2..len).rev().find(|&n| is_prime(n)).unwrap_or(1);
+    let index = height.wrapping_mul(prime).wrapping_add(round) % len;
+    Some(index as usize)
+}
+
+fn is_prime(n: u64) -> bool {
+    n >= 2 && (2..).take_while(|d| d * d <= n).all(|d| !n.is_multiple_of(d))
+}
+
+/// Draw below the largest multiple of the total weight that fits in a `u64`,
+/// so that every unit of weight is as likely, and pick the validator whose
+/// share of that range holds the draw.
+fn proposer_index(seed: u64, validators: &[ValidatorExtend]) -> Option<usize> {
+    let weight_sum = validators
+        .iter()
+        .map(|v| u64::from(v.propose_weight))
+        .sum::<u64>();
+    if weight_sum == 0 {
+        return None;
+    }
+
+    let unit = u64::MAX / weight_sum;
+    let mut rng = Pcg64Mcg::seed_from_u64(seed);
+    let mut draw = rng.next_u64();
+    while draw >= weight_sum * unit {
+        draw = rng.next_u64();
+    }
+
+    let mut acc = 0;
+    validators.iter().position(|v| {
+        acc += u64::from(v.propose_weight);
+        draw < acc * unit
+    })
+}
+
+/// The `Pcg64Mcg` generator of `rand_pcg` Overlord draws with, seeded as
+/// `rand_core`'s `SeedableRng::seed_from_u64` does.
+struct Pcg64Mcg {
+    state: u128,
+}
+
+impl Pcg64Mcg {
+    const MULTIPLIER: u128 = 0x2360_ed05_1fc6_5da4_4385_df64_9fcc_f645;
+
+    fn seed_from_u64(mut seed: u64) -> Self {
+        const MUL: u64 = 6_364_136_223_846_793_005;
+        const INC: u64 = 11_634_580_027_462_260_723;
+
+        // Expand the seed with PCG32, four bytes at a time.
+        let mut bytes = [0u8; 16];
+        for chunk in bytes.chunks_mut(4) {
+            seed = seed.wrapping_mul(MUL).wrapping_add(INC);
+            let xorshifted = (((seed >> 18) ^ seed) >> 27) as u32;
+            let rot = (seed >> 59) as u32;
+            chunk.copy_from_slice(&xorshifted.rotate_right(rot).to_le_bytes());
+        }
+
+        // A multiplicative generator needs an odd state.
+        Pcg64Mcg {
+            state: u128::from_le_bytes(bytes) | 1,
+        }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_mul(Self::MULTIPLIER);
+        let rot = (self.state >> 122) as u32;
+        (((self.state >> 64) as u64) ^ (self.state as u64)).rotate_right(rot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use bytes::Bytes;
+    use overlord::types::Node;
+
+    use super::*;
+    use crate::types::tests::mock_validator;
+
+    #[test]
+    fn test_pcg64_mcg() {
+        // The first draws of `rand_pcg::Pcg64Mcg::seed_from_u64`.
+        let cases = [
+            (0, [0x5603_f242_407d_eca2, 0xd684_5f75_e9e2_1acd, 0x5a22_d8b7_6f39_f418]),
+            (101, [0xd843_1fee_a835_3566, 0xa07c_e3bc_201a_dbba, 0xe0f6_7f74_994c_50a1]),
+        ];
+        for (seed, draws) in cases {
+            let mut rng = Pcg64Mcg::seed_from_u64(seed);
+            assert_eq!(draws.map(|_| rng.next_u64()), draws);
+        }
+    }
+
+    // Overlord's authority list, whose addresses are the validators' public
+    // keys.
+    fn authority_list(metadata: &Metadata) -> Vec<Node> {
+        metadata
+            .verifier_list
+            .iter()
+            .map(|v| Node {
+                address:        Bytes::copy_from_slice(&v.pub_key),
+                propose_weight: v.propose_weight,
+                vote_weight:    v.vote_weight,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_rotation_matches_overlord() {
+        for len in 1..=7u8 {
+            let metadata = Metadata {
+                verifier_list: (1..=len).rev().map(mock_validator).collect(),
+                ..Default::default()
+            };
+            let validators = metadata.to_validators();
+            for height in 0..20 {
+                for round in 0..3 {
+                    let leader = overlord::get_leader(height, round, authority_list(&metadata));
+                    let index = validators
+                        .iter()
+                        .position(|v| v.pub_key[..] == leader[..])
+                        .unwrap();
+                    assert_eq!(
+                        expected_proposer(height, round, &metadata),
+                        Some(validators[index].address),
+                        "{} validators, height {}, round {}",
+                        len,
+                        height,
+                        round
+                    );
+                }
+            }
+        }
+
+        assert_eq!(expected_proposer(0, 0, &Metadata::default()), None);
+    }
+
+    #[test]
+    fn test_proposer_order_matches_overlord() {
+        let metadata = Metadata {
+            verifier_list: [3u8, 1, 4, 2]
+                .map(|seed| ValidatorExtend {
+                    propose_weight: u32::from(seed),
+                    ..mock_validator(seed)
+                })
+                .to_vec(),
+            ..Default::default()
+        };
+
+        // Overlord sorts its authority list before picking from it.
+        let mut authority_list = authority_list(&metadata);
+        authority_list.sort();
+        let validators = metadata.to_validators();
+        for (node, v) in authority_list.iter().zip(&validators) {
+            assert_eq!(&node.address[..], &v.pub_key[..]);
+            assert_eq!(node.propose_weight, v.propose_weight);
+        }
+
+        // The draw of seed 0 is 0x5603_f242_407d_eca2, about a third of the
+        // range, in the share of the third validator, [3/10, 6/10).
+        let random = |height, round, metadata: &Metadata| {
+            expected_proposer_with(height, round, metadata, ProposerSelection::Random)
+        };
+        assert_eq!(random(0, 0, &metadata), Some(validators[2].address));
+        assert_eq!(random(1, 0, &metadata), random(0, 1, &metadata));
+
+        let mut counts = [0usize; 4];
+        for height in 0..10_000 {
+            let proposer = random(height, 0, &metadata).unwrap();
+            counts[validators.iter().position(|v| v.address == proposer).unwrap()] += 1;
+        }
+        // About 1000, 2000, 3000 and 4000.
+        for (i, count) in counts.iter().enumerate() {
+            assert!(count.abs_diff((i + 1) * 1000) < 200, "{:?}", counts);
+        }
+
+        let unweighted = Metadata {
+            verifier_list: vec![ValidatorExtend {
+                propose_weight: 0,
+                ..mock_validator(1)
+            }],
+            ..Default::default()
+        };
+        assert_eq!(random(0, 0, &unweighted), None);
+        assert!(expected_proposer(0, 0, &unweighted).is_some());
+    }
+}
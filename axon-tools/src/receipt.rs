@@ -0,0 +1,270 @@
+#[cfg(feature = "trie")]
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use bytes::Bytes;
+#[cfg(feature = "trie")]
+use cita_trie::{MemoryDB, PatriciaTrie, Trie};
+use ethereum_types::{Bloom, H160, H256, U256};
+#[cfg(feature = "impl-serde")]
+use faster_hex::withpfx_lowercase;
+#[cfg(feature = "impl-rlp")]
+use rlp::{Encodable, RlpStream};
+
+#[cfg(feature = "trie")]
+use crate::hash::InnerKeccak;
+#[cfg(all(feature = "trie", feature = "impl-serde"))]
+use crate::rpc::TransactionReceipt;
+use crate::types::{AxonHeader, TypesError};
+#[cfg(feature = "trie")]
+use crate::Error;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "impl-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Log {
+    pub address: H160,
+    pub topics:  Vec<H256>,
+    #[cfg_attr(
+        feature = "impl-serde",
+        serde(
+            serialize_with = "withpfx_lowercase::serialize",
+            deserialize_with = "withpfx_lowercase::deserialize"
+        )
+    )]
+    pub data:    Bytes,
+}
+
+#[cfg(feature = "impl-rlp")]
+impl Encodable for Log {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(3)
+            .append(&self.address)
+            .append_list(&self.topics)
+            .append(&self.data.to_vec());
+    }
+}
+
+/// The consensus part of a transaction receipt, i.e. what is committed in the
+/// receipts trie of a block.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "impl-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Receipt {
+    #[cfg_attr(feature = "impl-serde", serde(alias = "txType"))]
+    pub tx_type:             u8,
+    pub status:              u8,
+    #[cfg_attr(feature = "impl-serde", serde(alias = "cumulativeGasUsed"))]
+    pub cumulative_gas_used: U256,
+    #[cfg_attr(feature = "impl-serde", serde(alias = "logsBloom"))]
+    pub logs_bloom:          Bloom,
+    pub logs:                Vec<Log>,
+}
+
+/// Encode a receipt the way it is committed in the receipts trie: the RLP
+/// list `[status, cumulative_gas_used, logs_bloom, logs]`, prefixed with the
+/// EIP-2718 transaction type unless the transaction is legacy.
+#[cfg(feature = "impl-rlp")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "impl-rlp")))]
+pub fn encode_receipt(receipt: &Receipt) -> Vec<u8> {
+    let mut s = RlpStream::new_list(4);
+    s.append(&receipt.status)
+        .append(&receipt.cumulative_gas_used)
+        .append(&receipt.logs_bloom)
+        .append_list(&receipt.logs);
+
+    let mut ret = Vec::new();
+    if receipt.tx_type != 0 {
+        ret.push(receipt.tx_type);
+    }
+    ret.extend_from_slice(&s.out());
+    ret
+}
+
+/// The root of the receipts trie of a block with `receipts`, keyed by the RLP
+/// of each receipt's index.
+#[cfg(feature = "trie")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "trie")))]
+pub fn receipts_root(receipts: &[Receipt]) -> Result<H256, Error> {
+    let db = Arc::new(MemoryDB::new(true));
+    let mut trie = PatriciaTrie::new(db, Arc::new(InnerKeccak));
+    for (index, receipt) in receipts.iter().enumerate() {
+        trie.insert(rlp::encode(&index).to_vec(), encode_receipt(receipt))?;
+    }
+    Ok(H256::from_slice(&trie.root()?))
+}
+
+/// Check the full receipt list of a block, such as the result of
+/// `eth_getBlockReceipts`, against its already verified header. The receipts
+/// are returned only if they belong to the header's block and both the
+/// receipts root and the log bloom match.
+#[cfg(all(feature = "trie", feature = "impl-serde"))]
+#[cfg_attr(doc_cfg, doc(cfg(all(feature = "trie", feature = "impl-serde"))))]
+pub fn verify_block_receipts(
+    header: &AxonHeader,
+    receipts: Vec<TransactionReceipt>,
+) -> Result<Vec<TransactionReceipt>, Error> {
+    if receipts.iter().any(|r| r.block_number != header.number) {
+        return Err(Error::ReceiptBlockMismatch);
+    }
+
+    let consensus = receipts
+        .iter()
+        .enumerate()
+        .map(|(index, r)| {
+            Receipt::try_from(r.clone()).map_err(|error| Error::InvalidReceipt { index, error })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if receipts_root(&consensus)? != header.receipts_root {
+        return Err(Error::ReceiptsRootMismatch);
+    }
+
+    let mut bloom = Bloom::zero();
+    for receipt in consensus.iter() {
+        bloom.accrue_bloom(&receipt.logs_bloom);
+    }
+    if bloom != header.log_bloom {
+        return Err(Error::LogBloomMismatch);
+    }
+
+    Ok(receipts)
+}
+
+/// Check that the cumulative gas used by the receipts of a block never
+/// decreases and that the last one equals the gas used of the header.
+pub fn check_receipts_gas(header: &AxonHeader, receipts: &[Receipt]) -> Result<(), TypesError> {
+    let mut prev = U256::zero();
+    for (index, receipt) in receipts.iter().enumerate() {
+        if receipt.cumulative_gas_used < prev {
+            return Err(TypesError::CumulativeGasDecrease { index });
+        }
+        prev = receipt.cumulative_gas_used;
+    }
+
+    if prev != header.gas_used {
+        return Err(TypesError::GasUsedMismatch {
+            expect: header.gas_used,
+            real:   prev,
+        });
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn receipt(cumulative_gas_used: u64) -> Receipt {
+        Receipt {
+            cumulative_gas_used: cumulative_gas_used.into(),
+            ..Default::default()
+        }
+    }
+
+    #[cfg(feature = "impl-rlp")]
+    #[test]
+    fn test_encode_receipt() {
+        use ethereum_types::BloomInput;
+
+        // The reference encoding from `eth_light_client_in_ckb_prover`, which
+        // Axon uses to build its receipts trie.
+        let logs = vec![Log::default()];
+        let mut logs_bloom = Bloom::zero();
+        logs_bloom.accrue(BloomInput::Raw(logs[0].address.as_bytes()));
+        let receipt = Receipt {
+            tx_type:             2,
+            status:              1,
+            cumulative_gas_used: U256::from(10),
+            logs_bloom,
+            logs,
+        };
+
+        let mut expect = vec![2u8, 249, 1, 30, 1, 10, 185, 1, 0];
+        expect.extend_from_slice(logs_bloom.as_bytes());
+        expect.extend_from_slice(&[216, 215, 148]);
+        expect.extend_from_slice(&[0u8; 20]);
+        expect.extend_from_slice(&[192, 128]);
+        assert_eq!(encode_receipt(&receipt), expect);
+
+        let mut bloom = [0u8; 256];
+        bloom[9] = 128;
+        bloom[47] = 2;
+        bloom[143] = 1;
+        assert_eq!(logs_bloom.as_bytes(), &bloom[..]);
+
+        let legacy = Receipt {
+            tx_type: 0,
+            ..receipt
+        };
+        assert_eq!(encode_receipt(&legacy), expect[1..]);
+    }
+
+    #[cfg(all(feature = "trie", feature = "impl-serde"))]
+    #[test]
+    fn test_verify_block_receipts() {
+        use ethereum_types::BloomInput;
+
+        use crate::consts::EMPTY_TRIE_ROOT;
+
+        assert_eq!(receipts_root(&[]).unwrap(), EMPTY_TRIE_ROOT);
+
+        let mut header = crate::types::tests::mock_header();
+        let receipts = (1..=3u64)
+            .map(|i| {
+                let mut logs_bloom = Bloom::zero();
+                logs_bloom.accrue(BloomInput::Raw(&i.to_be_bytes()));
+                TransactionReceipt {
+                    block_number:        header.number,
+                    cumulative_gas_used: U256::from(21000 * i),
+                    logs_bloom,
+                    transaction_type:    2,
+                    status:              1,
+                    ..Default::default()
+                }
+            })
+            .collect::<Vec<_>>();
+        let consensus = receipts
+            .iter()
+            .map(|r| Receipt::try_from(r.clone()).unwrap())
+            .collect::<Vec<_>>();
+        header.receipts_root = receipts_root(&consensus).unwrap();
+        header.log_bloom = Bloom::zero();
+        for receipt in consensus.iter() {
+            header.log_bloom.accrue_bloom(&receipt.logs_bloom);
+        }
+        assert_eq!(verify_block_receipts(&header, receipts.clone()).unwrap(), receipts);
+
+        let mut tampered = receipts.clone();
+        tampered.swap(0, 1);
+        assert!(matches!(
+            verify_block_receipts(&header, tampered),
+            Err(Error::ReceiptsRootMismatch)
+        ));
+
+        header.log_bloom = Bloom::zero();
+        assert!(matches!(
+            verify_block_receipts(&header, receipts),
+            Err(Error::LogBloomMismatch)
+        ));
+    }
+
+    #[test]
+    fn test_check_receipts_gas() {
+        let mut header = crate::types::tests::mock_header();
+        header.gas_used = U256::from(63000);
+
+        let receipts = vec![receipt(21000), receipt(42000), receipt(63000)];
+        assert!(check_receipts_gas(&header, &receipts).is_ok());
+
+        let receipts = vec![receipt(42000), receipt(21000), receipt(63000)];
+        assert!(matches!(
+            check_receipts_gas(&header, &receipts),
+            Err(TypesError::CumulativeGasDecrease { index: 1 })
+        ));
+
+        let receipts = vec![receipt(21000), receipt(42000)];
+        assert!(matches!(
+            check_receipts_gas(&header, &receipts),
+            Err(TypesError::GasUsedMismatch { .. })
+        ));
+    }
+}
@@ -0,0 +1,226 @@
+//! Typed responses of Axon's JSON-RPC methods. Besides the Ethereum compatible
+//! ones below, `axon_getCurrentMetadata` and `axon_getMetadataByNumber` return
+//! a [`Metadata`](crate::types::Metadata), `axon_getProofByNumber` a
+//! [`Proof`](crate::types::Proof) and `axon_getCkbRelatedInfo` a
+//! [`CkbRelatedInfo`]. [`Client`](crate::client::Client) calls them over any
+//! transport.
+
+use alloc::vec::Vec;
+
+use bytes::Bytes;
+use ethereum_types::{Bloom, H160, H256, U256};
+use faster_hex::withpfx_lowercase;
+use serde::{Deserialize, Serialize};
+
+use crate::receipt::{Log, Receipt};
+use crate::types::{decode, encode, BlockNumber, Hex, TypesError};
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcLog {
+    pub address:           H160,
+    pub topics:            Vec<H256>,
+    #[serde(
+        serialize_with = "withpfx_lowercase::serialize",
+        deserialize_with = "withpfx_lowercase::deserialize"
+    )]
+    pub data:              Bytes,
+    pub block_hash:        Option<H256>,
+    pub transaction_hash:  Option<H256>,
+    pub log_index:         Option<U256>,
+    pub transaction_index: Option<U256>,
+    #[serde(default)]
+    pub removed:           bool,
+}
+
+impl From<RpcLog> for Log {
+    fn from(log: RpcLog) -> Self {
+        Log {
+            address: log.address,
+            topics:  log.topics,
+            data:    log.data,
+        }
+    }
+}
+
+/// The result of `eth_getTransactionReceipt`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionReceipt {
+    pub transaction_hash:    H256,
+    pub transaction_index:   U256,
+    pub block_hash:          H256,
+    #[serde(
+        serialize_with = "encode::serialize_uint",
+        deserialize_with = "decode::deserialize_hex_u64"
+    )]
+    pub block_number:        BlockNumber,
+    pub from:                H160,
+    pub to:                  Option<H160>,
+    pub cumulative_gas_used: U256,
+    pub gas_used:            U256,
+    pub effective_gas_price: U256,
+    pub contract_address:    Option<H160>,
+    pub logs:                Vec<RpcLog>,
+    pub logs_bloom:          Bloom,
+    #[serde(
+        rename = "type",
+        serialize_with = "encode::serialize_uint",
+        deserialize_with = "decode::deserialize_hex_u64"
+    )]
+    pub transaction_type:    u64,
+    #[serde(
+        serialize_with = "encode::serialize_uint",
+        deserialize_with = "decode::deserialize_hex_u64"
+    )]
+    pub status:              u64,
+}
+
+/// Fails if the transaction type or the status does not fit in a byte.
+impl TryFrom<TransactionReceipt> for Receipt {
+    type Error = TypesError;
+
+    fn try_from(receipt: TransactionReceipt) -> Result<Self, TypesError> {
+        Ok(Receipt {
+            tx_type:             byte(receipt.transaction_type)?,
+            status:              byte(receipt.status)?,
+            cumulative_gas_used: receipt.cumulative_gas_used,
+            logs_bloom:          receipt.logs_bloom,
+            logs:                receipt.logs.into_iter().map(Into::into).collect(),
+        })
+    }
+}
+
+fn byte(value: u64) -> Result<u8, TypesError> {
+    u8::try_from(value).map_err(|_| TypesError::U256Overflow {
+        value: value.into(),
+        bits:  8,
+    })
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageProof {
+    pub key:   U256,
+    pub value: U256,
+    pub proof: Vec<Hex>,
+}
+
+/// The result of `eth_getProof`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EIP1186ProofResponse {
+    pub address:       H160,
+    pub balance:       U256,
+    pub code_hash:     H256,
+    pub nonce:         U256,
+    pub storage_hash:  H256,
+    pub account_proof: Vec<Hex>,
+    pub storage_proof: Vec<StorageProof>,
+}
+
+/// The result of `axon_getCkbRelatedInfo`, the type IDs of the CKB cells Axon
+/// keeps its state in.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CkbRelatedInfo {
+    pub metadata_type_id:     H256,
+    pub checkpoint_type_id:   H256,
+    pub xudt_args:            H256,
+    pub stake_smt_type_id:    H256,
+    pub delegate_smt_type_id: H256,
+    pub reward_smt_type_id:   H256,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transaction_receipt_deserialize() {
+        let json = r#"{
+            "transactionHash": "0x1b1ae0d3d1a8ab0e5d2a9ff1a9c1f87b2e7f5b4d3f8c9a2f7c0f6ee3b2b0a4c1",
+            "transactionIndex": "0x0",
+            "blockHash": "0x6e5e1b3f6ac2cc2d2a1e6f4d2bcae8cf1d9e8c8a3e8f1c2b4b3d9a6f3c9a8f13",
+            "blockNumber": "0x1b4",
+            "from": "0x8ab0cf264df99d83525e9e11c7e4db01558ae1b1",
+            "to": "0xffffffffffffffffffffffffffffffffffffff01",
+            "cumulativeGasUsed": "0xa410",
+            "gasUsed": "0x5208",
+            "effectiveGasPrice": "0x539",
+            "contractAddress": null,
+            "logs": [{
+                "address": "0xffffffffffffffffffffffffffffffffffffff01",
+                "topics": ["0x0000000000000000000000000000000000000000000000000000000000000001"],
+                "data": "0x1234",
+                "blockNumber": "0x1b4",
+                "blockHash": "0x6e5e1b3f6ac2cc2d2a1e6f4d2bcae8cf1d9e8c8a3e8f1c2b4b3d9a6f3c9a8f13",
+                "transactionHash": "0x1b1ae0d3d1a8ab0e5d2a9ff1a9c1f87b2e7f5b4d3f8c9a2f7c0f6ee3b2b0a4c1",
+                "transactionIndex": "0x0",
+                "logIndex": "0x0",
+                "removed": false
+            }],
+            "logsBloom": "0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000",
+            "type": "0x2",
+            "status": "0x1"
+        }"#;
+
+        let rpc: TransactionReceipt = serde_json::from_str(json).unwrap();
+        assert_eq!(rpc.block_number, 0x1b4);
+        assert_eq!(rpc.gas_used, U256::from(21000));
+
+        let receipt = Receipt::try_from(rpc.clone()).unwrap();
+        assert_eq!(receipt.tx_type, 2);
+        assert_eq!(receipt.status, 1);
+        assert_eq!(receipt.cumulative_gas_used, U256::from(42000));
+        assert_eq!(receipt.logs[0].data, Bytes::from_static(&[0x12, 0x34]));
+
+        let invalid = TransactionReceipt {
+            transaction_type: 0x102,
+            ..rpc.clone()
+        };
+        assert!(matches!(
+            Receipt::try_from(invalid),
+            Err(TypesError::U256Overflow { bits: 8, .. })
+        ));
+        let invalid = TransactionReceipt { status: 256, ..rpc };
+        assert!(Receipt::try_from(invalid).is_err());
+    }
+
+    #[test]
+    fn test_proof_response_deserialize() {
+        let json = r#"{
+            "address": "0xffffffffffffffffffffffffffffffffffffff01",
+            "balance": "0x0",
+            "codeHash": "0xc5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470",
+            "nonce": "0x1",
+            "storageHash": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+            "accountProof": ["0xf8518080", "0xe2a0"],
+            "storageProof": [{
+                "key": "0x0",
+                "value": "0x2a",
+                "proof": ["0xe3a120"]
+            }]
+        }"#;
+
+        let resp: EIP1186ProofResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(resp.nonce, U256::one());
+        assert_eq!(resp.account_proof.len(), 2);
+        assert_eq!(resp.storage_proof[0].value, U256::from(42));
+        assert_eq!(resp.storage_proof[0].proof[0].as_ref(), &[0xe3, 0xa1, 0x20]);
+    }
+
+    #[test]
+    fn test_ckb_related_info_deserialize() {
+        let json = r#"{
+            "metadata_type_id": "0x0000000000000000000000000000000000000000000000000000000000000001",
+            "checkpoint_type_id": "0x0000000000000000000000000000000000000000000000000000000000000002",
+            "xudt_args": "0x0000000000000000000000000000000000000000000000000000000000000003",
+            "stake_smt_type_id": "0x0000000000000000000000000000000000000000000000000000000000000004",
+            "delegate_smt_type_id": "0x0000000000000000000000000000000000000000000000000000000000000005",
+            "reward_smt_type_id": "0x0000000000000000000000000000000000000000000000000000000000000006"
+        }"#;
+
+        let info: CkbRelatedInfo = serde_json::from_str(json).unwrap();
+        assert_eq!(info.metadata_type_id, H256::from_low_u64_be(1));
+        assert_eq!(info.reward_smt_type_id, H256::from_low_u64_be(6));
+    }
+}
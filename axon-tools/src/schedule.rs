@@ -0,0 +1,247 @@
+//! Verification rules that change at hardforks. A [`VerifierConfig`] maps
+//! block heights to the [`VerifierParams`] in force, so that one verifier can
+//! check history spanning several rule sets.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use ethereum_types::H256;
+
+use crate::consts::BLS_DST;
+use crate::crypto::ConsensusCrypto;
+use crate::metrics::NoopMetrics;
+use crate::proof::{check_proof, verify_aggregate_signature_with_dst};
+use crate::types::{AxonBlock, BlockNumber, Proof, ValidatorExtend};
+use crate::{Error, VerifyReport};
+
+/// How a block is encoded into the proposal its proof commits to.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ProposalEncoding {
+    /// `Proposal`'s RLP encoding.
+    #[default]
+    V0,
+}
+
+/// What a proof needs more than two thirds of to be final.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum QuorumRule {
+    /// Of the validators, whatever their weight.
+    #[default]
+    SignerCount,
+    /// Of the total vote weight.
+    VoteWeight,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerifierParams {
+    pub proposal_encoding: ProposalEncoding,
+    /// The BLS domain separation tag votes are signed with.
+    pub dst:               String,
+    pub quorum:            QuorumRule,
+}
+
+impl Default for VerifierParams {
+    /// The rules Axon has verified with since genesis.
+    fn default() -> Self {
+        VerifierParams {
+            proposal_encoding: ProposalEncoding::V0,
+            dst:               BLS_DST.into(),
+            quorum:            QuorumRule::SignerCount,
+        }
+    }
+}
+
+/// The verification parameters by activation height.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerifierConfig {
+    // Sorted by height, the first entry is at height 0.
+    schedule: Vec<(BlockNumber, VerifierParams)>,
+}
+
+impl Default for VerifierConfig {
+    fn default() -> Self {
+        VerifierConfig::new(VerifierParams::default())
+    }
+}
+
+impl VerifierConfig {
+    /// A schedule applying `genesis` from block 0 on.
+    pub fn new(genesis: VerifierParams) -> Self {
+        VerifierConfig {
+            schedule: alloc::vec![(0, genesis)],
+        }
+    }
+
+    /// Apply `params` from block `from` on, replacing anything scheduled at
+    /// the same height.
+    pub fn activate(mut self, from: BlockNumber, params: VerifierParams) -> Self {
+        match self.schedule.binary_search_by_key(&from, |(height, _)| *height) {
+            Ok(i) => self.schedule[i].1 = params,
+            Err(i) => self.schedule.insert(i, (from, params)),
+        }
+        self
+    }
+
+    /// The parameters in force at block `number`.
+    pub fn params_at(&self, number: BlockNumber) -> &VerifierParams {
+        let i = self.schedule.partition_point(|(height, _)| *height <= number);
+        &self.schedule[i - 1].1
+    }
+}
+
+/// Same as [`crate::verify_proof`] with the rules `config` schedules at the
+/// block's height.
+pub fn verify_proof_with_config(
+    block: &AxonBlock,
+    previous_state_root: H256,
+    validator_list: &[ValidatorExtend],
+    proof: &Proof,
+    config: &VerifierConfig,
+) -> Result<VerifyReport, Error> {
+    let params = config.params_at(block.header.number);
+    // The only encoding so far, the one `check_proposal_hash` rebuilds.
+    let ProposalEncoding::V0 = params.proposal_encoding;
+
+    check_proof(
+        block,
+        previous_state_root,
+        validator_list,
+        proof,
+        &NoopMetrics,
+        &BlsWithDst(params.dst.as_bytes()),
+        params.quorum,
+    )
+}
+
+/// BLS min-pk, as `BlsMinPk`, with a scheduled domain separation tag.
+struct BlsWithDst<'a>(&'a [u8]);
+
+impl ConsensusCrypto for BlsWithDst<'_> {
+    fn check_lengths(&self, signature: &[u8], pubkeys: &[&[u8]]) -> Result<(), Error> {
+        crate::crypto::BlsMinPk.check_lengths(signature, pubkeys)
+    }
+
+    fn verify_aggregate(
+        &self,
+        message: &[u8],
+        signature: &[u8],
+        pubkeys: &[&[u8]],
+    ) -> Result<(), Error> {
+        verify_aggregate_signature_with_dst(message, signature, pubkeys, self.0)
+    }
+
+    fn bls_dst(&self) -> Option<&[u8]> {
+        Some(self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use blst::min_pk::{AggregateSignature, SecretKey};
+    use bytes::Bytes;
+
+    use super::*;
+    use crate::keccak_256;
+    use crate::proof::{precommit_vote, vote_hash};
+    use crate::static_verify::proposal_rlp;
+    use crate::types::tests::{mock_header, mock_validator};
+    use crate::types::Hex;
+
+    /// A block at `number` with a proof the keys at `signers` signed with
+    /// `dst`.
+    fn signed_block(
+        number: u64,
+        sks: &[SecretKey],
+        signers: &[usize],
+        dst: &str,
+    ) -> (AxonBlock, Proof) {
+        let mut header = mock_header();
+        header.number = number;
+        let block = AxonBlock {
+            header,
+            tx_hashes: vec![],
+        };
+        let bitmap = signers.iter().fold(0u8, |bits, i| bits | 0b1000_0000 >> i);
+        let mut proof = Proof {
+            number,
+            block_hash: H256(keccak_256(&proposal_rlp(&block, H256::zero()).unwrap())),
+            bitmap: Bytes::from(vec![bitmap]),
+            ..Default::default()
+        };
+        let message = vote_hash(&precommit_vote(&proof));
+        let sigs = signers
+            .iter()
+            .map(|i| sks[*i].sign(&message, dst.as_bytes(), &[]))
+            .collect::<Vec<_>>();
+        let sigs = sigs.iter().collect::<Vec<_>>();
+        let sig = AggregateSignature::aggregate(&sigs, true).unwrap().to_signature();
+        proof.signature = Bytes::from(sig.to_bytes().to_vec());
+        (block, proof)
+    }
+
+    #[test]
+    fn test_verify_proof_with_config() {
+        let sks = (1..=3u8)
+            .map(|i| SecretKey::key_gen(&[i; 32], &[]).unwrap())
+            .collect::<Vec<_>>();
+        let validators = sks
+            .iter()
+            .zip([(1u8, 1), (2, 1), (3, 5)])
+            .map(|(sk, (seed, vote_weight))| ValidatorExtend {
+                bls_pub_key: Hex::encode(sk.sk_to_pk().to_bytes()),
+                vote_weight,
+                ..mock_validator(seed)
+            })
+            .collect::<Vec<_>>();
+        let verify = |(block, proof): (AxonBlock, Proof), config: &VerifierConfig| {
+            let validators = validators.clone();
+            verify_proof_with_config(&block, H256::zero(), &validators, &proof, config)
+        };
+
+        // Two of three signers, but six of seven in vote weight.
+        let weighted = VerifierConfig::default().activate(100, VerifierParams {
+            quorum: QuorumRule::VoteWeight,
+            ..Default::default()
+        });
+        assert!(matches!(
+            verify(signed_block(99, &sks, &[0, 2], BLS_DST), &weighted),
+            Err(Error::NotEnoughSignatures)
+        ));
+        assert!(verify(signed_block(100, &sks, &[0, 2], BLS_DST), &weighted).is_ok());
+
+        let retagged = VerifierConfig::default().activate(100, VerifierParams {
+            dst: "AXON_V2".into(),
+            ..Default::default()
+        });
+        let all = [0, 1, 2];
+        assert!(verify(signed_block(99, &sks, &all, BLS_DST), &retagged).is_ok());
+        assert!(verify(signed_block(99, &sks, &all, "AXON_V2"), &retagged).is_err());
+        assert!(verify(signed_block(100, &sks, &all, BLS_DST), &retagged).is_err());
+        assert!(verify(signed_block(100, &sks, &all, "AXON_V2"), &retagged).is_ok());
+    }
+
+    #[test]
+    fn test_params_at() {
+        let weighted = VerifierParams {
+            quorum: QuorumRule::VoteWeight,
+            ..Default::default()
+        };
+        let retagged = VerifierParams {
+            dst: "AXON_V2".into(),
+            ..Default::default()
+        };
+        let config = VerifierConfig::default()
+            .activate(200, retagged.clone())
+            .activate(100, weighted.clone());
+
+        assert_eq!(config.params_at(0), &VerifierParams::default());
+        assert_eq!(config.params_at(99), &VerifierParams::default());
+        assert_eq!(config.params_at(100), &weighted);
+        assert_eq!(config.params_at(199), &weighted);
+        assert_eq!(config.params_at(u64::MAX), &retagged);
+
+        let config = config.activate(0, weighted.clone());
+        assert_eq!(config.params_at(0), &weighted);
+    }
+}
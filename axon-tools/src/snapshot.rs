@@ -0,0 +1,206 @@
+//! Checkpoint snapshots for bootstrapping a light client. A snapshot lists
+//! the block hash at every multiple of a fixed interval, typically the epoch
+//! length so that each checkpoint is the last block of an epoch, and commits
+//! to that list with a single hash. A client that trusts the commitment can
+//! start from the latest checkpoint instead of replaying every proof.
+
+use alloc::vec::Vec;
+
+use ethereum_types::H256;
+use rlp::{Decodable, DecoderError, Encodable, Rlp, RlpStream};
+
+use crate::keccak_256;
+use crate::types::{BlockNumber, TypesError};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Checkpoint {
+    pub number: BlockNumber,
+    pub hash:   H256,
+}
+
+impl Encodable for Checkpoint {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(2).append(&self.number).append(&self.hash);
+    }
+}
+
+impl Decodable for Checkpoint {
+    fn decode(r: &Rlp) -> Result<Self, DecoderError> {
+        Ok(Checkpoint {
+            number: r.val_at(0)?,
+            hash:   r.val_at(1)?,
+        })
+    }
+}
+
+/// Block hashes at `interval`, `2 * interval`, ... in ascending order with no
+/// gaps.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Snapshot {
+    pub interval:    u64,
+    pub checkpoints: Vec<Checkpoint>,
+}
+
+impl Snapshot {
+    /// An empty snapshot, failing if `interval` is zero.
+    pub fn new(interval: u64) -> Result<Self, TypesError> {
+        if interval == 0 {
+            return Err(TypesError::ZeroSnapshotInterval);
+        }
+        Ok(Snapshot {
+            interval,
+            checkpoints: Vec::new(),
+        })
+    }
+
+    /// Build a snapshot from `(number, hash)` pairs in ascending order, such
+    /// as every verified header of a chain. Pairs between checkpoints are
+    /// skipped.
+    pub fn generate<I>(interval: u64, hashes: I) -> Result<Self, TypesError>
+    where
+        I: IntoIterator<Item = (BlockNumber, H256)>,
+    {
+        let mut snapshot = Snapshot::new(interval)?;
+        for (number, hash) in hashes {
+            if number != 0 && number.is_multiple_of(interval) {
+                snapshot.push(Checkpoint { number, hash })?;
+            }
+        }
+        Ok(snapshot)
+    }
+
+    /// Append the next checkpoint, which must be exactly one interval after
+    /// the latest one.
+    pub fn push(&mut self, checkpoint: Checkpoint) -> Result<(), TypesError> {
+        let expect = self.next_number();
+        if checkpoint.number != expect {
+            return Err(TypesError::UnexpectedCheckpoint {
+                expect,
+                real: checkpoint.number,
+            });
+        }
+        self.checkpoints.push(checkpoint);
+        Ok(())
+    }
+
+    pub fn latest(&self) -> Option<&Checkpoint> {
+        self.checkpoints.last()
+    }
+
+    /// The checkpointed hash of block `number`, `None` if `number` is not a
+    /// checkpoint height or is beyond the snapshot.
+    pub fn hash_at(&self, number: BlockNumber) -> Option<H256> {
+        // A zero interval, possible in a decoded snapshot, has no multiples.
+        if number == 0 || !number.is_multiple_of(self.interval) {
+            return None;
+        }
+        let index = usize::try_from(number / self.interval - 1).ok()?;
+        self.checkpoints.get(index).map(|c| c.hash)
+    }
+
+    /// The hash a trusted party publishes for this snapshot, the keccak of
+    /// its RLP encoding.
+    pub fn commitment(&self) -> H256 {
+        H256(keccak_256(&rlp::encode(self)))
+    }
+
+    /// Check that the checkpoints are well formed and match `commitment`.
+    pub fn verify(&self, commitment: H256) -> Result<(), TypesError> {
+        let mut expect = Snapshot::new(self.interval)?;
+        for checkpoint in self.checkpoints.iter() {
+            expect.push(*checkpoint)?;
+        }
+
+        if self.commitment() != commitment {
+            return Err(TypesError::SnapshotCommitmentMismatch);
+        }
+        Ok(())
+    }
+
+    fn next_number(&self) -> BlockNumber {
+        self.latest().map_or(0, |c| c.number) + self.interval
+    }
+}
+
+impl Encodable for Snapshot {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(2)
+            .append(&self.interval)
+            .append_list(&self.checkpoints);
+    }
+}
+
+impl Decodable for Snapshot {
+    fn decode(r: &Rlp) -> Result<Self, DecoderError> {
+        Ok(Snapshot {
+            interval:    r.val_at(0)?,
+            checkpoints: r.list_at(1)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hash(number: BlockNumber) -> H256 {
+        H256::from_low_u64_be(number)
+    }
+
+    fn checkpoint(number: BlockNumber) -> Checkpoint {
+        Checkpoint {
+            number,
+            hash: hash(number),
+        }
+    }
+
+    #[test]
+    fn test_snapshot_roundtrip() {
+        let snapshot = Snapshot::generate(100, (0..=350).map(|n| (n, hash(n)))).unwrap();
+        assert_eq!(snapshot.checkpoints.len(), 3);
+        assert_eq!(snapshot.latest().unwrap().number, 300);
+        assert_eq!(snapshot.hash_at(200), Some(hash(200)));
+        assert_eq!(snapshot.hash_at(250), None);
+        assert_eq!(snapshot.hash_at(400), None);
+
+        let commitment = snapshot.commitment();
+        let decoded: Snapshot = rlp::decode(&rlp::encode(&snapshot)).unwrap();
+        assert_eq!(decoded, snapshot);
+        assert!(decoded.verify(commitment).is_ok());
+        assert!(decoded.verify(H256::zero()).is_err());
+    }
+
+    #[test]
+    fn test_snapshot_rejects_gaps() {
+        let mut snapshot = Snapshot::new(100).unwrap();
+        assert!(snapshot.push(checkpoint(200)).is_err());
+        snapshot.push(checkpoint(100)).unwrap();
+        assert!(snapshot.push(checkpoint(100)).is_err());
+        assert!(Snapshot::generate(100, [(100, hash(100)), (300, hash(300))]).is_err());
+
+        let mut tampered = Snapshot::generate(100, (0..=300).map(|n| (n, hash(n)))).unwrap();
+        let commitment = tampered.commitment();
+        tampered.checkpoints.remove(1);
+        assert!(tampered.verify(commitment).is_err());
+    }
+
+    #[test]
+    fn test_zero_interval() {
+        assert!(matches!(Snapshot::new(0), Err(TypesError::ZeroSnapshotInterval)));
+        assert!(matches!(
+            Snapshot::generate(0, [(100, hash(100))]),
+            Err(TypesError::ZeroSnapshotInterval)
+        ));
+
+        let snapshot = Snapshot {
+            interval:    0,
+            checkpoints: vec![],
+        };
+        let decoded: Snapshot = rlp::decode(&rlp::encode(&snapshot)).unwrap();
+        assert_eq!(decoded.hash_at(100), None);
+        assert!(matches!(
+            decoded.verify(decoded.commitment()),
+            Err(TypesError::ZeroSnapshotInterval)
+        ));
+    }
+}
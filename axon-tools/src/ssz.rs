@@ -0,0 +1,507 @@
+//! SSZ encoding and hash tree roots of `AxonHeader` and `Proof`, for light
+//! client infrastructure and circuits that merkleize with SSZ rather than
+//! hashing RLP. The schemas are:
+//!
+//! ```text
+//! Proof {
+//!     number:     uint64,
+//!     round:      uint64,
+//!     block_hash: Bytes32,
+//!     signature:  List[byte, SSZ_MAX_SIGNATURE_LEN],
+//!     bitmap:     List[byte, MAX_BITMAP_LEN],
+//! }
+//!
+//! AxonHeader {
+//!     version:                  uint8,
+//!     prev_hash:                Bytes32,
+//!     proposer:                 Bytes20,
+//!     state_root:               Bytes32,
+//!     transactions_root:        Bytes32,
+//!     signed_txs_hash:          Bytes32,
+//!     receipts_root:            Bytes32,
+//!     log_bloom:                ByteVector[256],
+//!     timestamp:                uint64,
+//!     number:                   uint64,
+//!     gas_used:                 uint256,
+//!     gas_limit:                uint256,
+//!     extra_data:               List[List[byte, SSZ_MAX_EXTRA_DATA_LEN],
+//!                                    SSZ_MAX_EXTRA_DATA],
+//!     base_fee_per_gas:         uint256,
+//!     proof:                    Proof,
+//!     call_system_script_count: uint32,
+//!     chain_id:                 uint64,
+//! }
+//! ```
+//!
+//! Values exceeding a list limit have no SSZ form, so encoding and hashing
+//! fail on them instead of producing a root no other implementation agrees
+//! with.
+
+use alloc::vec::Vec;
+
+use bytes::Bytes;
+use ethereum_types::{Bloom, H160, H256, U256};
+use sha2::{Digest, Sha256};
+
+use crate::consts::{BLS_SIGNATURE_LENS, MAX_BITMAP_LEN};
+use crate::types::{AxonHeader, BlockVersion, ExtraData, Proof, TypesError};
+
+/// The list limit of `Proof::signature`, an uncompressed BLS signature.
+pub const SSZ_MAX_SIGNATURE_LEN: usize = BLS_SIGNATURE_LENS[1];
+/// The list limit of `AxonHeader::extra_data`.
+pub const SSZ_MAX_EXTRA_DATA: usize = 16;
+/// The list limit of each `ExtraData` entry.
+pub const SSZ_MAX_EXTRA_DATA_LEN: usize = 1024;
+
+const CHUNK: usize = 32;
+const OFFSET_LEN: usize = 4;
+const PROOF_FIXED_LEN: usize = 8 + 8 + 32 + OFFSET_LEN + OFFSET_LEN;
+const HEADER_FIXED_LEN: usize =
+    1 + 32 + 20 + 4 * 32 + 256 + 8 + 8 + 32 + 32 + OFFSET_LEN + 32 + OFFSET_LEN + 4 + 8;
+
+impl Proof {
+    pub fn to_ssz_bytes(&self) -> Result<Vec<u8>, TypesError> {
+        self.check_ssz_limits()?;
+        let mut buf = Vec::new();
+        self.ssz_append(&mut buf);
+        Ok(buf)
+    }
+
+    pub fn from_ssz_bytes(bytes: &[u8]) -> Result<Self, TypesError> {
+        let mut r = ContainerReader::new(bytes, PROOF_FIXED_LEN);
+        let number = r.u64()?;
+        let round = r.u64()?;
+        let block_hash = H256::from_slice(r.fixed(32)?);
+        r.offset()?;
+        r.offset()?;
+        let [signature, bitmap] = r.finish::<2>()?;
+
+        Ok(Proof {
+            number,
+            round,
+            block_hash,
+            signature: byte_list(signature, SSZ_MAX_SIGNATURE_LEN)?,
+            bitmap: byte_list(bitmap, MAX_BITMAP_LEN)?,
+        })
+    }
+
+    pub fn hash_tree_root(&self) -> Result<H256, TypesError> {
+        self.check_ssz_limits()?;
+        Ok(H256(self.ssz_root()))
+    }
+
+    fn check_ssz_limits(&self) -> Result<(), TypesError> {
+        check_limit(self.signature.len(), SSZ_MAX_SIGNATURE_LEN, "signature too long")?;
+        check_limit(self.bitmap.len(), MAX_BITMAP_LEN, "bitmap too long")
+    }
+
+    fn ssz_append(&self, buf: &mut Vec<u8>) {
+        let mut w = ContainerWriter::new(buf, PROOF_FIXED_LEN);
+        w.fixed(&self.number.to_le_bytes());
+        w.fixed(&self.round.to_le_bytes());
+        w.fixed(self.block_hash.as_bytes());
+        w.variable(&self.signature);
+        w.variable(&self.bitmap);
+        w.finish();
+    }
+
+    fn ssz_root(&self) -> [u8; 32] {
+        merkleize(
+            &[
+                uint_chunk(&self.number.to_le_bytes()),
+                uint_chunk(&self.round.to_le_bytes()),
+                self.block_hash.0,
+                byte_list_root(&self.signature, SSZ_MAX_SIGNATURE_LEN),
+                byte_list_root(&self.bitmap, MAX_BITMAP_LEN),
+            ],
+            5,
+        )
+    }
+}
+
+impl AxonHeader {
+    pub fn to_ssz_bytes(&self) -> Result<Vec<u8>, TypesError> {
+        self.check_ssz_limits()?;
+        let mut extra_data = Vec::new();
+        let mut w = ContainerWriter::new(&mut extra_data, self.extra_data.len() * OFFSET_LEN);
+        for data in self.extra_data.iter() {
+            w.variable(&data.inner);
+        }
+        w.finish();
+        let mut proof = Vec::new();
+        self.proof.ssz_append(&mut proof);
+
+        let mut buf = Vec::new();
+        let mut w = ContainerWriter::new(&mut buf, HEADER_FIXED_LEN);
+        w.fixed(&[self.version.into()]);
+        w.fixed(self.prev_hash.as_bytes());
+        w.fixed(self.proposer.as_bytes());
+        w.fixed(self.state_root.as_bytes());
+        w.fixed(self.transactions_root.as_bytes());
+        w.fixed(self.signed_txs_hash.as_bytes());
+        w.fixed(self.receipts_root.as_bytes());
+        w.fixed(self.log_bloom.as_bytes());
+        w.fixed(&self.timestamp.to_le_bytes());
+        w.fixed(&self.number.to_le_bytes());
+        w.fixed(&u256_le(&self.gas_used));
+        w.fixed(&u256_le(&self.gas_limit));
+        w.variable(&extra_data);
+        w.fixed(&u256_le(&self.base_fee_per_gas));
+        w.variable(&proof);
+        w.fixed(&self.call_system_script_count.to_le_bytes());
+        w.fixed(&self.chain_id.to_le_bytes());
+        w.finish();
+        Ok(buf)
+    }
+
+    pub fn from_ssz_bytes(bytes: &[u8]) -> Result<Self, TypesError> {
+        let mut r = ContainerReader::new(bytes, HEADER_FIXED_LEN);
+        let version = BlockVersion::try_from(r.fixed(1)?[0])?;
+        let prev_hash = H256::from_slice(r.fixed(32)?);
+        let proposer = H160::from_slice(r.fixed(20)?);
+        let state_root = H256::from_slice(r.fixed(32)?);
+        let transactions_root = H256::from_slice(r.fixed(32)?);
+        let signed_txs_hash = H256::from_slice(r.fixed(32)?);
+        let receipts_root = H256::from_slice(r.fixed(32)?);
+        let log_bloom = Bloom::from_slice(r.fixed(256)?);
+        let timestamp = r.u64()?;
+        let number = r.u64()?;
+        let gas_used = U256::from_little_endian(r.fixed(32)?);
+        let gas_limit = U256::from_little_endian(r.fixed(32)?);
+        r.offset()?;
+        let base_fee_per_gas = U256::from_little_endian(r.fixed(32)?);
+        r.offset()?;
+        let call_system_script_count = u32::from_le_bytes(r.fixed(4)?.try_into().unwrap());
+        let chain_id = r.u64()?;
+        let [extra_data, proof] = r.finish::<2>()?;
+
+        Ok(AxonHeader {
+            version,
+            prev_hash,
+            proposer,
+            state_root,
+            transactions_root,
+            signed_txs_hash,
+            receipts_root,
+            log_bloom,
+            timestamp,
+            number,
+            gas_used,
+            gas_limit,
+            extra_data: extra_data_list(extra_data)?,
+            base_fee_per_gas,
+            proof: Proof::from_ssz_bytes(proof)?,
+            call_system_script_count,
+            chain_id,
+        })
+    }
+
+    pub fn hash_tree_root(&self) -> Result<H256, TypesError> {
+        self.check_ssz_limits()?;
+        let extra_data = self
+            .extra_data
+            .iter()
+            .map(|data| byte_list_root(&data.inner, SSZ_MAX_EXTRA_DATA_LEN))
+            .collect::<Vec<_>>();
+        let bloom = self
+            .log_bloom
+            .as_bytes()
+            .chunks(CHUNK)
+            .map(uint_chunk)
+            .collect::<Vec<_>>();
+
+        let fields = [
+            uint_chunk(&[self.version.into()]),
+            self.prev_hash.0,
+            uint_chunk(self.proposer.as_bytes()),
+            self.state_root.0,
+            self.transactions_root.0,
+            self.signed_txs_hash.0,
+            self.receipts_root.0,
+            merkleize(&bloom, bloom.len()),
+            uint_chunk(&self.timestamp.to_le_bytes()),
+            uint_chunk(&self.number.to_le_bytes()),
+            u256_le(&self.gas_used),
+            u256_le(&self.gas_limit),
+            mix_in_length(
+                merkleize(&extra_data, SSZ_MAX_EXTRA_DATA),
+                self.extra_data.len(),
+            ),
+            u256_le(&self.base_fee_per_gas),
+            self.proof.ssz_root(),
+            uint_chunk(&self.call_system_script_count.to_le_bytes()),
+            uint_chunk(&self.chain_id.to_le_bytes()),
+        ];
+        Ok(H256(merkleize(&fields, fields.len())))
+    }
+
+    fn check_ssz_limits(&self) -> Result<(), TypesError> {
+        check_limit(self.extra_data.len(), SSZ_MAX_EXTRA_DATA, "too many extra data")?;
+        for data in self.extra_data.iter() {
+            check_limit(data.inner.len(), SSZ_MAX_EXTRA_DATA_LEN, "extra data too long")?;
+        }
+        self.proof.check_ssz_limits()
+    }
+}
+
+fn check_limit(len: usize, limit: usize, reason: &'static str) -> Result<(), TypesError> {
+    if len > limit {
+        return Err(TypesError::InvalidSsz(reason));
+    }
+    Ok(())
+}
+
+/// Writes the fixed part of a container, with offsets in place of the
+/// variable fields, followed by the variable fields in order.
+struct ContainerWriter<'a> {
+    buf:       &'a mut Vec<u8>,
+    fixed_len: usize,
+    variable:  Vec<u8>,
+}
+
+impl<'a> ContainerWriter<'a> {
+    fn new(buf: &'a mut Vec<u8>, fixed_len: usize) -> Self {
+        ContainerWriter {
+            buf,
+            fixed_len,
+            variable: Vec::new(),
+        }
+    }
+
+    fn fixed(&mut self, bytes: &[u8]) {
+        self.buf.extend_from_slice(bytes);
+    }
+
+    fn variable(&mut self, bytes: &[u8]) {
+        let offset = (self.fixed_len + self.variable.len()) as u32;
+        self.buf.extend_from_slice(&offset.to_le_bytes());
+        self.variable.extend_from_slice(bytes);
+    }
+
+    fn finish(self) {
+        self.buf.extend_from_slice(&self.variable);
+    }
+}
+
+/// Reads the fixed part of a container, then splits the rest at the offsets
+/// read on the way.
+struct ContainerReader<'a> {
+    bytes:     &'a [u8],
+    fixed_len: usize,
+    pos:       usize,
+    offsets:   Vec<usize>,
+}
+
+impl<'a> ContainerReader<'a> {
+    fn new(bytes: &'a [u8], fixed_len: usize) -> Self {
+        ContainerReader {
+            bytes,
+            fixed_len,
+            pos: 0,
+            offsets: Vec::new(),
+        }
+    }
+
+    fn fixed(&mut self, len: usize) -> Result<&'a [u8], TypesError> {
+        let end = self.pos + len;
+        let ret = self
+            .bytes
+            .get(self.pos..end)
+            .ok_or(TypesError::InvalidSsz("input too short"))?;
+        self.pos = end;
+        Ok(ret)
+    }
+
+    fn u64(&mut self) -> Result<u64, TypesError> {
+        Ok(u64::from_le_bytes(self.fixed(8)?.try_into().unwrap()))
+    }
+
+    fn offset(&mut self) -> Result<(), TypesError> {
+        let offset = u32::from_le_bytes(self.fixed(OFFSET_LEN)?.try_into().unwrap());
+        self.offsets.push(offset as usize);
+        Ok(())
+    }
+
+    /// The `N` variable fields, after checking that the offsets start right
+    /// after the fixed part and never decrease.
+    fn finish<const N: usize>(self) -> Result<[&'a [u8]; N], TypesError> {
+        debug_assert_eq!(self.pos, self.fixed_len);
+        debug_assert_eq!(self.offsets.len(), N);
+        if self.offsets.first().is_some_and(|first| *first != self.fixed_len) {
+            return Err(TypesError::InvalidSsz("invalid first offset"));
+        }
+
+        let mut ret = [&self.bytes[..0]; N];
+        for (i, field) in ret.iter_mut().enumerate() {
+            let start = self.offsets[i];
+            let end = self.offsets.get(i + 1).copied().unwrap_or(self.bytes.len());
+            *field = self
+                .bytes
+                .get(start..end)
+                .ok_or(TypesError::InvalidSsz("invalid offset"))?;
+        }
+        Ok(ret)
+    }
+}
+
+fn byte_list(bytes: &[u8], limit: usize) -> Result<Bytes, TypesError> {
+    check_limit(bytes.len(), limit, "list too long")?;
+    Ok(Bytes::copy_from_slice(bytes))
+}
+
+fn extra_data_list(bytes: &[u8]) -> Result<Vec<ExtraData>, TypesError> {
+    if bytes.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let first = bytes
+        .get(..OFFSET_LEN)
+        .map(|b| u32::from_le_bytes(b.try_into().unwrap()) as usize)
+        .ok_or(TypesError::InvalidSsz("input too short"))?;
+    if first == 0 || first % OFFSET_LEN != 0 {
+        return Err(TypesError::InvalidSsz("invalid first offset"));
+    }
+    let count = first / OFFSET_LEN;
+    check_limit(count, SSZ_MAX_EXTRA_DATA, "too many extra data")?;
+
+    let mut r = ContainerReader::new(bytes, first);
+    for _ in 0..count {
+        r.offset()?;
+    }
+    let offsets = r.offsets;
+    offsets
+        .iter()
+        .enumerate()
+        .map(|(i, start)| {
+            let end = offsets.get(i + 1).copied().unwrap_or(bytes.len());
+            let inner = bytes
+                .get(*start..end)
+                .ok_or(TypesError::InvalidSsz("invalid offset"))?;
+            Ok(ExtraData {
+                inner: byte_list(inner, SSZ_MAX_EXTRA_DATA_LEN)?,
+            })
+        })
+        .collect()
+}
+
+fn u256_le(value: &U256) -> [u8; 32] {
+    let mut ret = [0u8; 32];
+    value.to_little_endian(&mut ret);
+    ret
+}
+
+/// `bytes`, at most 32 of them, right-padded with zeros into a chunk.
+fn uint_chunk(bytes: &[u8]) -> [u8; 32] {
+    let mut ret = [0u8; 32];
+    ret[..bytes.len()].copy_from_slice(bytes);
+    ret
+}
+
+fn byte_list_root(bytes: &[u8], limit: usize) -> [u8; 32] {
+    let chunks = bytes.chunks(CHUNK).map(uint_chunk).collect::<Vec<_>>();
+    mix_in_length(merkleize(&chunks, limit.div_ceil(CHUNK)), bytes.len())
+}
+
+fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+fn mix_in_length(root: [u8; 32], len: usize) -> [u8; 32] {
+    hash_pair(&root, &uint_chunk(&(len as u64).to_le_bytes()))
+}
+
+/// The root of `chunks` padded with zero chunks to the next power of two of
+/// `limit`.
+fn merkleize(chunks: &[[u8; 32]], limit: usize) -> [u8; 32] {
+    debug_assert!(chunks.len() <= limit.max(1));
+    let depth = limit.max(1).next_power_of_two().trailing_zeros();
+    let mut layer = chunks.to_vec();
+    let mut zero = [0u8; 32];
+    for _ in 0..depth {
+        if layer.len() % 2 == 1 {
+            layer.push(zero);
+        }
+        layer = layer
+            .chunks(2)
+            .map(|pair| hash_pair(&pair[0], &pair[1]))
+            .collect();
+        zero = hash_pair(&zero, &zero);
+    }
+    layer.first().copied().unwrap_or(zero)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::tests::{mock_header, random_bytes};
+
+    #[test]
+    fn test_merkleize_zero_hashes() {
+        assert_eq!(merkleize(&[], 1), [0u8; 32]);
+        assert_eq!(
+            H256(merkleize(&[], 2)),
+            H256::from_slice(&[
+                0xf5, 0xa5, 0xfd, 0x42, 0xd1, 0x6a, 0x20, 0x30, 0x27, 0x98, 0xef, 0x6e, 0xd3,
+                0x09, 0x97, 0x9b, 0x43, 0x00, 0x3d, 0x23, 0x20, 0xd9, 0xf0, 0xe8, 0xea, 0x98,
+                0x31, 0xa9, 0x27, 0x59, 0xfb, 0x4b
+            ])
+        );
+        assert_eq!(merkleize(&[[1u8; 32]], 1), [1u8; 32]);
+        assert_eq!(merkleize(&[[1u8; 32]], 4), merkleize(&[[1u8; 32], [0u8; 32]], 3));
+    }
+
+    #[test]
+    fn test_proof_ssz_roundtrip() {
+        let proof = Proof {
+            number:     7,
+            round:      1,
+            block_hash: H256(rand::random()),
+            signature:  random_bytes(96),
+            bitmap:     random_bytes(2),
+        };
+        let bytes = proof.to_ssz_bytes().unwrap();
+        assert_eq!(bytes.len(), PROOF_FIXED_LEN + 96 + 2);
+        assert_eq!(Proof::from_ssz_bytes(&bytes).unwrap(), proof);
+
+        assert!(Proof::from_ssz_bytes(&bytes[..PROOF_FIXED_LEN - 1]).is_err());
+        let mut bad_offset = bytes.clone();
+        bad_offset[48] += 1;
+        assert!(Proof::from_ssz_bytes(&bad_offset).is_err());
+    }
+
+    #[test]
+    fn test_header_ssz_roundtrip() {
+        let mut header = mock_header();
+        header.extra_data = vec![
+            ExtraData {
+                inner: random_bytes(40),
+            },
+            ExtraData {
+                inner: Bytes::new(),
+            },
+        ];
+        let bytes = header.to_ssz_bytes().unwrap();
+        assert_eq!(AxonHeader::from_ssz_bytes(&bytes).unwrap(), header);
+
+        header.extra_data.clear();
+        let bytes = header.to_ssz_bytes().unwrap();
+        assert_eq!(AxonHeader::from_ssz_bytes(&bytes).unwrap(), header);
+    }
+
+    #[test]
+    fn test_hash_tree_root() {
+        let header = mock_header();
+        let root = header.hash_tree_root().unwrap();
+
+        let mut other = header.clone();
+        other.proof.round += 1;
+        assert_ne!(other.hash_tree_root().unwrap(), root);
+
+        other.proof.bitmap = random_bytes(MAX_BITMAP_LEN + 1);
+        assert!(other.hash_tree_root().is_err());
+        assert!(other.to_ssz_bytes().is_err());
+    }
+}
@@ -0,0 +1,369 @@
+//! Proof verification without heap allocation on the hot path. The proposal
+//! is RLP-encoded into a caller-provided buffer and the signers' public keys
+//! are aggregated one at a time instead of being collected first.
+
+use alloc::vec::Vec;
+
+use blst::min_pk::{AggregatePublicKey, PublicKey, Signature};
+use blst::BLST_ERROR;
+use ethereum_types::{H160, H256, U256};
+
+use crate::consts::BLS_DST;
+use crate::types::{
+    find_duplicate_validator, try_into_u64, AxonBlock, Proof, ValidatorExtend, VoteType,
+};
+use crate::validator_set::ValidatorOrder;
+use crate::{check_bitmap, check_lengths, check_quorum, keccak_256, Error};
+
+/// Same checks as [`crate::verify_proof`] for a set of at most
+/// `MAX_VALIDATORS` validators. `buf` must hold the RLP of the block's
+/// proposal, roughly 400 bytes plus 33 bytes per transaction hash. As there,
+/// the validator list must be sorted by `pub_key`.
+pub fn verify_proof_static<const MAX_VALIDATORS: usize>(
+    block: &AxonBlock,
+    previous_state_root: H256,
+    validator_list: &[ValidatorExtend],
+    proof: &Proof,
+    buf: &mut [u8],
+) -> Result<(), Error> {
+    if validator_list.len() > MAX_VALIDATORS {
+        return Err(Error::TooManyValidators);
+    }
+    check_lengths(proof, validator_list)?;
+    check_bitmap(proof, validator_list.len())?;
+    if let Some(address) = find_duplicate_validator(validator_list) {
+        return Err(Error::DuplicateValidator(address));
+    }
+    ValidatorOrder::PubKey.check_sorted(validator_list)?;
+
+    try_into_u64(block.header.gas_limit).map_err(|_| Error::GasLimitOverflow)?;
+    let mut counter = Counter(0);
+    encode_proposal(&mut counter, block, previous_state_root);
+    let len = counter.0;
+    let mut writer = SliceWriter::new(buf.get_mut(..len).ok_or(Error::BufferTooSmall)?);
+    encode_proposal(&mut writer, block, previous_state_root);
+    if keccak_256(&buf[..len]) != proof.block_hash.0 {
+        return Err(Error::InvalidProofBlockHash);
+    }
+
+    let signers = validator_list
+        .iter()
+        .map(|v| (v.address, &v.bls_pub_key[..]));
+    let (aggregate, signer_count) = aggregate_signers(&proof.bitmap, signers)?;
+    check_quorum(signer_count, validator_list.len())?;
+    let aggregate = aggregate.ok_or(Error::NotEnoughSignatures)?;
+
+    verify_precommit(
+        proof.number,
+        proof.round,
+        proof.block_hash,
+        &proof.signature,
+        &aggregate,
+    )
+}
+
+/// Aggregate, one at a time, the BLS public keys of the validators whose bit
+/// is set in `bitmap`. `validators` yields the address and key of each
+/// validator in bitmap order. Returns the aggregate, `None` if nobody signed,
+/// and the number of signers.
+pub(crate) fn aggregate_signers<'a>(
+    bitmap: &[u8],
+    validators: impl Iterator<Item = (H160, &'a [u8])>,
+) -> Result<(Option<AggregatePublicKey>, usize), Error> {
+    let mut aggregate: Option<AggregatePublicKey> = None;
+    let mut signer_count = 0;
+    for (i, (address, bls_pub_key)) in validators.enumerate() {
+        let signed = bitmap
+            .get(i / 8)
+            .is_some_and(|byte| byte & (0x80 >> (i % 8)) != 0);
+        if !signed {
+            continue;
+        }
+
+        let pk = PublicKey::key_validate(bls_pub_key)
+            .map_err(|error| Error::InvalidValidatorKey { address, error })?;
+        match aggregate.as_mut() {
+            Some(aggregate) => aggregate.add_public_key(&pk, false)?,
+            None => aggregate = Some(AggregatePublicKey::from_public_key(&pk)),
+        }
+        signer_count += 1;
+    }
+
+    Ok((aggregate, signer_count))
+}
+
+/// Verify that `signature` is the aggregate signature by the holders of
+/// `aggregate` of the precommit vote for `block_hash` at `number` and
+/// `round`.
+pub(crate) fn verify_precommit(
+    number: u64,
+    round: u64,
+    block_hash: H256,
+    signature: &[u8],
+    aggregate: &AggregatePublicKey,
+) -> Result<(), Error> {
+    // The vote is `[height, round, vote_type, block_hash]`, 48 bytes at most.
+    let mut vote = [0u8; 64];
+    let mut writer = SliceWriter::new(&mut vote);
+    encode_vote(&mut writer, number, round, block_hash);
+    let len = writer.pos;
+    let hash_vote = keccak_256(&vote[..len]);
+
+    let sig = Signature::from_bytes(signature)?;
+    let c_pk = aggregate.to_public_key();
+    let res = sig.verify(true, &hash_vote, BLS_DST.as_bytes(), &[], &c_pk, true);
+    if res == BLST_ERROR::BLST_SUCCESS {
+        return Ok(());
+    }
+
+    Err(res.into())
+}
+
+/// The RLP of `block`'s proposal, encoded from the block in place instead of
+/// through a `Proposal` holding copies of its fields. Fails like
+/// [`Proposal::from_block`](crate::types::Proposal::from_block).
+pub(crate) fn proposal_rlp(
+    block: &AxonBlock,
+    previous_state_root: H256,
+) -> Result<Vec<u8>, Error> {
+    try_into_u64(block.header.gas_limit).map_err(|_| Error::GasLimitOverflow)?;
+    let mut counter = Counter(0);
+    encode_proposal(&mut counter, block, previous_state_root);
+    let mut buf = Vec::with_capacity(counter.0);
+    encode_proposal(&mut buf, block, previous_state_root);
+    Ok(buf)
+}
+
+/// Somewhere to put RLP bytes.
+trait Sink {
+    fn put(&mut self, bytes: &[u8]);
+}
+
+/// Counts the bytes instead of storing them, to size lists.
+struct Counter(usize);
+
+impl Sink for Counter {
+    fn put(&mut self, bytes: &[u8]) {
+        self.0 += bytes.len();
+    }
+}
+
+impl Sink for Vec<u8> {
+    fn put(&mut self, bytes: &[u8]) {
+        self.extend_from_slice(bytes);
+    }
+}
+
+struct SliceWriter<'a> {
+    buf: &'a mut [u8],
+    pos: usize,
+}
+
+impl<'a> SliceWriter<'a> {
+    fn new(buf: &'a mut [u8]) -> Self {
+        SliceWriter { buf, pos: 0 }
+    }
+}
+
+impl Sink for SliceWriter<'_> {
+    /// Callers size the buffer with a `Counter` pass first.
+    fn put(&mut self, bytes: &[u8]) {
+        self.buf[self.pos..self.pos + bytes.len()].copy_from_slice(bytes);
+        self.pos += bytes.len();
+    }
+}
+
+fn put_len<S: Sink + ?Sized>(sink: &mut S, offset: u8, len: usize) {
+    if len <= 55 {
+        sink.put(&[offset + len as u8]);
+        return;
+    }
+
+    let be = (len as u64).to_be_bytes();
+    let skip = be.iter().take_while(|b| **b == 0).count();
+    sink.put(&[offset + 55 + (8 - skip) as u8]);
+    sink.put(&be[skip..]);
+}
+
+fn put_bytes<S: Sink + ?Sized>(sink: &mut S, bytes: &[u8]) {
+    match bytes {
+        [b] if *b < 0x80 => sink.put(bytes),
+        _ => {
+            put_len(sink, 0x80, bytes.len());
+            sink.put(bytes);
+        }
+    }
+}
+
+fn put_u64<S: Sink + ?Sized>(sink: &mut S, val: u64) {
+    let be = val.to_be_bytes();
+    let skip = be.iter().take_while(|b| **b == 0).count();
+    put_bytes(sink, &be[skip..]);
+}
+
+/// Same as `put_u64`, the RLP of a value is the same whatever its width.
+fn put_u256<S: Sink + ?Sized>(sink: &mut S, val: U256) {
+    let mut be = [0u8; 32];
+    val.to_big_endian(&mut be);
+    let skip = be.iter().take_while(|b| **b == 0).count();
+    put_bytes(sink, &be[skip..]);
+}
+
+/// Write a list whose items `f` puts, computing its length with a dry run.
+fn put_list<S: Sink + ?Sized>(sink: &mut S, f: impl Fn(&mut dyn FnMut(&[u8]))) {
+    let mut len = 0;
+    f(&mut |bytes: &[u8]| len += bytes.len());
+    put_len(sink, 0xc0, len);
+    f(&mut |bytes: &[u8]| sink.put(bytes));
+}
+
+impl Sink for dyn FnMut(&[u8]) + '_ {
+    fn put(&mut self, bytes: &[u8]) {
+        self(bytes)
+    }
+}
+
+/// Mirrors `Encodable for Proposal`.
+fn encode_proposal<S: Sink + ?Sized>(sink: &mut S, block: &AxonBlock, previous_state_root: H256) {
+    let header = &block.header;
+    put_list(sink, |s| {
+        put_list(s, |s| put_u64(s, u8::from(header.version).into()));
+        put_bytes(s, header.prev_hash.as_bytes());
+        put_bytes(s, header.proposer.as_bytes());
+        put_bytes(s, previous_state_root.as_bytes());
+        put_bytes(s, header.transactions_root.as_bytes());
+        put_bytes(s, header.signed_txs_hash.as_bytes());
+        put_u64(s, header.timestamp);
+        put_u64(s, header.number);
+        put_u256(s, header.gas_limit);
+        put_list(s, |s| {
+            for extra in header.extra_data.iter() {
+                put_list(s, |s| put_bytes(s, &extra.inner));
+            }
+        });
+        put_list(s, |s| {
+            put_u64(s, header.proof.number);
+            put_u64(s, header.proof.round);
+            put_bytes(s, header.proof.block_hash.as_bytes());
+            put_bytes(s, &header.proof.signature);
+            put_bytes(s, &header.proof.bitmap);
+        });
+        put_u64(s, header.call_system_script_count.into());
+        put_list(s, |s| {
+            for hash in block.tx_hashes.iter() {
+                put_bytes(s, hash.as_bytes());
+            }
+        });
+    });
+}
+
+/// Mirrors `Encodable for Vote` for the precommit vote for `block_hash`.
+fn encode_vote<S: Sink + ?Sized>(sink: &mut S, number: u64, round: u64, block_hash: H256) {
+    put_list(sink, |s| {
+        put_u64(s, number);
+        put_u64(s, round);
+        put_u64(s, u8::from(VoteType::Precommit).into());
+        put_bytes(s, block_hash.as_bytes());
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use rlp::Encodable;
+
+    use super::*;
+    use crate::types::tests::{mock_header, mock_validator, random_bytes};
+    use crate::types::{ExtraData, Proposal, Vote};
+
+    #[test]
+    fn test_encode_proposal_matches_rlp() {
+        for tx_count in [0, 1, 100] {
+            let mut block = AxonBlock {
+                header:    mock_header(),
+                tx_hashes: (0..tx_count).map(|_| H256(rand::random())).collect(),
+            };
+            block.header.extra_data = vec![ExtraData {
+                inner: random_bytes(70),
+            }];
+            block.header.proof.signature = random_bytes(96);
+            block.header.proof.bitmap = random_bytes(1);
+            let root = H256(rand::random());
+
+            let expect = Proposal::from_block(&block, root).unwrap().rlp_bytes();
+            let mut counter = Counter(0);
+            encode_proposal(&mut counter, &block, root);
+            assert_eq!(counter.0, expect.len());
+
+            let mut buf = vec![0u8; expect.len()];
+            encode_proposal(&mut SliceWriter::new(&mut buf), &block, root);
+            assert_eq!(buf, expect.to_vec());
+            assert_eq!(proposal_rlp(&block, root).unwrap(), buf);
+        }
+    }
+
+    #[test]
+    fn test_gas_limit_overflow() {
+        let mut block = AxonBlock {
+            header:    mock_header(),
+            tx_hashes: vec![],
+        };
+        block.header.gas_limit = U256::from(u64::MAX);
+        let mut proposal = Proposal::from_block(&block, H256::zero()).unwrap();
+        assert_eq!(proposal_rlp(&block, H256::zero()).unwrap(), proposal.rlp_bytes().to_vec());
+
+        block.header.gas_limit = U256::one() << 64;
+        assert!(matches!(proposal_rlp(&block, H256::zero()), Err(Error::GasLimitOverflow)));
+        assert!(matches!(
+            Proposal::from_block(&block, H256::zero()),
+            Err(Error::GasLimitOverflow)
+        ));
+        // A proposal built by hand still encodes.
+        proposal.gas_limit = U256::MAX;
+        assert!(!proposal.rlp_bytes().is_empty());
+    }
+
+    #[test]
+    fn test_unsorted_validators() {
+        let block = AxonBlock {
+            header:    mock_header(),
+            tx_hashes: vec![],
+        };
+        let proof = Proof {
+            signature: random_bytes(96),
+            bitmap: Bytes::from_static(&[0b1110_0000]),
+            ..Default::default()
+        };
+        let validator_list = (1..=3u8).rev().map(mock_validator).collect::<Vec<_>>();
+        let res = verify_proof_static::<4>(
+            &block,
+            H256::zero(),
+            &validator_list,
+            &proof,
+            &mut [0u8; 1024],
+        );
+        assert!(matches!(res, Err(Error::ValidatorsOutOfOrder { index: 1 })));
+    }
+
+    #[test]
+    fn test_encode_vote_matches_rlp() {
+        let proof = Proof {
+            number:     u64::MAX,
+            round:      3,
+            block_hash: H256(rand::random()),
+            ..Default::default()
+        };
+        let vote = Vote {
+            height:     proof.number,
+            round:      proof.round,
+            vote_type:  VoteType::Precommit,
+            block_hash: proof.block_hash.0.to_vec().into(),
+        };
+
+        let mut buf = [0u8; 64];
+        let mut writer = SliceWriter::new(&mut buf);
+        encode_vote(&mut writer, proof.number, proof.round, proof.block_hash);
+        let len = writer.pos;
+        assert_eq!(&buf[..len], &rlp::encode(&vote)[..]);
+    }
+}
@@ -0,0 +1,220 @@
+//! Overlord timeouts derived from `ConsensusConfig`.
+//!
+//! `interval` is the target block interval in milliseconds. Each step ratio
+//! is in tenths of the interval, so a `propose_ratio` of 15 gives the propose
+//! step `1.5 * interval` before the round moves on. Like Overlord's timer,
+//! the helpers double the step timeouts every round of a height, up to
+//! `2^MAX_TIMEOUT_COEF` times those of round 0. The brake timeout is not
+//! scaled.
+//!
+//! A round whose precommit step times out without a quorum enters brake
+//! mode: validators broadcast chokes every brake timeout until a quorum of
+//! them agree to start the next round. A finalized proof with round `r` thus
+//! went through brake mode `r` times.
+
+use core::time::Duration;
+
+use crate::types::{ConsensusConfig, Proof};
+
+/// The denominator of every ratio in `ConsensusConfig`.
+pub const RATIO_DENOMINATOR: u64 = 10;
+/// The round from which Overlord stops doubling the step timeouts.
+pub const MAX_TIMEOUT_COEF: u32 = 5;
+
+/// The timeout of each step of a round.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RoundTimeouts {
+    pub propose:   Duration,
+    pub prevote:   Duration,
+    pub precommit: Duration,
+}
+
+impl RoundTimeouts {
+    pub fn new(config: &ConsensusConfig) -> Self {
+        RoundTimeouts {
+            propose:   step_timeout(config.interval, config.propose_ratio),
+            prevote:   step_timeout(config.interval, config.prevote_ratio),
+            precommit: step_timeout(config.interval, config.precommit_ratio),
+        }
+    }
+
+    /// The timeouts of round `round`, those of round 0 scaled by
+    /// [`timeout_coef`], saturating at `Duration::MAX`.
+    pub fn at_round(config: &ConsensusConfig, round: u32) -> Self {
+        let timeouts = RoundTimeouts::new(config);
+        let coef = timeout_coef(round);
+        RoundTimeouts {
+            propose:   timeouts.propose.saturating_mul(coef),
+            prevote:   timeouts.prevote.saturating_mul(coef),
+            precommit: timeouts.precommit.saturating_mul(coef),
+        }
+    }
+
+    /// How long a round takes when every step times out.
+    pub fn total(&self) -> Duration {
+        self.propose + self.prevote + self.precommit
+    }
+}
+
+/// The brake timeout, waited between retries once a round has failed. It is
+/// the same in every round.
+pub fn brake_timeout(config: &ConsensusConfig) -> Duration {
+    step_timeout(config.interval, config.brake_ratio)
+}
+
+/// What Overlord multiplies the step timeouts of round `round` by, as in its
+/// timer's `set_timer`: `2^min(round, MAX_TIMEOUT_COEF)`.
+pub fn timeout_coef(round: u32) -> u32 {
+    1 << round.min(MAX_TIMEOUT_COEF)
+}
+
+/// The latest a height is finalized after `failed_rounds` rounds time out in
+/// every step, counted from the start of the height. With no failed round
+/// this is the block interval. Saturates at `Duration::MAX` rather than
+/// overflowing on extreme configs.
+pub fn estimated_finality_latency(config: &ConsensusConfig, failed_rounds: u32) -> Duration {
+    let round_timeout = RoundTimeouts::new(config).total();
+    scale(round_timeout, coef_sum(u64::from(failed_rounds)))
+        .and_then(|failed| Duration::from_millis(config.interval).checked_add(failed))
+        .unwrap_or(Duration::MAX)
+}
+
+/// When round `round` of a height enters brake mode, counted from the start
+/// of the height, assuming every earlier round left brake mode after a single
+/// brake timeout. The steps of each round are scaled by [`timeout_coef`], the
+/// brake timeouts are not. Saturates at `Duration::MAX` like
+/// [`estimated_finality_latency`].
+pub fn brake_entry(config: &ConsensusConfig, round: u32) -> Duration {
+    let round_timeout = RoundTimeouts::new(config).total();
+    let brakes = brake_timeout(config).checked_mul(round);
+    scale(round_timeout, coef_sum(u64::from(round) + 1))
+        .zip(brakes)
+        .and_then(|(rounds, brakes)| rounds.checked_add(brakes))
+        .unwrap_or(Duration::MAX)
+}
+
+/// Brake mode seen across a sequence of finalized proofs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BrakeStats {
+    pub blocks:        usize,
+    /// Blocks finalized in a round other than 0.
+    pub braked_blocks: usize,
+    /// Rounds that ended in brake mode, summed over all blocks.
+    pub failed_rounds: u64,
+    pub max_round:     u64,
+}
+
+impl BrakeStats {
+    pub fn from_proofs<'a, I>(proofs: I) -> Self
+    where
+        I: IntoIterator<Item = &'a Proof>,
+    {
+        let mut stats = BrakeStats::default();
+        for proof in proofs {
+            stats.blocks += 1;
+            if proof.round > 0 {
+                stats.braked_blocks += 1;
+            }
+            stats.failed_rounds = stats.failed_rounds.saturating_add(proof.round);
+            stats.max_round = stats.max_round.max(proof.round);
+        }
+        stats
+    }
+}
+
+// The sum of `timeout_coef` over rounds `0..rounds`: the coefficients double
+// up to round `MAX_TIMEOUT_COEF`, and stay there.
+fn coef_sum(rounds: u64) -> u64 {
+    let doubling = rounds.min(u64::from(MAX_TIMEOUT_COEF) + 1);
+    let capped = rounds - doubling;
+    ((1 << doubling) - 1) + capped * u64::from(timeout_coef(MAX_TIMEOUT_COEF))
+}
+
+fn scale(duration: Duration, coef: u64) -> Option<Duration> {
+    const NANOS_PER_SEC: u128 = 1_000_000_000;
+
+    let nanos = duration.as_nanos().checked_mul(u128::from(coef))?;
+    let secs = u64::try_from(nanos / NANOS_PER_SEC).ok()?;
+    Some(Duration::new(secs, (nanos % NANOS_PER_SEC) as u32))
+}
+
+fn step_timeout(interval: u64, ratio: u64) -> Duration {
+    let millis = u128::from(interval) * u128::from(ratio) / u128::from(RATIO_DENOMINATOR);
+    Duration::from_millis(u64::try_from(millis).unwrap_or(u64::MAX))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_timeouts() {
+        let config = ConsensusConfig {
+            interval:        3000,
+            propose_ratio:   15,
+            prevote_ratio:   10,
+            precommit_ratio: 10,
+            brake_ratio:     10,
+            ..Default::default()
+        };
+
+        let timeouts = RoundTimeouts::new(&config);
+        assert_eq!(timeouts.propose, Duration::from_millis(4500));
+        assert_eq!(timeouts.prevote, Duration::from_secs(3));
+        assert_eq!(timeouts.total(), Duration::from_millis(10_500));
+        assert_eq!(brake_timeout(&config), Duration::from_secs(3));
+
+        // Doubled every round up to 32 times, from round 5 on.
+        assert_eq!(RoundTimeouts::at_round(&config, 0), timeouts);
+        assert_eq!(RoundTimeouts::at_round(&config, 1).total(), Duration::from_secs(21));
+        assert_eq!(RoundTimeouts::at_round(&config, 5).total(), Duration::from_secs(336));
+        assert_eq!(RoundTimeouts::at_round(&config, 9), RoundTimeouts::at_round(&config, 5));
+        assert_eq!(coef_sum(0), 0);
+        assert_eq!(coef_sum(6), 63);
+        assert_eq!(coef_sum(8), 127);
+
+        // 3s for the round that finalizes, after 10.5s and 21s of failed
+        // rounds.
+        assert_eq!(estimated_finality_latency(&config, 0), Duration::from_secs(3));
+        assert_eq!(estimated_finality_latency(&config, 2), Duration::from_millis(34_500));
+        assert_eq!(estimated_finality_latency(&config, 8), Duration::from_millis(1_336_500));
+
+        // Rounds of 10.5s, 21s and 42s, with a 3s brake after each of the
+        // first two.
+        assert_eq!(brake_entry(&config, 0), Duration::from_millis(10_500));
+        assert_eq!(brake_entry(&config, 1), Duration::from_millis(34_500));
+        assert_eq!(brake_entry(&config, 2), Duration::from_millis(79_500));
+        assert_eq!(brake_entry(&config, 7), Duration::from_millis(1_354_500));
+    }
+
+    #[test]
+    fn test_timing_saturates() {
+        let config = ConsensusConfig {
+            interval:        u64::MAX,
+            propose_ratio:   u64::MAX,
+            prevote_ratio:   u64::MAX,
+            precommit_ratio: u64::MAX,
+            brake_ratio:     u64::MAX,
+            ..Default::default()
+        };
+        assert_eq!(estimated_finality_latency(&config, u32::MAX), Duration::MAX);
+        assert_eq!(brake_entry(&config, u32::MAX), Duration::MAX);
+    }
+
+    #[test]
+    fn test_brake_stats() {
+        let proof = |round| Proof {
+            round,
+            ..Default::default()
+        };
+        let proofs = vec![proof(0), proof(2), proof(0), proof(1)];
+
+        assert_eq!(BrakeStats::from_proofs(&proofs), BrakeStats {
+            blocks:        4,
+            braked_blocks: 2,
+            failed_rounds: 3,
+            max_round:     2,
+        });
+        assert_eq!(BrakeStats::from_proofs(&[]), BrakeStats::default());
+    }
+}
@@ -0,0 +1,152 @@
+//! Trust bundles for handing a light client's trust root between services.
+//! A bundle names a trusted block by hash and height together with the
+//! metadata that was active at that height, so the receiver can verify the
+//! next proofs without fetching anything else first.
+
+use alloc::vec::Vec;
+
+use ethereum_types::H256;
+use rlp::{Decodable, DecoderError, Encodable, Rlp, RlpStream};
+
+use crate::keccak_256;
+use crate::types::{BlockNumber, Metadata, TypesError, ValidatorExtend};
+
+/// The only format version so far. It is the first item of the RLP encoding
+/// and lets a decoder reject bundles written by a newer release.
+pub const TRUST_BUNDLE_VERSION: u8 = 0;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "impl-serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "impl-serde", serde(rename_all = "camelCase"))]
+pub struct TrustBundle {
+    pub version:            u8,
+    pub header_hash:        H256,
+    #[cfg_attr(
+        feature = "impl-serde",
+        serde(
+            serialize_with = "crate::types::encode::serialize_uint",
+            deserialize_with = "crate::types::decode::deserialize_hex_u64"
+        )
+    )]
+    pub number:             BlockNumber,
+    pub metadata:           Metadata,
+    pub validator_set_hash: H256,
+}
+
+impl TrustBundle {
+    /// Bundle the trusted block `header_hash` at `number` with the metadata
+    /// active at that height.
+    pub fn new(header_hash: H256, number: BlockNumber, metadata: Metadata) -> Self {
+        let validator_set_hash = validator_set_hash(&metadata.verifier_list);
+        TrustBundle {
+            version: TRUST_BUNDLE_VERSION,
+            header_hash,
+            number,
+            metadata,
+            validator_set_hash,
+        }
+    }
+
+    /// Check that the bundle is internally consistent: the format version is
+    /// known, the metadata covers the trusted height and the validator set
+    /// hash matches the metadata's verifier list.
+    pub fn verify(&self) -> Result<(), TypesError> {
+        if self.version != TRUST_BUNDLE_VERSION {
+            return Err(TypesError::UnknownTrustBundleVersion(self.version));
+        }
+        if !self.metadata.version.contains(self.number) {
+            return Err(TypesError::MetadataNotActive(self.number));
+        }
+        if validator_set_hash(&self.metadata.verifier_list) != self.validator_set_hash {
+            return Err(TypesError::ValidatorSetHashMismatch);
+        }
+        Ok(())
+    }
+
+    /// Decode and verify a bundle from its RLP encoding.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, TypesError> {
+        let bundle: TrustBundle = rlp::decode(bytes)?;
+        bundle.verify()?;
+        Ok(bundle)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        rlp::encode(self).to_vec()
+    }
+}
+
+/// The keccak of the RLP list of `validators` in bitmap order, independent of
+/// the order they are given in.
+pub fn validator_set_hash(validators: &[ValidatorExtend]) -> H256 {
+    let mut sorted = validators.to_vec();
+    sorted.sort();
+    let mut s = RlpStream::new();
+    s.append_list(&sorted);
+    H256(keccak_256(&s.out()))
+}
+
+impl Encodable for TrustBundle {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(5)
+            .append(&self.version)
+            .append(&self.header_hash)
+            .append(&self.number)
+            .append(&self.metadata)
+            .append(&self.validator_set_hash);
+    }
+}
+
+impl Decodable for TrustBundle {
+    fn decode(r: &Rlp) -> Result<Self, DecoderError> {
+        Ok(TrustBundle {
+            version:            r.val_at(0)?,
+            header_hash:        r.val_at(1)?,
+            number:             r.val_at(2)?,
+            metadata:           r.val_at(3)?,
+            validator_set_hash: r.val_at(4)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::tests::mock_validator;
+    use crate::types::MetadataBuilder;
+
+    fn bundle() -> TrustBundle {
+        let metadata = MetadataBuilder::new()
+            .version(1, 100)
+            .epoch(0)
+            .validator(mock_validator(1))
+            .validator(mock_validator(2))
+            .build();
+        TrustBundle::new(H256(rand::random()), 50, metadata)
+    }
+
+    #[test]
+    fn test_trust_bundle_roundtrip() {
+        let bundle = bundle();
+        assert!(bundle.verify().is_ok());
+        assert_eq!(TrustBundle::from_bytes(&bundle.to_bytes()).unwrap(), bundle);
+
+        let mut reversed = bundle.metadata.verifier_list.clone();
+        reversed.reverse();
+        assert_eq!(validator_set_hash(&reversed), bundle.validator_set_hash);
+    }
+
+    #[test]
+    fn test_trust_bundle_rejects_inconsistent() {
+        let mut bundle = bundle();
+        bundle.number = 101;
+        assert!(TrustBundle::from_bytes(&bundle.to_bytes()).is_err());
+
+        let mut bundle = self::bundle();
+        bundle.metadata.verifier_list.push(mock_validator(3));
+        assert!(TrustBundle::from_bytes(&bundle.to_bytes()).is_err());
+
+        let mut bundle = self::bundle();
+        bundle.version = TRUST_BUNDLE_VERSION + 1;
+        assert!(bundle.verify().is_err());
+    }
+}
@@ -1,9 +1,13 @@
-use std::cmp::Ordering;
-
+use alloc::string::{String, ToString};
 use alloc::vec::Vec;
 use bytes::{Bytes, BytesMut};
+use core::cmp::Ordering;
+use core::fmt;
+use core::ops::{Deref, Index, RangeBounds};
+use core::slice::SliceIndex;
 use core::str::FromStr;
 use derive_more::{Display, From};
+#[cfg(feature = "impl-serde")]
 use faster_hex::withpfx_lowercase;
 use rlp::{Decodable, DecoderError, Rlp};
 
@@ -16,8 +20,11 @@ use serde::{Deserialize, Serialize};
 use rlp::{Encodable, RlpStream};
 use rlp_derive::{RlpDecodable, RlpEncodable};
 
+use crate::consts::{BLS_SIGNATURE_LENS, MAX_BITMAP_LEN, MAX_PROOF_ROUND};
+#[cfg(feature = "impl-rlp")]
+use crate::hardfork::HardforkInfoInner;
 #[cfg(feature = "hex")]
-use crate::hex::{hex_decode, hex_encode};
+use crate::hex::{hex_decode_with, hex_encode, hex_nibble, HexParseOptions};
 #[cfg(feature = "hex")]
 use crate::Error;
 
@@ -57,14 +64,52 @@ impl Hex {
         self.0.clone()
     }
 
-    fn is_prefixed(s: &str) -> bool {
-        s.starts_with(HEX_PREFIX)
+    /// Parse a hex string, tolerating what `options` allows.
+    pub fn from_str_with(s: &str, options: HexParseOptions) -> Result<Self, Error> {
+        Ok(Hex(hex_decode_with(s, options)?.into()))
+    }
+
+    /// Decode the `0x` prefixed hex text in `buf` into the same buffer, so no
+    /// second allocation is made. Like `from_str`, either case is accepted and
+    /// the length must be even.
+    pub fn from_hex_in_place(mut buf: BytesMut) -> Result<Self, Error> {
+        if !buf.starts_with(HEX_PREFIX.as_bytes()) {
+            return Err(Error::HexPrefix);
+        }
+        let digits = buf.len() - HEX_PREFIX.len();
+        if !digits.is_multiple_of(2) {
+            return Err(faster_hex::Error::InvalidLength(digits).into());
+        }
+
+        // Byte `i` is written after the digits at `2 + 2i` and `3 + 2i` are
+        // read, so the output never overtakes the input.
+        for i in 0..digits / 2 {
+            let hi = hex_nibble(buf[2 + 2 * i])?;
+            let lo = hex_nibble(buf[3 + 2 * i])?;
+            buf[i] = hi << 4 | lo;
+        }
+        buf.truncate(digits / 2);
+        Ok(Hex(buf.freeze()))
+    }
+
+    /// A sub-range sharing the same buffer, no bytes are copied.
+    pub fn slice(&self, range: impl RangeBounds<usize>) -> Self {
+        Hex(self.0.slice(range))
+    }
+
+    /// Join `parts` into a single `Hex`.
+    pub fn concat(parts: &[Hex]) -> Self {
+        let mut ret = BytesMut::with_capacity(parts.iter().map(Hex::len).sum());
+        for part in parts {
+            ret.extend_from_slice(part);
+        }
+        Hex(ret.freeze())
     }
 }
 
 impl Default for Hex {
     fn default() -> Self {
-        Hex(vec![0u8; 8].into())
+        Hex(alloc::vec![0u8; 8].into())
     }
 }
 
@@ -74,15 +119,36 @@ impl AsRef<[u8]> for Hex {
     }
 }
 
+impl Deref for Hex {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl<I: SliceIndex<[u8]>> Index<I> for Hex {
+    type Output = I::Output;
+
+    fn index(&self, index: I) -> &Self::Output {
+        &self.0[index]
+    }
+}
+
+impl<'a> IntoIterator for &'a Hex {
+    type IntoIter = core::slice::Iter<'a, u8>;
+    type Item = &'a u8;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
 impl FromStr for Hex {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if !Self::is_prefixed(s) {
-            return Err(crate::Error::HexPrefix);
-        }
-
-        Ok(Hex(hex_decode(&s[2..])?.into()))
+        Self::from_str_with(s, HexParseOptions::default())
     }
 }
 
@@ -92,6 +158,7 @@ impl From<Hex> for Bytes {
     }
 }
 
+#[cfg(feature = "impl-serde")]
 impl Serialize for Hex {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -101,13 +168,13 @@ impl Serialize for Hex {
     }
 }
 
+#[cfg(feature = "impl-serde")]
 impl<'de> Deserialize<'de> for Hex {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: serde::de::Deserializer<'de>,
     {
-        String::deserialize(deserializer)
-            .and_then(|s| Hex::from_str(&s).map_err(serde::de::Error::custom))
+        crate::hex::deserialize_str_with(deserializer, Hex::from_str)
     }
 }
 
@@ -163,13 +230,83 @@ pub enum TypesError {
 
     #[display(fmt = "InvalidBlockVersion {:?}", _0)]
     InvalidBlockVersion(u8),
+
+    #[display(fmt = "InvalidVoteType {:?}", _0)]
+    #[from(ignore)]
+    InvalidVoteType(u8),
+
+    #[display(fmt = "Invalid signature length {}", _0)]
+    #[from(ignore)]
+    InvalidSignatureLength(usize),
+
+    #[display(fmt = "Block hash is zero")]
+    ZeroBlockHash,
+
+    #[display(fmt = "Bitmap has no signer")]
+    EmptyBitmap,
+
+    #[display(fmt = "Invalid round {}", _0)]
+    #[from(ignore)]
+    InvalidRound(u64),
+
+    #[display(fmt = "Cumulative gas used decreases at receipt {}", index)]
+    CumulativeGasDecrease { index: usize },
+
+    #[display(fmt = "Gas used mismatch, expect {}, get {}", expect, real)]
+    GasUsedMismatch { expect: U256, real: U256 },
+
+    #[display(fmt = "Unexpected checkpoint, expect block {}, get {}", expect, real)]
+    UnexpectedCheckpoint { expect: u64, real: u64 },
+
+    #[display(fmt = "Snapshot interval is zero")]
+    ZeroSnapshotInterval,
+
+    #[display(fmt = "Snapshot commitment mismatch")]
+    SnapshotCommitmentMismatch,
+
+    #[display(fmt = "Rlp decode error {:?}", _0)]
+    Rlp(DecoderError),
+
+    #[display(fmt = "Unknown trust bundle version {}", _0)]
+    #[from(ignore)]
+    UnknownTrustBundleVersion(u8),
+
+    #[display(fmt = "Metadata is not active at block {}", _0)]
+    #[from(ignore)]
+    MetadataNotActive(u64),
+
+    #[display(fmt = "Validator set hash mismatch")]
+    ValidatorSetHashMismatch,
+
+    #[display(fmt = "Duplicate validator {:?}", _0)]
+    DuplicateValidator(H160),
+
+    #[display(fmt = "{} does not fit in {} bits", value, bits)]
+    U256Overflow { value: U256, bits: u32 },
+
+    #[display(fmt = "Invalid SSZ: {}", _0)]
+    #[from(ignore)]
+    InvalidSsz(&'static str),
+
+    #[display(fmt = "Invalid asset {}: {}", symbol, reason)]
+    #[from(ignore)]
+    InvalidAsset {
+        symbol: String,
+        reason: &'static str,
+    },
+
+    #[display(fmt = "Custom extra data needs a hardfork entry in front")]
+    MissingHardforkEntry,
+
+    #[display(fmt = "No epoch follows epoch {} of blocks {} to {}", epoch, start, end)]
+    NoNextEpoch { epoch: u64, start: u64, end: u64 },
 }
 
+#[cfg(feature = "std")]
 impl std::error::Error for TypesError {}
 
 #[derive(Default, Copy, Clone, Debug, PartialEq, Eq)]
-#[cfg(feature = "impl-serde")]
-#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "impl-serde", derive(Serialize, Deserialize))]
 pub enum BlockVersion {
     #[default]
     V0,
@@ -213,6 +350,24 @@ pub type Hash = H256;
 pub type MerkleRoot = Hash;
 pub type BlockNumber = u64;
 
+/// `value` as a `u64`, failing rather than truncating or panicking when it
+/// does not fit.
+pub fn try_into_u64(value: U256) -> Result<u64, TypesError> {
+    if value.bits() > 64 {
+        return Err(TypesError::U256Overflow { value, bits: 64 });
+    }
+    Ok(value.low_u64())
+}
+
+/// `value` as a `u128`, failing rather than truncating or panicking when it
+/// does not fit.
+pub fn try_into_u128(value: U256) -> Result<u128, TypesError> {
+    if value.bits() > 128 {
+        return Err(TypesError::U256Overflow { value, bits: 128 });
+    }
+    Ok(value.low_u128())
+}
+
 #[derive(Default, Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(
     feature = "impl-rlp",
@@ -236,14 +391,49 @@ pub struct ExtraData {
     derive(rlp_derive::RlpEncodable, rlp_derive::RlpDecodable)
 )]
 #[cfg_attr(feature = "impl-serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "strict-serde", serde(deny_unknown_fields))]
 pub struct AxonHeader {
     pub version:                  BlockVersion,
+    #[cfg_attr(
+        feature = "impl-serde",
+        serde(deserialize_with = "decode::deserialize_unpadded", alias = "prevHash")
+    )]
     pub prev_hash:                Hash,
+    #[cfg_attr(
+        feature = "impl-serde",
+        serde(deserialize_with = "decode::deserialize_unpadded")
+    )]
     pub proposer:                 H160,
+    #[cfg_attr(
+        feature = "impl-serde",
+        serde(deserialize_with = "decode::deserialize_unpadded", alias = "stateRoot")
+    )]
     pub state_root:               MerkleRoot,
+    #[cfg_attr(
+        feature = "impl-serde",
+        serde(
+            deserialize_with = "decode::deserialize_unpadded",
+            alias = "transactionsRoot"
+        )
+    )]
     pub transactions_root:        MerkleRoot,
+    #[cfg_attr(
+        feature = "impl-serde",
+        serde(
+            deserialize_with = "decode::deserialize_unpadded",
+            alias = "signedTxsHash"
+        )
+    )]
     pub signed_txs_hash:          Hash,
+    #[cfg_attr(
+        feature = "impl-serde",
+        serde(
+            deserialize_with = "decode::deserialize_unpadded",
+            alias = "receiptsRoot"
+        )
+    )]
     pub receipts_root:            MerkleRoot,
+    #[cfg_attr(feature = "impl-serde", serde(alias = "logBloom"))]
     pub log_bloom:                Bloom,
     #[cfg_attr(
         feature = "impl-serde",
@@ -261,19 +451,33 @@ pub struct AxonHeader {
         )
     )]
     pub number:                   BlockNumber,
+    #[cfg_attr(
+        feature = "impl-serde",
+        serde(deserialize_with = "crate::hex::dec_or_hex::deserialize", alias = "gasUsed")
+    )]
     pub gas_used:                 U256,
+    #[cfg_attr(
+        feature = "impl-serde",
+        serde(deserialize_with = "crate::hex::dec_or_hex::deserialize", alias = "gasLimit")
+    )]
     pub gas_limit:                U256,
     /// Extra data for the block header
     /// The first index of extra_data is used to store hardfork information:
     /// `HardforkInfoInner`
+    #[cfg_attr(feature = "impl-serde", serde(alias = "extraData"))]
     pub extra_data:               Vec<ExtraData>,
+    #[cfg_attr(
+        feature = "impl-serde",
+        serde(deserialize_with = "crate::hex::dec_or_hex::deserialize", alias = "baseFeePerGas")
+    )]
     pub base_fee_per_gas:         U256,
     pub proof:                    Proof,
     #[cfg_attr(
         feature = "impl-serde",
         serde(
             serialize_with = "encode::serialize_uint",
-            deserialize_with = "decode::deserialize_hex_u32"
+            deserialize_with = "decode::deserialize_hex_u32",
+            alias = "callSystemScriptCount"
         )
     )]
     pub call_system_script_count: u32,
@@ -281,12 +485,85 @@ pub struct AxonHeader {
         feature = "impl-serde",
         serde(
             serialize_with = "encode::serialize_uint",
-            deserialize_with = "decode::deserialize_hex_u64"
+            deserialize_with = "decode::deserialize_hex_u64",
+            alias = "chainId"
         )
     )]
     pub chain_id:                 u64,
 }
 
+impl AxonHeader {
+    /// The raw hardfork entry, i.e. the first `extra_data` entry.
+    pub fn hardfork_entry(&self) -> Option<&ExtraData> {
+        self.extra_data.first()
+    }
+
+    /// The entries following the hardfork entry.
+    pub fn custom_entries(&self) -> &[ExtraData] {
+        self.extra_data.get(1..).unwrap_or_default()
+    }
+
+    /// Decode the hardfork activation stored in the first `extra_data` entry,
+    /// `None` if the header carries no extra data.
+    #[cfg(feature = "impl-rlp")]
+    pub fn hardfork_info(&self) -> Result<Option<HardforkInfoInner>, DecoderError> {
+        self.hardfork_entry()
+            .map(|data| rlp::decode(&data.inner))
+            .transpose()
+    }
+
+    /// `keccak(rlp(header))`, the hash the next header refers to as its
+    /// `prev_hash`.
+    #[cfg(all(feature = "impl-rlp", feature = "hash"))]
+    #[cfg_attr(doc_cfg, doc(cfg(all(feature = "impl-rlp", feature = "hash"))))]
+    pub fn hash(&self) -> H256 {
+        H256(crate::keccak_256(&rlp::encode(self)))
+    }
+}
+
+/// Builds an `extra_data` vector with the hardfork entry in first position.
+#[derive(Clone, Debug, Default)]
+pub struct ExtraDataBuilder {
+    hardfork: Option<ExtraData>,
+    custom:   Vec<ExtraData>,
+}
+
+impl ExtraDataBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[cfg(feature = "impl-rlp")]
+    pub fn hardfork(mut self, info: &HardforkInfoInner) -> Self {
+        self.hardfork = Some(ExtraData {
+            inner: rlp::encode(info).freeze(),
+        });
+        self
+    }
+
+    pub fn custom(mut self, inner: Bytes) -> Self {
+        self.custom.push(ExtraData { inner });
+        self
+    }
+
+    /// The hardfork entry, if set, followed by the custom entries. The first
+    /// entry is always read as the hardfork entry, so custom entries without
+    /// one are rejected.
+    pub fn build(self) -> Result<Vec<ExtraData>, TypesError> {
+        let Some(hardfork) = self.hardfork else {
+            if !self.custom.is_empty() {
+                return Err(TypesError::MissingHardforkEntry);
+            }
+            return Ok(Vec::new());
+        };
+
+        let mut extra_data = Vec::with_capacity(self.custom.len() + 1);
+        extra_data.push(hardfork);
+        extra_data.extend(self.custom);
+        Ok(extra_data)
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(
     feature = "impl-rlp",
@@ -295,47 +572,152 @@ pub struct AxonHeader {
 #[cfg_attr(feature = "impl-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AxonBlock {
     pub header:    AxonHeader,
+    #[cfg_attr(feature = "impl-serde", serde(alias = "txHashes"))]
     pub tx_hashes: Vec<H256>,
 }
 
+/// A block bundled with the proof that finalizes it, so that the pair can be
+/// shipped between services as a single unit. The binary encoding is RLP and
+/// the JSON encoding is meant for debugging.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "impl-rlp",
+    derive(rlp_derive::RlpEncodable, rlp_derive::RlpDecodable)
+)]
+#[cfg_attr(feature = "impl-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BlockWithProof {
+    pub block: AxonBlock,
+    pub proof: Proof,
+}
+
+impl BlockWithProof {
+    pub fn new(block: AxonBlock, proof: Proof) -> Self {
+        BlockWithProof { block, proof }
+    }
+
+    #[cfg(feature = "impl-rlp")]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        rlp::encode(self).to_vec()
+    }
+
+    #[cfg(feature = "impl-rlp")]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DecoderError> {
+        rlp::decode(bytes)
+    }
+
+    #[cfg(feature = "proof")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "proof")))]
+    pub fn verify(
+        &self,
+        previous_state_root: H256,
+        validator_list: &[ValidatorExtend],
+    ) -> Result<crate::VerifyReport, crate::Error> {
+        crate::verify_proof(
+            &self.block,
+            previous_state_root,
+            validator_list,
+            &self.proof,
+        )
+    }
+}
+
 #[cfg(feature = "proof")]
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(doc_cfg, doc(cfg(feature = "proof")))]
-#[cfg_attr(feature = "impl-serde", derive(serde::Deserialize))]
+#[cfg_attr(feature = "impl-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Proposal {
     pub version:                  BlockVersion,
+    #[cfg_attr(feature = "impl-serde", serde(alias = "prevHash"))]
     pub prev_hash:                Hash,
     pub proposer:                 H160,
+    #[cfg_attr(feature = "impl-serde", serde(alias = "prevStateRoot"))]
     pub prev_state_root:          MerkleRoot,
+    #[cfg_attr(feature = "impl-serde", serde(alias = "transactionsRoot"))]
     pub transactions_root:        MerkleRoot,
+    #[cfg_attr(feature = "impl-serde", serde(alias = "signedTxsHash"))]
     pub signed_txs_hash:          Hash,
     #[cfg_attr(
         feature = "impl-serde",
-        serde(deserialize_with = "decode::deserialize_hex_u64")
+        serde(
+            serialize_with = "encode::serialize_uint",
+            deserialize_with = "decode::deserialize_hex_u64"
+        )
     )]
     pub timestamp:                u64,
     #[cfg_attr(
         feature = "impl-serde",
-        serde(deserialize_with = "decode::deserialize_hex_u64")
+        serde(
+            serialize_with = "encode::serialize_uint",
+            deserialize_with = "decode::deserialize_hex_u64"
+        )
     )]
     pub number:                   BlockNumber,
+    #[cfg_attr(
+        feature = "impl-serde",
+        serde(deserialize_with = "crate::hex::dec_or_hex::deserialize", alias = "gasLimit")
+    )]
     pub gas_limit:                U256,
+    #[cfg_attr(feature = "impl-serde", serde(alias = "extraData"))]
     pub extra_data:               Vec<ExtraData>,
+    #[cfg_attr(
+        feature = "impl-serde",
+        serde(deserialize_with = "crate::hex::dec_or_hex::deserialize", alias = "baseFeePerGas")
+    )]
     pub base_fee_per_gas:         U256,
     pub proof:                    Proof,
     #[cfg_attr(
         feature = "impl-serde",
-        serde(deserialize_with = "decode::deserialize_hex_u64")
+        serde(
+            serialize_with = "encode::serialize_uint",
+            deserialize_with = "decode::deserialize_hex_u64",
+            alias = "chainId"
+        )
     )]
     pub chain_id:                 u64,
     #[cfg_attr(
         feature = "impl-serde",
-        serde(deserialize_with = "decode::deserialize_hex_u32")
+        serde(
+            serialize_with = "encode::serialize_uint",
+            deserialize_with = "decode::deserialize_hex_u32",
+            alias = "callSystemScriptCount"
+        )
     )]
     pub call_system_script_count: u32,
+    #[cfg_attr(feature = "impl-serde", serde(alias = "txHashes"))]
     pub tx_hashes:                Vec<Hash>,
 }
 
+#[cfg(feature = "proof")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "proof")))]
+impl Proposal {
+    /// The proposal a block was voted on as, whose hash is the one its proof
+    /// commits to. Fails with `Error::GasLimitOverflow` if the block's gas
+    /// limit is wider than the `u64` a proposal holds.
+    pub fn from_block(
+        block: &AxonBlock,
+        previous_state_root: H256,
+    ) -> Result<Self, crate::Error> {
+        try_into_u64(block.header.gas_limit).map_err(|_| crate::Error::GasLimitOverflow)?;
+        Ok(Proposal {
+            version:                  block.header.version,
+            prev_hash:                block.header.prev_hash,
+            proposer:                 block.header.proposer,
+            prev_state_root:          previous_state_root,
+            transactions_root:        block.header.transactions_root,
+            signed_txs_hash:          block.header.signed_txs_hash,
+            timestamp:                block.header.timestamp,
+            number:                   block.header.number,
+            gas_limit:                block.header.gas_limit,
+            extra_data:               block.header.extra_data.clone(),
+            base_fee_per_gas:         block.header.base_fee_per_gas,
+            proof:                    block.header.proof.clone(),
+            chain_id:                 block.header.chain_id,
+            call_system_script_count: block.header.call_system_script_count,
+            tx_hashes:                block.tx_hashes.clone(),
+        })
+    }
+}
+
 #[cfg(feature = "impl-rlp")]
 impl Encodable for Proposal {
     fn rlp_append(&self, s: &mut RlpStream) {
@@ -348,7 +730,9 @@ impl Encodable for Proposal {
             .append(&self.signed_txs_hash)
             .append(&self.timestamp)
             .append(&self.number)
-            .append(&self.gas_limit.as_u64())
+            // Axon encodes a `u64` here, whose RLP is the one of a `U256` of
+            // the same value.
+            .append(&self.gas_limit)
             .append_list(&self.extra_data)
             .append(&self.proof)
             .append(&self.call_system_script_count)
@@ -362,6 +746,7 @@ impl Encodable for Proposal {
     derive(rlp_derive::RlpEncodable, rlp_derive::RlpDecodable)
 )]
 #[cfg_attr(feature = "impl-serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "strict-serde", serde(deny_unknown_fields))]
 pub struct Proof {
     #[cfg_attr(
         feature = "impl-serde",
@@ -379,6 +764,10 @@ pub struct Proof {
         )
     )]
     pub round:      u64,
+    #[cfg_attr(
+        feature = "impl-serde",
+        serde(deserialize_with = "decode::deserialize_unpadded", alias = "blockHash")
+    )]
     pub block_hash: Hash,
     #[cfg_attr(
         feature = "impl-serde",
@@ -398,13 +787,53 @@ pub struct Proof {
     pub bitmap:     Bytes,
 }
 
+impl Proof {
+    /// The number of validators whose bit is set in the bitmap.
+    pub fn signer_count(&self) -> usize {
+        self.bitmap.iter().map(|b| b.count_ones() as usize).sum()
+    }
+
+    /// Cheap structural checks to run before any cryptographic work, so that
+    /// obviously malformed proofs such as `Proof::default()` are rejected with
+    /// a meaningful error.
+    pub fn validate(&self) -> Result<(), TypesError> {
+        if !BLS_SIGNATURE_LENS.contains(&self.signature.len()) {
+            return Err(TypesError::InvalidSignatureLength(self.signature.len()));
+        }
+
+        if self.block_hash.is_zero() {
+            return Err(TypesError::ZeroBlockHash);
+        }
+
+        if self.bitmap.is_empty() || self.signer_count() == 0 {
+            return Err(TypesError::EmptyBitmap);
+        }
+
+        if self.bitmap.len() > MAX_BITMAP_LEN {
+            return Err(TypesError::LengthMismatch {
+                expect: MAX_BITMAP_LEN,
+                real:   self.bitmap.len(),
+            });
+        }
+
+        if self.round > MAX_PROOF_ROUND {
+            return Err(TypesError::InvalidRound(self.round));
+        }
+
+        Ok(())
+    }
+}
+
 #[cfg(feature = "proof")]
 #[derive(rlp_derive::RlpEncodable, rlp_derive::RlpDecodable, Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(doc_cfg, doc(cfg(feature = "proof")))]
 #[cfg_attr(feature = "impl-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Validator {
+    #[cfg_attr(feature = "impl-serde", serde(alias = "pubKey"))]
     pub pub_key:        Bytes,
+    #[cfg_attr(feature = "impl-serde", serde(alias = "proposeWeight"))]
     pub propose_weight: u32,
+    #[cfg_attr(feature = "impl-serde", serde(alias = "voteWeight"))]
     pub vote_weight:    u32,
 }
 
@@ -424,6 +853,60 @@ impl core::cmp::Ord for Validator {
     }
 }
 
+#[cfg(feature = "proof")]
+#[derive(Default, Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "proof")))]
+#[cfg_attr(
+    feature = "impl-serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "u8", try_from = "u8")
+)]
+pub enum VoteType {
+    Prevote,
+    #[default]
+    Precommit,
+}
+
+#[cfg(feature = "proof")]
+impl From<VoteType> for u8 {
+    fn from(value: VoteType) -> Self {
+        match value {
+            VoteType::Prevote => 1,
+            VoteType::Precommit => 2,
+        }
+    }
+}
+
+#[cfg(feature = "proof")]
+impl TryFrom<u8> for VoteType {
+    type Error = TypesError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(VoteType::Prevote),
+            2 => Ok(VoteType::Precommit),
+            _ => Err(TypesError::InvalidVoteType(value)),
+        }
+    }
+}
+
+#[cfg(all(feature = "proof", feature = "impl-rlp"))]
+impl Encodable for VoteType {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        u8::from(*self).rlp_append(s);
+    }
+}
+
+#[cfg(all(feature = "proof", feature = "impl-rlp"))]
+impl Decodable for VoteType {
+    fn decode(r: &Rlp) -> Result<Self, DecoderError> {
+        let vote_type: u8 = r.as_val()?;
+        vote_type
+            .try_into()
+            .map_err(|_| DecoderError::Custom("Invalid vote type"))
+    }
+}
+
 #[cfg(feature = "proof")]
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(doc_cfg, doc(cfg(feature = "proof")))]
@@ -431,29 +914,42 @@ impl core::cmp::Ord for Validator {
 pub struct Vote {
     pub height:     u64,
     pub round:      u64,
-    pub vote_type:  u8,
+    #[cfg_attr(feature = "impl-serde", serde(alias = "voteType"))]
+    pub vote_type:  VoteType,
+    #[cfg_attr(feature = "impl-serde", serde(alias = "blockHash"))]
     pub block_hash: Bytes,
 }
 
 #[cfg(feature = "impl-rlp")]
 impl Encodable for Vote {
     fn rlp_append(&self, s: &mut RlpStream) {
-        let vote_type: u8 = self.vote_type.clone().into();
         s.begin_list(4)
             .append(&self.height)
             .append(&self.round)
-            .append(&vote_type)
+            .append(&self.vote_type)
             .append(&self.block_hash.to_vec());
     }
 }
 
+#[cfg(feature = "impl-rlp")]
+impl Decodable for Vote {
+    fn decode(r: &Rlp) -> Result<Self, DecoderError> {
+        Ok(Vote {
+            height:     r.val_at(0)?,
+            round:      r.val_at(1)?,
+            vote_type:  r.val_at(2)?,
+            block_hash: Bytes::from(r.val_at::<Vec<u8>>(3)?),
+        })
+    }
+}
+
 #[cfg(test)]
 impl Vote {
     fn random() -> Self {
         Self {
             height:     rand::random(),
             round:      rand::random(),
-            vote_type:  2,
+            vote_type:  VoteType::Precommit,
             block_hash: tests::random_bytes(32),
         }
     }
@@ -468,12 +964,18 @@ impl Vote {
 pub struct MetadataVersion {
     #[cfg_attr(
         feature = "impl-serde",
-        serde(deserialize_with = "decode::deserialize_hex_u64")
+        serde(
+            serialize_with = "encode::serialize_uint",
+            deserialize_with = "decode::deserialize_hex_u64"
+        )
     )]
     pub start: BlockNumber,
     #[cfg_attr(
         feature = "impl-serde",
-        serde(deserialize_with = "decode::deserialize_hex_u64")
+        serde(
+            serialize_with = "encode::serialize_uint",
+            deserialize_with = "decode::deserialize_hex_u64"
+        )
     )]
     pub end:   BlockNumber,
 }
@@ -488,25 +990,165 @@ impl MetadataVersion {
     }
 }
 
+/// Integers are `0x` prefixed hex quantities in JSON, written as well as read,
+/// the form Axon's RPC returns metadata in.
 #[derive(Default, Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(
     feature = "impl-rlp",
     derive(rlp_derive::RlpEncodable, rlp_derive::RlpDecodable)
 )]
 #[cfg_attr(feature = "impl-serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "strict-serde", serde(deny_unknown_fields))]
 pub struct Metadata {
     pub version:          MetadataVersion,
     #[cfg_attr(
         feature = "impl-serde",
-        serde(deserialize_with = "decode::deserialize_hex_u64")
+        serde(
+            serialize_with = "encode::serialize_uint",
+            deserialize_with = "decode::deserialize_hex_u64"
+        )
     )]
     pub epoch:            u64,
+    #[cfg_attr(feature = "impl-serde", serde(alias = "verifierList"))]
     pub verifier_list:    Vec<ValidatorExtend>,
-    #[serde(skip_deserializing)]
+    // RPC responses carry a counter this crate cannot parse, it is only read
+    // back through `BinaryMetadata`.
+    #[cfg_attr(
+        all(feature = "impl-serde", not(feature = "strict-serde")),
+        serde(skip_deserializing)
+    )]
+    #[cfg_attr(
+        feature = "strict-serde",
+        serde(default, deserialize_with = "decode::deserialize_ignored")
+    )]
     pub propose_counter:  Vec<ProposeCount>,
+    #[cfg_attr(feature = "impl-serde", serde(alias = "consensusConfig"))]
     pub consensus_config: ConsensusConfig,
 }
 
+impl Metadata {
+    /// The verifier list in the order proof bitmaps are built against, i.e.
+    /// sorted by public key, ready to pass to `verify_proof`.
+    pub fn to_validators(&self) -> Vec<ValidatorExtend> {
+        let mut validators = self.verifier_list.clone();
+        validators.sort();
+        validators
+    }
+
+    /// Reject a verifier list in which a validator appears twice, which would
+    /// count its signature twice towards the quorum.
+    pub fn validate(&self) -> Result<(), TypesError> {
+        match find_duplicate_validator(&self.verifier_list) {
+            Some(address) => Err(TypesError::DuplicateValidator(address)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// `Metadata` for non-self-describing formats such as bincode. `Metadata`'s
+/// own impls skip `propose_counter` when deserializing, which such formats
+/// cannot do, this wrapper writes and reads every field.
+#[cfg(feature = "binary-serde")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "binary-serde")))]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BinaryMetadata(pub Metadata);
+
+#[cfg(feature = "binary-serde")]
+impl Serialize for BinaryMetadata {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let m = &self.0;
+        let fields = (
+            &m.version,
+            m.epoch,
+            &m.verifier_list,
+            &m.propose_counter,
+            &m.consensus_config,
+        );
+        fields.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "binary-serde")]
+impl<'de> Deserialize<'de> for BinaryMetadata {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (version, epoch, verifier_list, propose_counter, consensus_config) =
+            Deserialize::deserialize(deserializer)?;
+        Ok(BinaryMetadata(Metadata {
+            version,
+            epoch,
+            verifier_list,
+            propose_counter,
+            consensus_config,
+        }))
+    }
+}
+
+#[cfg(feature = "binary-serde")]
+impl From<Metadata> for BinaryMetadata {
+    fn from(metadata: Metadata) -> Self {
+        BinaryMetadata(metadata)
+    }
+}
+
+/// Builds a `Metadata` whose verifier list is in bitmap order.
+#[derive(Clone, Debug, Default)]
+pub struct MetadataBuilder {
+    metadata: Metadata,
+}
+
+impl MetadataBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A builder for the epoch after `previous`: its version starts right
+    /// after `previous` ends and spans as many blocks, and the consensus config
+    /// is carried over. Only the new verifier list is left to add. Fails if
+    /// `previous` ends before it starts, or if the next version or epoch
+    /// number overflows.
+    pub fn next_epoch(previous: &Metadata) -> Result<Self, TypesError> {
+        let MetadataVersion { start, end } = previous.version;
+        let next = end.checked_add(1).and_then(|next_start| {
+            let next_end = next_start.checked_add(end.checked_sub(start)?)?;
+            Some((next_start, next_end, previous.epoch.checked_add(1)?))
+        });
+        let (start, end, epoch) = next.ok_or(TypesError::NoNextEpoch {
+            epoch: previous.epoch,
+            start,
+            end,
+        })?;
+        Ok(Self::new()
+            .version(start, end)
+            .epoch(epoch)
+            .consensus_config(previous.consensus_config.clone()))
+    }
+
+    pub fn version(mut self, start: BlockNumber, end: BlockNumber) -> Self {
+        self.metadata.version = MetadataVersion::new(start, end);
+        self
+    }
+
+    pub fn epoch(mut self, epoch: u64) -> Self {
+        self.metadata.epoch = epoch;
+        self
+    }
+
+    pub fn validator(mut self, validator: ValidatorExtend) -> Self {
+        self.metadata.verifier_list.push(validator);
+        self
+    }
+
+    pub fn consensus_config(mut self, config: ConsensusConfig) -> Self {
+        self.metadata.consensus_config = config;
+        self
+    }
+
+    pub fn build(mut self) -> Metadata {
+        self.metadata.verifier_list.sort();
+        self.metadata
+    }
+}
+
 #[derive(Default, Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(
     feature = "impl-rlp",
@@ -516,42 +1158,73 @@ pub struct Metadata {
 pub struct ConsensusConfig {
     #[cfg_attr(
         feature = "impl-serde",
-        serde(deserialize_with = "decode::deserialize_hex_u64")
+        serde(
+            serialize_with = "encode::serialize_uint",
+            deserialize_with = "decode::deserialize_hex_u64",
+            alias = "gasLimit"
+        )
     )]
     pub gas_limit:       u64,
     #[cfg_attr(
         feature = "impl-serde",
-        serde(deserialize_with = "decode::deserialize_hex_u64")
+        serde(
+            serialize_with = "encode::serialize_uint",
+            deserialize_with = "decode::deserialize_hex_u64"
+        )
     )]
     pub interval:        u64,
     #[cfg_attr(
         feature = "impl-serde",
-        serde(deserialize_with = "decode::deserialize_hex_u64")
+        serde(
+            serialize_with = "encode::serialize_uint",
+            deserialize_with = "decode::deserialize_hex_u64",
+            alias = "proposeRatio"
+        )
     )]
     pub propose_ratio:   u64,
     #[cfg_attr(
         feature = "impl-serde",
-        serde(deserialize_with = "decode::deserialize_hex_u64")
+        serde(
+            serialize_with = "encode::serialize_uint",
+            deserialize_with = "decode::deserialize_hex_u64",
+            alias = "prevoteRatio"
+        )
     )]
     pub prevote_ratio:   u64,
     #[cfg_attr(
         feature = "impl-serde",
-        serde(deserialize_with = "decode::deserialize_hex_u64")
+        serde(
+            serialize_with = "encode::serialize_uint",
+            deserialize_with = "decode::deserialize_hex_u64",
+            alias = "precommitRatio"
+        )
     )]
     pub precommit_ratio: u64,
     #[cfg_attr(
         feature = "impl-serde",
-        serde(deserialize_with = "decode::deserialize_hex_u64")
+        serde(
+            serialize_with = "encode::serialize_uint",
+            deserialize_with = "decode::deserialize_hex_u64",
+            alias = "brakeRatio"
+        )
     )]
     pub brake_ratio:     u64,
     #[cfg_attr(
         feature = "impl-serde",
-        serde(deserialize_with = "decode::deserialize_hex_u64")
+        serde(
+            serialize_with = "encode::serialize_uint",
+            deserialize_with = "decode::deserialize_hex_u64",
+            alias = "txNumLimit"
+        )
     )]
     pub tx_num_limit:    u64,
     #[cfg_attr(
         feature = "impl-serde",
-        serde(deserialize_with = "decode::deserialize_hex_u64")
+        serde(
+            serialize_with = "encode::serialize_uint",
+            deserialize_with = "decode::deserialize_hex_u64",
+            alias = "maxTxSize"
+        )
     )]
     pub max_tx_size:     u64,
 }
@@ -562,7 +1235,10 @@ pub struct ProposeCount {
     pub address: H160,
     #[cfg_attr(
         feature = "impl-serde",
-        serde(deserialize_with = "decode::deserialize_hex_u64")
+        serde(
+            serialize_with = "encode::serialize_uint",
+            deserialize_with = "decode::deserialize_hex_u64"
+        )
     )]
     pub count:   u64,
 }
@@ -574,21 +1250,47 @@ pub struct ProposeCount {
 )]
 #[cfg_attr(feature = "impl-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ValidatorExtend {
+    #[cfg_attr(feature = "impl-serde", serde(alias = "blsPubKey"))]
     pub bls_pub_key:    Hex,
+    #[cfg_attr(feature = "impl-serde", serde(alias = "pubKey"))]
     pub pub_key:        Hex,
     pub address:        H160,
     #[cfg_attr(
         feature = "impl-serde",
-        serde(deserialize_with = "decode::deserialize_hex_u32")
+        serde(
+            serialize_with = "encode::serialize_uint",
+            deserialize_with = "decode::deserialize_hex_u32",
+            alias = "proposeWeight"
+        )
     )]
     pub propose_weight: u32,
     #[cfg_attr(
         feature = "impl-serde",
-        serde(deserialize_with = "decode::deserialize_hex_u32")
+        serde(
+            serialize_with = "encode::serialize_uint",
+            deserialize_with = "decode::deserialize_hex_u32",
+            alias = "voteWeight"
+        )
     )]
     pub vote_weight:    u32,
 }
 
+/// The address of the first validator that shares its address, public key or
+/// BLS public key with an earlier one.
+pub fn find_duplicate_validator(validators: &[ValidatorExtend]) -> Option<H160> {
+    validators
+        .iter()
+        .enumerate()
+        .find(|(i, v)| {
+            validators[..*i].iter().any(|prev| {
+                prev.address == v.address
+                    || prev.pub_key == v.pub_key
+                    || prev.bls_pub_key == v.bls_pub_key
+            })
+        })
+        .map(|(_, v)| v.address)
+}
+
 impl PartialOrd for ValidatorExtend {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         Some(self.cmp(&other))
@@ -611,8 +1313,8 @@ impl From<ValidatorExtend> for Validator {
     }
 }
 
-impl std::fmt::Debug for ValidatorExtend {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+impl fmt::Debug for ValidatorExtend {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let bls_pub_key = self.bls_pub_key.as_string_trim0x();
         let pk = if bls_pub_key.len() > 8 {
             unsafe { bls_pub_key.get_unchecked(0..8) }
@@ -628,26 +1330,77 @@ impl std::fmt::Debug for ValidatorExtend {
     }
 }
 
+impl fmt::Display for AxonHeader {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "header ")?;
+        fmt_header_summary(self, f)
+    }
+}
+
+impl fmt::Display for AxonBlock {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "block ")?;
+        fmt_header_summary(&self.header, f)?;
+        write!(f, ", txs {}", self.tx_hashes.len())
+    }
+}
+
+/// The number and, when it can be computed, the hash of the block, then its
+/// proposer, gas used and the signer count of the proof of its parent.
+fn fmt_header_summary(header: &AxonHeader, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "#{}", header.number)?;
+    #[cfg(all(feature = "impl-rlp", feature = "hash"))]
+    write!(f, " hash {}", header.hash())?;
+    write!(
+        f,
+        ", proposer {}, gas used {}, parent signers {}",
+        header.proposer,
+        header.gas_used,
+        header.proof.signer_count()
+    )
+}
+
+impl fmt::Display for Proof {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "proof #{} round {}, block hash {}, signers {}",
+            self.number,
+            self.round,
+            self.block_hash,
+            self.signer_count()
+        )
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "impl-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NodePubKey {
+    #[cfg_attr(feature = "impl-serde", serde(alias = "blsPubKey"))]
     pub bls_pub_key: Bytes,
+    #[cfg_attr(feature = "impl-serde", serde(alias = "pubKey"))]
     pub pub_key:     Bytes,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "impl-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CkbRelatedInfo {
+    #[cfg_attr(feature = "impl-serde", serde(alias = "metadataTypeId"))]
     pub metadata_type_id:     H256,
+    #[cfg_attr(feature = "impl-serde", serde(alias = "checkpointTypeId"))]
     pub checkpoint_type_id:   H256,
+    #[cfg_attr(feature = "impl-serde", serde(alias = "xudtArgs"))]
     pub xudt_args:            H256,
+    #[cfg_attr(feature = "impl-serde", serde(alias = "stakeSmtTypeId"))]
     pub stake_smt_type_id:    H256,
+    #[cfg_attr(feature = "impl-serde", serde(alias = "delegateSmtTypeId"))]
     pub delegate_smt_type_id: H256,
+    #[cfg_attr(feature = "impl-serde", serde(alias = "rewardSmtTypeId"))]
     pub reward_smt_type_id:   H256,
 }
 
 #[cfg(feature = "impl-serde")]
-mod encode {
+pub(crate) mod encode {
     use ethereum_types::U256;
     use serde::ser::Serializer;
     static CHARS: &[u8] = b"0123456789abcdef";
@@ -674,7 +1427,7 @@ mod encode {
         }
 
         // SAFETY: all characters come either from CHARS or "0x", therefore valid UTF8
-        unsafe { std::str::from_utf8_unchecked(&v[0..idx]) }
+        unsafe { core::str::from_utf8_unchecked(&v[0..idx]) }
     }
 
     pub fn serialize_uint<S, U>(val: &U, s: S) -> Result<S::Ok, S::Error>
@@ -698,11 +1451,14 @@ mod encode {
 }
 
 #[cfg(feature = "impl-serde")]
-mod decode {
+pub(crate) mod decode {
+    use alloc::string::{String, ToString};
+    use alloc::vec::Vec;
     // use bytes::Bytes;
     use ethereum_types::U256;
-    use serde::de::{Deserialize, Deserializer};
+    use serde::de::Deserializer;
 
+    use crate::hex::{deserialize_str_with, hex_decode_with, HexParseOptions};
     // use crate::types::Hex;
 
     // pub fn deserialize_u64<'de, D: Deserializer<'de>>(deserializer: D) ->
@@ -731,71 +1487,145 @@ mod decode {
     //     }
     // }
 
-    pub fn from_hex(hex: &str) -> Result<Vec<u8>, &'static str> {
-        // println!("from_hex hex: {:x?}", hex);
-        let mut bytes = Vec::with_capacity((hex.len() + 1) / 2);
-
-        let mut start_i = 0;
-        if hex.len() % 2 != 0 {
-            let byte =
-                u8::from_str_radix(&hex[0..1], 16).map_err(|_| "Failed to parse hex string")?;
-            bytes.push(byte);
-            start_i = 1;
-        }
+    /// Decode the digits of a quantity, odd lengths are left-padded.
+    pub fn from_hex(hex: &str) -> Result<Vec<u8>, crate::Error> {
+        let options = HexParseOptions {
+            allow_missing_prefix: true,
+            ..HexParseOptions::QUANTITY
+        };
+        hex_decode_with(hex, options)
+    }
 
-        for i in (start_i..hex.len()).step_by(2) {
-            let end_i = if i + 2 > hex.len() { i + 1 } else { i + 2 };
-            // println!("i:{}, end_i: {}, byte: {:x?}", i, end_i, &hex[i..end_i]);
-            let byte =
-                u8::from_str_radix(&hex[i..end_i], 16).map_err(|_| "Failed to parse hex string")?;
-            bytes.push(byte);
-        }
+    /// Accept and discard the field, so that fields which are never
+    /// deserialized are not reported as unknown in strict mode.
+    #[cfg(feature = "strict-serde")]
+    pub fn deserialize_ignored<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: Default,
+    {
+        deserializer.deserialize_ignored_any(serde::de::IgnoredAny)?;
+        Ok(T::default())
+    }
 
-        Ok(bytes)
+    /// Deserialize a fixed width hash or address from `0x` prefixed hex,
+    /// left-padding short input such as `"0x1"` with zeros.
+    pub fn deserialize_unpadded<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: Default + AsMut<[u8]>,
+    {
+        deserialize_str_with(deserializer, |s| {
+            let hex = s.strip_prefix("0x").ok_or_else(|| "Hex should start with 0x".to_string())?;
+            let bytes = from_hex(hex).map_err(|e| e.to_string())?;
+
+            let mut ret = T::default();
+            let out = ret.as_mut();
+            if bytes.len() > out.len() {
+                return Err(alloc::format!(
+                    "Expect at most {} bytes, get {}",
+                    out.len(),
+                    bytes.len()
+                ));
+            }
+            let offset = out.len() - bytes.len();
+            out[offset..].copy_from_slice(&bytes);
+            Ok(ret)
+        })
     }
 
     pub fn deserialize_hex_u32<'de, D>(deserializer: D) -> Result<u32, D::Error>
     where
         D: Deserializer<'de>,
-        // U: From<U256>,
     {
-        let s = String::deserialize(deserializer)?;
-        if s == "0x0" {
-            return Ok(0);
-        }
-
-        if s.len() >= 2 && &s[0..2] == "0x" {
-            let bytes = from_hex(&s[2..]).map_err(serde::de::Error::custom)?;
-            let val = U256::from_big_endian(&bytes);
-            Ok(val.low_u32())
-        } else {
-            Err(serde::de::Error::custom("Invalid format"))
-        }
+        deserialize_str_with(deserializer, |s| {
+            u32::try_from(hex_quantity(s)?).map_err(|_| alloc::format!("Hex {} overflows u32", s))
+        })
     }
 
     pub fn deserialize_hex_u64<'de, D>(deserializer: D) -> Result<u64, D::Error>
     where
         D: Deserializer<'de>,
     {
-        let s = String::deserialize(deserializer)?;
-        // println!("deserialize s: {}", s);
+        deserialize_str_with(deserializer, |s| {
+            u64::try_from(hex_quantity(s)?).map_err(|_| alloc::format!("Hex {} overflows u64", s))
+        })
+    }
+
+    fn hex_quantity(s: &str) -> Result<U256, String> {
         if s == "0x0" {
-            return Ok(0);
+            return Ok(U256::zero());
         }
 
-        if s.len() >= 2 && &s[0..2] == "0x" {
-            let bytes = from_hex(&s[2..]).map_err(serde::de::Error::custom)?;
-            // println!("bytes: {:x?}", bytes);
-            let val = U256::from_big_endian(&bytes);
-            // println!("val: {:?}", val);
-            Ok(val.low_u64())
-        } else {
-            Err(serde::de::Error::custom("Invalid format"))
+        match s.strip_prefix("0x") {
+            Some(hex) => {
+                let bytes = from_hex(hex).map_err(|e| e.to_string())?;
+                let len = bytes.len().saturating_sub(32);
+                if bytes[..len].iter().any(|b| *b != 0) {
+                    return Err(alloc::format!("Hex {} overflows U256", s));
+                }
+                Ok(U256::from_big_endian(&bytes[len..]))
+            }
+            None => Err("Invalid format".to_string()),
         }
     }
 
     #[cfg(test)]
     mod tests {
+        #[test]
+        fn test_from_hex_left_pads() {
+            assert_eq!(super::from_hex("abc").unwrap(), vec![0x0a, 0xbc]);
+            assert_eq!(super::from_hex("0abc").unwrap(), vec![0x0a, 0xbc]);
+            assert_eq!(super::from_hex("").unwrap(), Vec::<u8>::new());
+            assert!(super::from_hex("abg").is_err());
+            assert!(super::from_hex("0x0x12").is_err());
+        }
+
+        #[test]
+        fn test_deserialize_unpadded() {
+            use ethereum_types::{H160, H256};
+            use serde::Deserialize;
+
+            #[derive(Deserialize)]
+            struct Fields {
+                #[serde(deserialize_with = "super::deserialize_unpadded")]
+                hash:    H256,
+                #[serde(deserialize_with = "super::deserialize_unpadded")]
+                address: H160,
+            }
+
+            let json_str = r#"{"hash": "0x1", "address": "0xabc"}"#;
+            let fields: Fields = serde_json::from_str(json_str).unwrap();
+            assert_eq!(fields.hash, H256::from_low_u64_be(1));
+            assert_eq!(fields.address, H160::from_low_u64_be(0xabc));
+
+            let too_long = format!(r#"{{"hash": "0x1", "address": "0x{}"}}"#, "1".repeat(42));
+            assert!(serde_json::from_str::<Fields>(&too_long).is_err());
+        }
+
+        #[test]
+        fn test_deserialize_hex_overflow() {
+            use serde::Deserialize;
+
+            #[derive(Debug, Deserialize)]
+            struct Quantities {
+                #[serde(deserialize_with = "super::deserialize_hex_u32")]
+                small: u32,
+                #[serde(deserialize_with = "super::deserialize_hex_u64")]
+                large: u64,
+            }
+
+            let parse = |small: &str, large: &str| {
+                let json = format!(r#"{{"small": "{}", "large": "{}"}}"#, small, large);
+                serde_json::from_str::<Quantities>(&json)
+            };
+            let max = parse("0xffffffff", "0xffffffffffffffff").unwrap();
+            assert_eq!((max.small, max.large), (u32::MAX, u64::MAX));
+            assert!(parse("0x100000000", "0x0").is_err());
+            assert!(parse("0x0", "0x10000000000000000").is_err());
+            assert!(parse("0x0", &format!("0x1{}", "0".repeat(64))).is_err());
+        }
+
         #[cfg(all(
             feature = "hex",
             feature = "proof",
@@ -831,13 +1661,372 @@ mod decode {
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
 
     pub fn random_bytes(len: usize) -> Bytes {
         (0..len).map(|_| rand::random()).collect::<Vec<u8>>().into()
     }
 
+    /// A validator whose keys and address are `seed` repeated, so validators
+    /// of distinct seeds are distinct and sort by seed.
+    pub fn mock_validator(seed: u8) -> ValidatorExtend {
+        ValidatorExtend {
+            bls_pub_key:    Hex::encode([seed; 48]),
+            pub_key:        Hex::encode([seed; 33]),
+            address:        H160::repeat_byte(seed),
+            propose_weight: 1,
+            vote_weight:    1,
+        }
+    }
+
+    pub fn mock_header() -> AxonHeader {
+        AxonHeader {
+            version:                  BlockVersion::V0,
+            prev_hash:                H256(rand::random()),
+            proposer:                 H160(rand::random()),
+            state_root:               H256(rand::random()),
+            transactions_root:        H256(rand::random()),
+            signed_txs_hash:          H256(rand::random()),
+            receipts_root:            H256(rand::random()),
+            log_bloom:                Bloom::default(),
+            timestamp:                rand::random(),
+            number:                   rand::random(),
+            gas_used:                 U256::from(21000),
+            gas_limit:                U256::from(30_000_000),
+            extra_data:               Vec::new(),
+            base_fee_per_gas:         U256::from(1337),
+            proof:                    Proof::default(),
+            call_system_script_count: 0,
+            chain_id:                 2022,
+        }
+    }
+
+    #[test]
+    fn test_metadata_to_validators() {
+        let metadata = Metadata {
+            verifier_list: vec![mock_validator(3), mock_validator(1), mock_validator(2)],
+            ..Default::default()
+        };
+
+        let validators = metadata.to_validators();
+        let pub_keys = validators.iter().map(|v| v.pub_key[0]).collect::<Vec<_>>();
+        assert_eq!(pub_keys, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_metadata_validate_duplicates() {
+        let mut metadata = MetadataBuilder::new()
+            .validator(mock_validator(1))
+            .validator(mock_validator(2))
+            .build();
+        assert!(metadata.validate().is_ok());
+
+        let mut duplicate = mock_validator(3);
+        duplicate.pub_key = metadata.verifier_list[0].pub_key.clone();
+        metadata.verifier_list.push(duplicate.clone());
+        assert!(matches!(
+            metadata.validate(),
+            Err(TypesError::DuplicateValidator(address)) if address == duplicate.address
+        ));
+    }
+
+    #[test]
+    fn test_metadata_builder_next_epoch() {
+        let previous = MetadataBuilder::new()
+            .version(1, 100)
+            .epoch(0)
+            .consensus_config(ConsensusConfig {
+                interval: 3000,
+                ..Default::default()
+            })
+            .build();
+
+        let next = MetadataBuilder::next_epoch(&previous).unwrap().build();
+        assert_eq!(next.version, MetadataVersion::new(101, 200));
+        assert_eq!(next.epoch, 1);
+        assert_eq!(next.consensus_config, previous.consensus_config);
+        assert!(next.verifier_list.is_empty());
+
+        // The next version or epoch overflows, or the previous version is
+        // reversed.
+        let cases = [
+            (1, u64::MAX, 0),
+            (u64::MAX - 10, u64::MAX - 5, 0),
+            (1, 100, u64::MAX),
+            (101, 100, 0),
+        ];
+        for (start, end, epoch) in cases {
+            let previous = MetadataBuilder::new().version(start, end).epoch(epoch).build();
+            assert!(matches!(
+                MetadataBuilder::next_epoch(&previous),
+                Err(TypesError::NoNextEpoch { .. })
+            ));
+        }
+    }
+
+    #[cfg(feature = "binary-serde")]
+    #[test]
+    fn test_bincode_roundtrip() {
+        fn roundtrip<T>(value: T)
+        where
+            T: Serialize + serde::de::DeserializeOwned + PartialEq + fmt::Debug,
+        {
+            let raw = bincode::serialize(&value).unwrap();
+            assert_eq!(bincode::deserialize::<T>(&raw).unwrap(), value);
+        }
+
+        let mut header = mock_header();
+        header.extra_data = vec![ExtraData {
+            inner: random_bytes(10),
+        }];
+        header.proof.signature = random_bytes(96);
+        header.proof.bitmap = random_bytes(1);
+        roundtrip(header.clone());
+
+        let block = AxonBlock {
+            header,
+            tx_hashes: vec![H256(rand::random())],
+        };
+        #[cfg(feature = "proof")]
+        roundtrip(Proposal::from_block(&block, H256(rand::random())).unwrap());
+        roundtrip(block);
+
+        roundtrip(BinaryMetadata(Metadata {
+            version:          MetadataVersion::new(1, 100),
+            epoch:            1,
+            verifier_list:    vec![ValidatorExtend {
+                vote_weight: 2,
+                ..mock_validator(1)
+            }],
+            propose_counter:  vec![ProposeCount {
+                address: H160(rand::random()),
+                count:   7,
+            }],
+            consensus_config: ConsensusConfig {
+                interval: 3000,
+                ..Default::default()
+            },
+        }));
+    }
+
+    #[test]
+    fn test_metadata_serde_roundtrip() {
+        let metadata = Metadata {
+            version:          MetadataVersion::new(1, 100),
+            epoch:            0,
+            verifier_list:    vec![ValidatorExtend {
+                vote_weight: 2,
+                ..mock_validator(1)
+            }],
+            propose_counter:  Vec::new(),
+            consensus_config: ConsensusConfig {
+                gas_limit: 30_000_000,
+                interval:  3000,
+                ..Default::default()
+            },
+        };
+
+        let json = serde_json::to_string(&metadata).unwrap();
+        assert!(json.contains(r#""interval":"0xbb8""#));
+        assert_eq!(serde_json::from_str::<Metadata>(&json).unwrap(), metadata);
+    }
+
+    #[test]
+    fn test_metadata_serializes_hex_quantities() {
+        // Metadata as Axon's RPC returns it, with every integer field set.
+        let rpc = serde_json::json!({
+            "version": { "start": "0x1", "end": "0x64" },
+            "epoch": "0x2",
+            "verifier_list": [{
+                "bls_pub_key": format!("0x{}", "01".repeat(48)),
+                "pub_key": format!("0x{}", "01".repeat(33)),
+                "address": format!("0x{}", "01".repeat(20)),
+                "propose_weight": "0x3",
+                "vote_weight": "0x0"
+            }],
+            "propose_counter": [],
+            "consensus_config": {
+                "gas_limit": "0x1c9c380",
+                "interval": "0xbb8",
+                "propose_ratio": "0xf",
+                "prevote_ratio": "0xa",
+                "precommit_ratio": "0xa",
+                "brake_ratio": "0xa",
+                "tx_num_limit": "0x4e20",
+                "max_tx_size": "0x186a00"
+            }
+        });
+        let metadata: Metadata = serde_json::from_value(rpc.clone()).unwrap();
+        assert_eq!(metadata.verifier_list[0].propose_weight, 3);
+        assert_eq!(metadata.consensus_config.max_tx_size, 0x18_6a00);
+        assert_eq!(serde_json::to_value(&metadata).unwrap(), rpc);
+
+        let counter = ProposeCount {
+            address: H160::zero(),
+            count:   u64::MAX,
+        };
+        assert_eq!(
+            serde_json::to_value(counter).unwrap(),
+            serde_json::json!({
+                "address": format!("0x{}", "00".repeat(20)),
+                "count": "0xffffffffffffffff"
+            })
+        );
+    }
+
+    #[test]
+    fn test_hex_byte_access() {
+        let hex = Hex::from_str("0x00112233").unwrap();
+        assert_eq!(hex[1], 0x11);
+        assert_eq!(&hex[2..], &[0x22, 0x33]);
+        assert!(hex.starts_with(&[0x00, 0x11]));
+        assert_eq!(hex.iter().copied().max(), Some(0x33));
+        assert_eq!((&hex).into_iter().count(), 4);
+
+        let head = hex.slice(..2);
+        let tail = hex.slice(2..);
+        assert_eq!(head.as_string(), "0x0011");
+        assert_eq!(Hex::concat(&[head, tail]), hex);
+        assert!(Hex::concat(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_try_into_primitive() {
+        assert_eq!(try_into_u64(U256::from(u64::MAX)).unwrap(), u64::MAX);
+        assert!(try_into_u64(U256::from(u64::MAX) + 1).is_err());
+        assert_eq!(try_into_u128(U256::from(u128::MAX)).unwrap(), u128::MAX);
+        assert!(matches!(
+            try_into_u128(U256::MAX),
+            Err(TypesError::U256Overflow { bits: 128, .. })
+        ));
+    }
+
+    #[test]
+    fn test_hex_from_hex_in_place() {
+        let hex = Hex::from_hex_in_place(BytesMut::from("0x00Aabb")).unwrap();
+        assert_eq!(hex, Hex::from_str("0x00aabb").unwrap());
+        assert!(Hex::from_hex_in_place(BytesMut::from("0x")).unwrap().is_empty());
+
+        assert!(Hex::from_hex_in_place(BytesMut::from("00aa")).is_err());
+        assert!(Hex::from_hex_in_place(BytesMut::from("0x0aa")).is_err());
+        assert!(Hex::from_hex_in_place(BytesMut::from("0x0g")).is_err());
+    }
+
+    #[cfg(feature = "impl-serde")]
+    #[test]
+    fn test_hex_deserialize_borrowed_and_owned() {
+        let json = r#"["0x0011", "0x2233"]"#;
+        let expect = vec![Hex::encode([0x00, 0x11]), Hex::encode([0x22, 0x33])];
+        assert_eq!(serde_json::from_str::<Vec<Hex>>(json).unwrap(), expect);
+        assert_eq!(
+            serde_json::from_reader::<_, Vec<Hex>>(json.as_bytes()).unwrap(),
+            expect
+        );
+        assert_eq!(
+            serde_json::from_value::<Hex>(serde_json::json!("0x0011")).unwrap(),
+            expect[0]
+        );
+        assert!(serde_json::from_str::<Hex>("1").is_err());
+    }
+
+    #[test]
+    fn test_block_with_proof_codec() {
+        let bundle = BlockWithProof::new(
+            AxonBlock {
+                header:    mock_header(),
+                tx_hashes: vec![H256(rand::random()), H256(rand::random())],
+            },
+            Proof {
+                number:     1,
+                round:      0,
+                block_hash: H256(rand::random()),
+                signature:  random_bytes(96),
+                bitmap:     random_bytes(1),
+            },
+        );
+
+        let decoded = BlockWithProof::from_bytes(&bundle.to_bytes()).unwrap();
+        assert_eq!(bundle, decoded);
+
+        let json = serde_json::to_string(&bundle).unwrap();
+        let decoded: BlockWithProof = serde_json::from_str(&json).unwrap();
+        assert_eq!(bundle, decoded);
+    }
+
+    #[cfg(feature = "hash")]
+    #[test]
+    fn test_header_display() {
+        let mut header = mock_header();
+        header.number = 10;
+        header.proposer = H160::repeat_byte(0xcd);
+        header.proof.bitmap = Bytes::from(vec![0b1100_0000]);
+        let expect = format!(
+            "#10 hash {}, proposer 0xcdcd…cdcd, gas used 21000, parent signers 2",
+            header.hash()
+        );
+        assert_ne!(header.hash(), header.prev_hash);
+        assert_eq!(header.to_string(), format!("header {}", expect));
+
+        let block = AxonBlock {
+            header,
+            tx_hashes: vec![H256::zero(); 3],
+        };
+        assert_eq!(block.to_string(), format!("block {}, txs 3", expect));
+    }
+
+    #[test]
+    fn test_proof_display() {
+        let proof = Proof {
+            number:     10,
+            round:      1,
+            block_hash: H256::repeat_byte(0xab),
+            signature:  random_bytes(96),
+            bitmap:     Bytes::from(vec![0b1110_0000]),
+        };
+        assert_eq!(
+            proof.to_string(),
+            "proof #10 round 1, block hash 0xabab…abab, signers 3"
+        );
+    }
+
+    #[test]
+    fn test_proof_validate() {
+        assert!(matches!(
+            Proof::default().validate(),
+            Err(TypesError::InvalidSignatureLength(0))
+        ));
+
+        let mut proof = Proof {
+            number:     1,
+            round:      0,
+            block_hash: H256::repeat_byte(1),
+            signature:  random_bytes(96),
+            bitmap:     Bytes::from(vec![0b1110_0000]),
+        };
+        assert!(proof.validate().is_ok());
+
+        proof.bitmap = Bytes::from(vec![0u8]);
+        assert!(matches!(proof.validate(), Err(TypesError::EmptyBitmap)));
+    }
+
+    #[test]
+    fn test_serde_camel_case_alias() {
+        let snake = r#"{"number": "0x1", "round": "0x2", "block_hash": "0x0101010101010101010101010101010101010101010101010101010101010101", "signature": "0x1234", "bitmap": "0x80"}"#;
+        let camel = r#"{"number": "0x1", "round": "0x2", "blockHash": "0x0101010101010101010101010101010101010101010101010101010101010101", "signature": "0x1234", "bitmap": "0x80"}"#;
+        let snake: Proof = serde_json::from_str(snake).unwrap();
+        let camel: Proof = serde_json::from_str(camel).unwrap();
+        assert_eq!(snake, camel);
+        assert_eq!(camel.block_hash, H256::repeat_byte(1));
+    }
+
+    #[cfg(feature = "strict-serde")]
+    #[test]
+    fn test_strict_serde_rejects_unknown_fields() {
+        let json = r#"{"number": "0x1", "round": "0x0", "block_hash": "0x0000000000000000000000000000000000000000000000000000000000000000", "signature": "0x", "bitmap": "0x", "bit_map": "0x"}"#;
+        assert!(serde_json::from_str::<Proof>(json).is_err());
+    }
+
     #[test]
     fn test_vote_codec() {
         let vote = Vote::random();
@@ -845,6 +2034,7 @@ mod tests {
         let decoded: overlord::types::Vote = rlp::decode(&raw).unwrap();
         assert_eq!(vote.height, decoded.height);
         assert_eq!(vote.round, decoded.round);
+        assert_eq!(u8::from(vote.vote_type), u8::from(decoded.vote_type));
         assert_eq!(vote.block_hash, decoded.block_hash);
     }
 }
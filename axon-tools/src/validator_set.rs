@@ -0,0 +1,391 @@
+//! A validator list prepared once for verifying many proofs. Decompressing
+//! and group checking the BLS public keys is the bulk of the work
+//! [`crate::verify_proof`] repeats on every call, a [`ValidatorSet`] does it
+//! when it is built and keeps the parsed keys.
+//!
+//! A proof bitmap has one bit per validator, in an order both sides must agree
+//! on. Axon orders validators by their secp256k1 `pub_key`, the [`Ord`] of
+//! [`ValidatorExtend`] and [`ValidatorOrder::PubKey`]. Sets in another order
+//! are built with [`ValidatorSet::sorted_by_address`] or
+//! [`ValidatorSet::sorted_by_bls_key`], or checked as given with
+//! [`ValidatorSet::from_sorted`].
+
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+use blst::min_pk::PublicKey;
+use ethereum_types::H256;
+
+use crate::crypto::{BlsMinPk, ConsensusCrypto};
+use crate::metrics::{timed, Metrics, NoopMetrics};
+use crate::proof::{check_public_key_length, check_votes};
+use crate::schedule::QuorumRule;
+use crate::types::{find_duplicate_validator, AxonBlock, Metadata, Proof, ValidatorExtend};
+use crate::{check_proposal_hash, Error, VerifyReport};
+
+/// The order of the validators a bitmap is read against.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ValidatorOrder {
+    /// By `pub_key` bytes, the order Axon builds bitmaps in.
+    #[default]
+    PubKey,
+    /// By address.
+    Address,
+    /// By `bls_pub_key` bytes.
+    BlsKey,
+}
+
+impl ValidatorOrder {
+    pub fn compare(self, a: &ValidatorExtend, b: &ValidatorExtend) -> Ordering {
+        match self {
+            ValidatorOrder::PubKey => a.cmp(b),
+            ValidatorOrder::Address => a.address.cmp(&b.address),
+            ValidatorOrder::BlsKey => a.bls_pub_key[..].cmp(&b.bls_pub_key[..]),
+        }
+    }
+
+    pub fn sort(self, validators: &mut [ValidatorExtend]) {
+        validators.sort_by(|a, b| self.compare(a, b));
+    }
+
+    /// Check that `validators` are strictly increasing in this order,
+    /// reporting the index of the first one that is not.
+    pub fn check_sorted(self, validators: &[ValidatorExtend]) -> Result<(), Error> {
+        let misplaced = validators
+            .windows(2)
+            .position(|w| self.compare(&w[0], &w[1]) != Ordering::Less);
+        match misplaced {
+            Some(i) => Err(Error::ValidatorsOutOfOrder { index: i + 1 }),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ValidatorSet {
+    // Sorted into bitmap order.
+    validators: Vec<ValidatorExtend>,
+    // The parsed `bls_pub_key` of each validator, in the same order.
+    keys:       Vec<PublicKey>,
+    order:      ValidatorOrder,
+}
+
+impl ValidatorSet {
+    /// Sort `validators` into Axon's bitmap order and parse their BLS public
+    /// keys, failing on a duplicate validator or an invalid key.
+    pub fn new(validators: Vec<ValidatorExtend>) -> Result<Self, Error> {
+        ValidatorSet::sorted_by(validators, ValidatorOrder::PubKey)
+    }
+
+    /// Same as [`ValidatorSet::new`] for bitmaps built against the
+    /// validators sorted by address.
+    pub fn sorted_by_address(validators: Vec<ValidatorExtend>) -> Result<Self, Error> {
+        ValidatorSet::sorted_by(validators, ValidatorOrder::Address)
+    }
+
+    /// Same as [`ValidatorSet::new`] for bitmaps built against the
+    /// validators sorted by BLS public key.
+    pub fn sorted_by_bls_key(validators: Vec<ValidatorExtend>) -> Result<Self, Error> {
+        ValidatorSet::sorted_by(validators, ValidatorOrder::BlsKey)
+    }
+
+    /// Take `validators` as already sorted in `order`, failing with
+    /// `Error::ValidatorsOutOfOrder` rather than sorting them if they are not.
+    pub fn from_sorted(
+        validators: Vec<ValidatorExtend>,
+        order: ValidatorOrder,
+    ) -> Result<Self, Error> {
+        order.check_sorted(&validators)?;
+        ValidatorSet::sorted_by(validators, order)
+    }
+
+    fn sorted_by(
+        mut validators: Vec<ValidatorExtend>,
+        order: ValidatorOrder,
+    ) -> Result<Self, Error> {
+        order.sort(&mut validators);
+        if let Some(address) = find_duplicate_validator(&validators) {
+            return Err(Error::DuplicateValidator(address));
+        }
+
+        let keys = validators
+            .iter()
+            .enumerate()
+            .map(|(index, v)| {
+                check_public_key_length(index, &v.bls_pub_key)?;
+                PublicKey::key_validate(&v.bls_pub_key).map_err(|error| {
+                    Error::InvalidValidatorKey {
+                        address: v.address,
+                        error,
+                    }
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(ValidatorSet {
+            validators,
+            keys,
+            order,
+        })
+    }
+
+    /// The set of `metadata`'s verifier list.
+    pub fn from_metadata(metadata: &Metadata) -> Result<Self, Error> {
+        ValidatorSet::new(metadata.to_validators())
+    }
+
+    /// The validators in bitmap order.
+    pub fn validators(&self) -> &[ValidatorExtend] {
+        &self.validators
+    }
+
+    pub fn order(&self) -> ValidatorOrder {
+        self.order
+    }
+
+    pub fn len(&self) -> usize {
+        self.validators.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.validators.is_empty()
+    }
+
+    /// Same as [`crate::verify_proof`] against this set, without parsing any
+    /// public key again.
+    pub fn verify_proof(
+        &self,
+        block: &AxonBlock,
+        previous_state_root: H256,
+        proof: &Proof,
+    ) -> Result<VerifyReport, Error> {
+        self.verify_proof_with(
+            block,
+            previous_state_root,
+            proof,
+            &NoopMetrics,
+            &BlsMinPk,
+            QuorumRule::SignerCount,
+        )
+    }
+
+    /// Same as [`ValidatorSet::verify_proof`], reporting to `metrics`, with
+    /// the signatures checked by `crypto` and the quorum counted by `quorum`.
+    /// The parsed keys are used when `crypto` has a
+    /// [`ConsensusCrypto::bls_dst`], the raw ones are passed to it otherwise.
+    pub fn verify_proof_with(
+        &self,
+        block: &AxonBlock,
+        previous_state_root: H256,
+        proof: &Proof,
+        metrics: &dyn Metrics,
+        crypto: &dyn ConsensusCrypto,
+        quorum: QuorumRule,
+    ) -> Result<VerifyReport, Error> {
+        timed(metrics, || {
+            check_proposal_hash(block, previous_state_root, proof)?;
+            check_votes(
+                proof,
+                &self.validators,
+                self.order,
+                Some(&self.keys),
+                metrics,
+                crypto,
+                quorum,
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::Cell;
+
+    use blst::min_pk::{AggregateSignature, SecretKey};
+    use bytes::Bytes;
+    use ethereum_types::H160;
+    use rlp::Encodable;
+
+    use super::*;
+    use crate::consts::BLS_DST;
+    use crate::proof::{precommit_vote, vote_hash};
+    use crate::types::tests::mock_header;
+    use crate::types::{Hex, Proposal};
+    use crate::{keccak_256, verify_proof};
+
+    // Four validators in `pub_key` order, the keys they sign with and a block
+    // for them to sign.
+    fn fixture() -> (Vec<SecretKey>, Vec<ValidatorExtend>, AxonBlock) {
+        let sks = (1..=4u8)
+            .map(|i| SecretKey::key_gen(&[i; 32], &[]).unwrap())
+            .collect::<Vec<_>>();
+        let validators = sks
+            .iter()
+            .zip(1..=4u8)
+            .map(|(sk, i)| ValidatorExtend {
+                bls_pub_key: Hex::encode(sk.sk_to_pk().to_bytes()),
+                pub_key:     Hex::encode([i; 33]),
+                address:     H160(rand::random()),
+                vote_weight: 1,
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+        let block = AxonBlock {
+            header:    mock_header(),
+            tx_hashes: vec![],
+        };
+        (sks, validators, block)
+    }
+
+    // A proof of `block` signed by `sks[i]` for each `i` of `signers`.
+    fn sign(sks: &[SecretKey], block: &AxonBlock, signers: &[usize], bitmap: u8) -> Proof {
+        let mut proof = Proof {
+            number:     block.header.number,
+            block_hash: H256(keccak_256(
+                &Proposal::from_block(block, H256::zero()).unwrap().rlp_bytes(),
+            )),
+            bitmap:     Bytes::from(vec![bitmap]),
+            ..Default::default()
+        };
+        let message = vote_hash(&precommit_vote(&proof));
+        let sigs = signers
+            .iter()
+            .map(|i| sks[*i].sign(&message, BLS_DST.as_bytes(), &[]))
+            .collect::<Vec<_>>();
+        let sigs = sigs.iter().collect::<Vec<_>>();
+        let sig = AggregateSignature::aggregate(&sigs, true).unwrap().to_signature();
+        proof.signature = Bytes::from(sig.to_bytes().to_vec());
+        proof
+    }
+
+    #[test]
+    fn test_verify_proof() {
+        let (sks, validators, block) = fixture();
+        let mut proof = sign(&sks, &block, &[0, 2, 3], 0b1011_0000);
+
+        let set = ValidatorSet::new(validators.clone()).unwrap();
+        let report = set.verify_proof(&block, H256::zero(), &proof).unwrap();
+        let expect = verify_proof(&block, H256::zero(), &validators, &proof).unwrap();
+        assert_eq!(report, expect);
+        assert_eq!(report.signer_indices, vec![0, 2, 3]);
+
+        proof.bitmap = Bytes::from_static(&[0b1101_0000]);
+        assert!(set.verify_proof(&block, H256::zero(), &proof).is_err());
+        proof.bitmap = Bytes::from_static(&[0b1000_0000]);
+        assert!(matches!(
+            set.verify_proof(&block, H256::zero(), &proof),
+            Err(Error::NotEnoughSignatures)
+        ));
+
+        let mut reversed = validators.clone();
+        reversed.reverse();
+        assert!(matches!(
+            ValidatorSet::from_sorted(reversed, ValidatorOrder::PubKey),
+            Err(Error::ValidatorsOutOfOrder { index: 1 })
+        ));
+        let set = ValidatorSet::from_sorted(validators.clone(), ValidatorOrder::PubKey).unwrap();
+        assert_eq!(set.validators(), &validators[..]);
+
+        let mut invalid = validators;
+        invalid[1].bls_pub_key = Hex::encode([0u8; 48]);
+        assert!(matches!(
+            ValidatorSet::new(invalid),
+            Err(Error::InvalidValidatorKey { .. })
+        ));
+    }
+
+    #[test]
+    fn test_verify_proof_with() {
+        #[derive(Default)]
+        struct RecordingMetrics {
+            quorum:             Cell<Option<(usize, usize)>>,
+            signature_failures: Cell<usize>,
+        }
+
+        impl Metrics for RecordingMetrics {
+            fn inc_signature_failure(&self) {
+                self.signature_failures.set(self.signature_failures.get() + 1);
+            }
+
+            fn observe_quorum(&self, signers: usize, validators: usize) {
+                self.quorum.set(Some((signers, validators)));
+            }
+        }
+
+        // Accepts any signature, without a BLS tag to use the parsed keys
+        // with.
+        struct AcceptAll(Cell<usize>);
+
+        impl ConsensusCrypto for AcceptAll {
+            fn verify_aggregate(&self, _: &[u8], _: &[u8], pubkeys: &[&[u8]]) -> Result<(), Error> {
+                self.0.set(pubkeys.len());
+                Ok(())
+            }
+        }
+
+        let (sks, mut validators, block) = fixture();
+        validators[3].vote_weight = 10;
+        let set = ValidatorSet::new(validators).unwrap();
+        let verify = |proof: &Proof, metrics: &dyn Metrics, crypto: &dyn ConsensusCrypto, quorum| {
+            set.verify_proof_with(&block, H256::zero(), proof, metrics, crypto, quorum)
+        };
+
+        // Three signers of four, but only 3 of 13 vote weight.
+        let proof = sign(&sks, &block, &[0, 1, 2], 0b1110_0000);
+        let metrics = RecordingMetrics::default();
+        assert!(verify(&proof, &metrics, &BlsMinPk, QuorumRule::SignerCount).is_ok());
+        assert_eq!(metrics.quorum.get(), Some((3, 4)));
+        assert!(matches!(
+            verify(&proof, &metrics, &BlsMinPk, QuorumRule::VoteWeight),
+            Err(Error::NotEnoughVoteWeight {
+                signed:   3,
+                required: 9,
+            })
+        ));
+
+        let proof = sign(&sks, &block, &[0, 3], 0b1001_0000);
+        let report = verify(&proof, &metrics, &BlsMinPk, QuorumRule::VoteWeight).unwrap();
+        assert_eq!(report.quorum.signed_weight, 11);
+
+        // Signed by the first two validators, claimed by the last two.
+        let mut forged = sign(&sks, &block, &[0, 1, 2], 0b1110_0000);
+        forged.bitmap = Bytes::from_static(&[0b0111_0000]);
+        assert!(verify(&forged, &metrics, &BlsMinPk, QuorumRule::SignerCount).is_err());
+        assert_eq!(metrics.signature_failures.get(), 1);
+
+        let crypto = AcceptAll(Cell::new(0));
+        let report = verify(&forged, &metrics, &crypto, QuorumRule::SignerCount).unwrap();
+        assert_eq!(crypto.0.get(), 3);
+        assert_eq!(report.signer_indices, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_validator_order() {
+        let validators = [(3u8, 1u8, 2u8), (1, 2, 3), (2, 3, 1)]
+            .map(|(pub_key, address, bls_pub_key)| ValidatorExtend {
+                pub_key:     Hex::encode([pub_key; 33]),
+                address:     H160::repeat_byte(address),
+                bls_pub_key: Hex::encode([bls_pub_key; 48]),
+                ..Default::default()
+            })
+            .to_vec();
+        let first_byte = |order: ValidatorOrder| {
+            let mut sorted = validators.clone();
+            order.sort(&mut sorted);
+            assert!(order.check_sorted(&sorted).is_ok());
+            sorted.iter().map(|v| v.pub_key[0]).collect::<Vec<_>>()
+        };
+        assert_eq!(first_byte(ValidatorOrder::PubKey), vec![1, 2, 3]);
+        assert_eq!(first_byte(ValidatorOrder::Address), vec![3, 1, 2]);
+        assert_eq!(first_byte(ValidatorOrder::BlsKey), vec![2, 3, 1]);
+
+        assert!(matches!(
+            ValidatorOrder::PubKey.check_sorted(&validators),
+            Err(Error::ValidatorsOutOfOrder { index: 1 })
+        ));
+        let mut duplicated = validators.clone();
+        duplicated[1] = duplicated[0].clone();
+        ValidatorOrder::Address.sort(&mut duplicated);
+        assert!(ValidatorOrder::Address.check_sorted(&duplicated).is_err());
+    }
+}
@@ -1,4 +1,4 @@
-use axon_tools::types::{AxonBlock, Metadata, Proof, ValidatorExtend, H256};
+use axon_tools::types::{AxonBlock, Metadata, Proof, H256};
 use serde::de::DeserializeOwned;
 
 fn read_json<T: DeserializeOwned>(path: &str) -> T {
@@ -8,30 +8,26 @@ fn read_json<T: DeserializeOwned>(path: &str) -> T {
 
 fn main() {
     let block: AxonBlock = read_json("examples/block.json");
-    println!("block: {:?}", block);
     let proof: Proof = read_json("examples/proof.json");
     let metadata: Metadata = read_json("examples/metadata.json");
-    let mut validators = metadata
-        .verifier_list
-        .iter()
-        .map(|v| ValidatorExtend {
-            bls_pub_key:    v.bls_pub_key.clone(),
-            pub_key:        v.pub_key.clone(),
-            address:        v.address,
-            propose_weight: v.propose_weight,
-            vote_weight:    v.vote_weight,
-        })
-        .collect::<Vec<_>>();
+    let validators = metadata.to_validators();
 
     let previous_state_root =
         hex::decode("9fc948be2cfb0127e979dc9c7e6d2f4a2890b54e0e81fd69c687303e6b25ddde").unwrap();
+    let previous_state_root = H256::from_slice(&previous_state_root);
 
-    let result = axon_tools::verify_proof(
-        block,
-        H256::from_slice(&previous_state_root),
-        &mut validators,
-        proof,
-    );
+    let trace =
+        axon_tools::trace_verification(&block, previous_state_root, &validators, &proof)
+            .unwrap();
+    let result = axon_tools::verify_proof(&block, previous_state_root, &validators, &proof);
+    match &result {
+        Ok(report) => println!(
+            "signed by {:?}, {:.1}% of the vote weight",
+            report.signer_indices,
+            report.quorum_ratio() * 100.0
+        ),
+        Err(_) => println!("{:#?}", trace),
+    }
     println!("verify_proof: {:?}", result);
 
     assert!(result.is_ok());
@@ -0,0 +1,119 @@
+use axon_tools::hex::hex_encode;
+use axon_tools::keccak_256;
+use axon_tools::types::{AxonBlock, AxonHeader, Hex, Proposal, H256};
+use rlp::Encodable;
+
+/// `block-hash <FILE> [--prev-state-root <HEX>]`
+///
+/// `FILE` holds a block or a header as JSON. The proposal hash, the one a
+/// proof commits to, needs a block and the state root of its parent.
+pub fn run(args: &[String]) -> Result<String, String> {
+    let (path, prev_state_root) = match args {
+        [path] => (path, None),
+        [path, flag, root] if flag == "--prev-state-root" => (path, Some(root)),
+        [flag, root, path] if flag == "--prev-state-root" => (path, Some(root)),
+        _ => {
+            return Err("Usage: axon-tools block-hash <FILE> [--prev-state-root <HEX>]".into())
+        }
+    };
+
+    let json = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+    let (header, block) = match serde_json::from_str::<AxonBlock>(&json) {
+        Ok(block) => (block.header.clone(), Some(block)),
+        Err(_) => {
+            let header = serde_json::from_str::<AxonHeader>(&json)
+                .map_err(|e| format!("{} is neither a block nor a header: {}", path, e))?;
+            (header, None)
+        }
+    };
+
+    let mut output = Vec::new();
+    push_hash(&mut output, "header", &header.rlp_bytes());
+
+    match (block, prev_state_root) {
+        (Some(block), Some(root)) => {
+            let root = root
+                .parse::<Hex>()
+                .map_err(|e| format!("Invalid previous state root: {}", e))?;
+            if root.len() != 32 {
+                return Err(format!("Previous state root is {} bytes, expect 32", root.len()));
+            }
+            let proposal = Proposal::from_block(&block, H256::from_slice(&root))
+                .map_err(|e| format!("The block has no proposal: {}", e))?;
+            push_hash(&mut output, "proposal", &proposal.rlp_bytes());
+        }
+        (None, Some(_)) => return Err("The proposal hash needs a block, not a header".into()),
+        (_, None) => output.push("pass --prev-state-root for the proposal hash".into()),
+    }
+
+    Ok(output.join("\n"))
+}
+
+fn push_hash(output: &mut Vec<String>, name: &str, rlp: &[u8]) {
+    output.push(format!("{} rlp:  0x{}", name, hex_encode(rlp)));
+    output.push(format!("{} hash: 0x{}", name, hex_encode(keccak_256(rlp))));
+}
+
+#[cfg(test)]
+mod tests {
+    use axon_tools::types::U256;
+
+    use super::*;
+
+    const EXAMPLE_BLOCK: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/examples/block.json");
+    const PREVIOUS_STATE_ROOT: &str =
+        "0x9fc948be2cfb0127e979dc9c7e6d2f4a2890b54e0e81fd69c687303e6b25ddde";
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn write_temp(name: &str, json: String) -> String {
+        let path = std::env::temp_dir().join(format!("axon-tools-{}-{}", std::process::id(), name));
+        std::fs::write(&path, json).unwrap();
+        path.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_block_hash() {
+        // The proof of the example block commits to this hash.
+        let output =
+            run(&args(&[EXAMPLE_BLOCK, "--prev-state-root", PREVIOUS_STATE_ROOT])).unwrap();
+        assert!(output.contains(
+            "proposal hash: 0x6e48fa2d2002453abd9b51e4ac0342abb1f027b8272d6e27ce79a630b3f26673"
+        ));
+        let reordered = run(&args(&["--prev-state-root", PREVIOUS_STATE_ROOT, EXAMPLE_BLOCK]));
+        assert_eq!(reordered.unwrap(), output);
+
+        let block: AxonBlock =
+            serde_json::from_str(&std::fs::read_to_string(EXAMPLE_BLOCK).unwrap()).unwrap();
+        let header_hash = format!("header hash: 0x{}", hex_encode(block.header.hash().0));
+        assert!(output.contains(&header_hash));
+
+        let header = write_temp("header.json", serde_json::to_string(&block.header).unwrap());
+        let output = run(&args(&[&header])).unwrap();
+        assert!(output.contains(&header_hash));
+        assert!(output.ends_with("pass --prev-state-root for the proposal hash"));
+        assert!(run(&args(&[&header, "--prev-state-root", PREVIOUS_STATE_ROOT])).is_err());
+        std::fs::remove_file(header).unwrap();
+    }
+
+    #[test]
+    fn test_block_hash_errors() {
+        assert!(run(&args(&[])).unwrap_err().starts_with("Usage"));
+        assert!(run(&args(&[EXAMPLE_BLOCK, "--state-root", "0x00"]))
+            .unwrap_err()
+            .starts_with("Usage"));
+        assert!(run(&args(&["/nonexistent.json"])).is_err());
+        let res = run(&args(&[EXAMPLE_BLOCK, "--prev-state-root", "0x0102"]));
+        assert_eq!(res.unwrap_err(), "Previous state root is 2 bytes, expect 32");
+
+        let mut block: AxonBlock =
+            serde_json::from_str(&std::fs::read_to_string(EXAMPLE_BLOCK).unwrap()).unwrap();
+        block.header.gas_limit = U256::MAX;
+        let path = write_temp("wide-gas-limit.json", serde_json::to_string(&block).unwrap());
+        let res = run(&args(&[&path, "--prev-state-root", PREVIOUS_STATE_ROOT]));
+        assert!(res.unwrap_err().starts_with("The block has no proposal"));
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,93 @@
+use axon_tools::hex::{hex_decode_with, HexParseOptions};
+use axon_tools::types::{AxonBlock, AxonHeader, Metadata, Proof};
+use rlp::Decodable;
+use serde::Serialize;
+
+/// `decode --type <TYPE> <HEX>`
+pub fn run(args: &[String]) -> Result<String, String> {
+    let (ty, hex) = match args {
+        [flag, ty, hex] if flag == "--type" => (ty.as_str(), hex.as_str()),
+        [hex, flag, ty] if flag == "--type" => (ty.as_str(), hex.as_str()),
+        _ => return Err("Usage: axon-tools decode --type <TYPE> <HEX>".to_string()),
+    };
+
+    let options = HexParseOptions {
+        allow_missing_prefix: true,
+        ..Default::default()
+    };
+    let bytes = hex_decode_with(hex, options).map_err(|e| e.to_string())?;
+
+    match ty {
+        "block" => decode_to_json::<AxonBlock>(&bytes),
+        "header" => decode_to_json::<AxonHeader>(&bytes),
+        "proof" => decode_to_json::<Proof>(&bytes),
+        "metadata" => decode_to_json::<Metadata>(&bytes),
+        // The proposal encoding leaves out `base_fee_per_gas` and `chain_id`,
+        // so it cannot be decoded back into a `Proposal`.
+        "proposal" => {
+            Err("Proposal RLP omits the base fee and chain id, it cannot be decoded".into())
+        }
+        _ => Err(format!("Unknown type {}, expect block, header, proof or metadata", ty)),
+    }
+}
+
+fn decode_to_json<T: Decodable + Serialize>(bytes: &[u8]) -> Result<String, String> {
+    let value = rlp::decode::<T>(bytes).map_err(|e| format!("RLP decode error: {}", e))?;
+    serde_json::to_string_pretty(&value).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use axon_tools::hex::hex_encode;
+    use axon_tools::types::H256;
+    use bytes::Bytes;
+    use serde::de::DeserializeOwned;
+
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn read_json<T: DeserializeOwned>(name: &str) -> T {
+        let path = format!("{}/examples/{}", env!("CARGO_MANIFEST_DIR"), name);
+        serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_decode_roundtrip() {
+        let proof = Proof {
+            number:     0xe7c3,
+            round:      1,
+            block_hash: H256::repeat_byte(1),
+            signature:  Bytes::from(vec![2; 96]),
+            bitmap:     Bytes::from_static(&[0x70]),
+        };
+        let hex = format!("0x{}", hex_encode(rlp::encode(&proof)));
+        let json = run(&args(&["--type", "proof", &hex])).unwrap();
+        assert_eq!(serde_json::from_str::<Proof>(&json).unwrap(), proof);
+
+        let block: AxonBlock = read_json("block.json");
+        let hex = hex_encode(rlp::encode(&block));
+        let json = run(&args(&[&hex, "--type", "block"])).unwrap();
+        assert_eq!(serde_json::from_str::<AxonBlock>(&json).unwrap(), block);
+        let hex = hex_encode(rlp::encode(&block.header));
+        let json = run(&args(&["--type", "header", &hex])).unwrap();
+        assert_eq!(serde_json::from_str::<AxonHeader>(&json).unwrap(), block.header);
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        assert!(run(&args(&[])).unwrap_err().starts_with("Usage"));
+        assert!(run(&args(&["--type", "proof"])).unwrap_err().starts_with("Usage"));
+        assert!(run(&args(&["proof", "--kind", "0x"])).unwrap_err().starts_with("Usage"));
+        assert!(run(&args(&["--type", "proof", "0xzz"])).is_err());
+        assert!(run(&args(&["--type", "proof", "0xc0"]))
+            .unwrap_err()
+            .starts_with("RLP decode error"));
+        assert!(run(&args(&["--type", "proposal", "0xc0"])).is_err());
+        assert!(run(&args(&["--type", "vote", "0xc0"]))
+            .unwrap_err()
+            .starts_with("Unknown type vote"));
+    }
+}
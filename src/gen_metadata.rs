@@ -0,0 +1,182 @@
+use axon_tools::types::{ConsensusConfig, Hex, MetadataBuilder, ValidatorExtend, H160};
+use ethers_core::k256::ecdsa::VerifyingKey;
+use ethers_core::utils::public_key_to_address;
+use serde::Deserialize;
+
+const USAGE: &str =
+    "Usage: axon-tools gen-metadata --start <N> --end <N> [--epoch <N>] <KEY_FILE>...";
+
+/// The public half of a validator key pair, as in the key files Axon's
+/// keygen writes. The address is optional and derived from `public_key` when
+/// missing.
+#[derive(Deserialize)]
+struct KeyPair {
+    bls_public_key: Hex,
+    public_key:     Hex,
+    address:        Option<H160>,
+}
+
+/// A key file holds either a single key pair or a list of them.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum KeyFile {
+    Many { keypairs: Vec<KeyPair> },
+    One(KeyPair),
+}
+
+/// `gen-metadata --start <N> --end <N> [--epoch <N>] <KEY_FILE>...`
+pub fn run(args: &[String]) -> Result<String, String> {
+    let mut builder = MetadataBuilder::new().consensus_config(consensus_config());
+    let (mut start, mut end, mut files) = (None, None, Vec::new());
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--start" => start = Some(parse_number(args.next())?),
+            "--end" => end = Some(parse_number(args.next())?),
+            "--epoch" => builder = builder.epoch(parse_number(args.next())?),
+            _ => files.push(arg),
+        }
+    }
+    let (Some(start), Some(end)) = (start, end) else {
+        return Err(USAGE.into());
+    };
+    if files.is_empty() || start > end {
+        return Err(USAGE.into());
+    }
+    builder = builder.version(start, end);
+
+    for path in files {
+        let json = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        let key_file = serde_json::from_str(&json).map_err(|e| format!("{}: {}", path, e))?;
+        let keypairs = match key_file {
+            KeyFile::Many { keypairs } => keypairs,
+            KeyFile::One(keypair) => vec![keypair],
+        };
+        for keypair in keypairs {
+            let validator = to_validator(keypair).map_err(|e| format!("{}: {}", path, e))?;
+            builder = builder.validator(validator);
+        }
+    }
+
+    serde_json::to_string_pretty(&builder.build()).map_err(|e| e.to_string())
+}
+
+/// The consensus parameters Axon ships with.
+pub(crate) fn consensus_config() -> ConsensusConfig {
+    ConsensusConfig {
+        gas_limit:       30_000_000,
+        interval:        3000,
+        propose_ratio:   15,
+        prevote_ratio:   10,
+        precommit_ratio: 10,
+        brake_ratio:     10,
+        tx_num_limit:    20_000,
+        max_tx_size:     1_024_000,
+    }
+}
+
+fn to_validator(keypair: KeyPair) -> Result<ValidatorExtend, String> {
+    let key = VerifyingKey::from_sec1_bytes(&keypair.public_key)
+        .map_err(|e| format!("Invalid secp256k1 public key: {}", e))?;
+    let address = public_key_to_address(&key);
+    if let Some(expect) = keypair.address {
+        if expect != address {
+            return Err(format!(
+                "Address {:?} does not match public key, which gives {:?}",
+                expect, address
+            ));
+        }
+    }
+
+    Ok(ValidatorExtend {
+        bls_pub_key:    keypair.bls_public_key,
+        pub_key:        keypair.public_key,
+        address,
+        propose_weight: 1,
+        vote_weight:    1,
+    })
+}
+
+fn parse_number(arg: Option<&String>) -> Result<u64, String> {
+    let arg = arg.ok_or(USAGE)?;
+    arg.parse().map_err(|e| format!("Invalid number {}: {}", arg, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use axon_tools::types::{Metadata, MetadataVersion};
+    use serde_json::json;
+
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn example_validators() -> Vec<ValidatorExtend> {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/examples/metadata.json");
+        let metadata: Metadata =
+            serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+        metadata.verifier_list
+    }
+
+    fn write_key_file(name: &str, key_file: serde_json::Value) -> String {
+        let path = std::env::temp_dir().join(format!("axon-tools-{}-{}", std::process::id(), name));
+        std::fs::write(&path, key_file.to_string()).unwrap();
+        path.to_str().unwrap().to_string()
+    }
+
+    fn keypair(v: &ValidatorExtend) -> serde_json::Value {
+        json!({
+            "bls_public_key": v.bls_pub_key,
+            "public_key": v.pub_key,
+            "address": v.address,
+        })
+    }
+
+    #[test]
+    fn test_gen_metadata() {
+        let validators = example_validators();
+        let many = json!({ "keypairs": validators[1..].iter().map(keypair).collect::<Vec<_>>() });
+        let many = write_key_file("many.json", many);
+        // The address is derived when missing.
+        let mut one = keypair(&validators[0]);
+        one.as_object_mut().unwrap().remove("address");
+        let one = write_key_file("one.json", one);
+
+        let output = run(&args(&["--start", "1", &many, "--end", "100", "--epoch", "2", &one]));
+        let metadata: Metadata = serde_json::from_str(&output.unwrap()).unwrap();
+        assert_eq!(metadata.version, MetadataVersion::new(1, 100));
+        assert_eq!(metadata.epoch, 2);
+        assert_eq!(metadata.consensus_config, consensus_config());
+        let mut expect = validators;
+        expect.sort();
+        let addresses =
+            |list: &[ValidatorExtend]| list.iter().map(|v| v.address).collect::<Vec<_>>();
+        assert_eq!(addresses(&metadata.verifier_list), addresses(&expect));
+
+        std::fs::remove_file(many).unwrap();
+        std::fs::remove_file(one).unwrap();
+    }
+
+    #[test]
+    fn test_gen_metadata_errors() {
+        let validators = example_validators();
+        let mut mismatched = keypair(&validators[0]);
+        mismatched["address"] = json!(validators[1].address);
+        let path = write_key_file("mismatched.json", mismatched);
+
+        let res = run(&args(&["--start", "1", "--end", "100", &path]));
+        assert!(res.unwrap_err().contains("does not match public key"));
+        assert_eq!(run(&args(&["--start", "1", &path])).unwrap_err(), USAGE);
+        assert_eq!(run(&args(&["--start", "1", "--end", "100"])).unwrap_err(), USAGE);
+        assert_eq!(run(&args(&["--start", "9", "--end", "1", &path])).unwrap_err(), USAGE);
+        assert_eq!(run(&args(&["--start"])).unwrap_err(), USAGE);
+        let res = run(&args(&["--start", "one", "--end", "100", &path]));
+        assert!(res.unwrap_err().starts_with("Invalid number one"));
+        assert!(run(&args(&["--start", "1", "--end", "100", "/nonexistent.json"])).is_err());
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,145 @@
+use std::path::Path;
+
+use axon_tools::consts::{BLS_DST, MIN_BASE_FEE_PER_GAS};
+use axon_tools::keccak_256;
+use axon_tools::types::{
+    AxonBlock, AxonHeader, BlockVersion, Bloom, Hex, MetadataBuilder, Proof, Proposal,
+    ValidatorExtend, Vote, VoteType, H160, H256, U256,
+};
+use blst::min_pk::{AggregateSignature, SecretKey};
+use bytes::Bytes;
+use ethers_core::k256::ecdsa::SigningKey;
+use ethers_core::utils::secret_key_to_address;
+use rlp::Encodable;
+
+const DEFAULT_VALIDATOR_COUNT: usize = 4;
+const PREVIOUS_STATE_ROOT: H256 = H256([0x11; 32]);
+
+/// `gen-vectors <DIR> [--validators <N>]`
+///
+/// Write `block.json`, `proof.json` and `metadata.json` for block 1 signed by
+/// every validator. Validator `i` derives both its BLS and secp256k1 keys
+/// from the 32 byte seed `[i + 1; 32]`, so the output is the same on every
+/// run.
+pub fn run(args: &[String]) -> Result<String, String> {
+    let (dir, count) = match args {
+        [dir] => (dir, DEFAULT_VALIDATOR_COUNT),
+        [dir, flag, n] | [flag, n, dir] if flag == "--validators" => {
+            let n = n.parse().map_err(|e| format!("Invalid validator count: {}", e))?;
+            (dir, n)
+        }
+        _ => return Err("Usage: axon-tools gen-vectors <DIR> [--validators <N>]".into()),
+    };
+    if count == 0 || count > 255 {
+        return Err(format!("Validator count {} is not in 1..=255", count));
+    }
+
+    let keys = (1..=count as u8)
+        .map(validator_keys)
+        .collect::<Result<Vec<_>, _>>()?;
+    let metadata = keys
+        .iter()
+        .fold(MetadataBuilder::new(), |builder, (_, v)| builder.validator(v.clone()))
+        .version(1, 100)
+        .consensus_config(crate::gen_metadata::consensus_config())
+        .build();
+    let block = block(metadata.verifier_list[0].address);
+
+    let proposal = Proposal::from_block(&block, PREVIOUS_STATE_ROOT).map_err(|e| e.to_string())?;
+    let block_hash = keccak_256(&proposal.rlp_bytes());
+    let vote = Vote {
+        height:     block.header.number,
+        round:      0,
+        vote_type:  VoteType::Precommit,
+        block_hash: Bytes::from(block_hash.to_vec()),
+    };
+    let vote_hash = keccak_256(&rlp::encode(&vote));
+    let signatures = keys
+        .iter()
+        .map(|(sk, _)| sk.sign(&vote_hash, BLS_DST.as_bytes(), &[]))
+        .collect::<Vec<_>>();
+    let signatures = signatures.iter().collect::<Vec<_>>();
+    let signature = AggregateSignature::aggregate(&signatures, true)
+        .map_err(|e| format!("BLS aggregate error: {:?}", e))?
+        .to_signature();
+
+    let mut bitmap = vec![0u8; (count + 7) / 8];
+    for i in 0..count {
+        bitmap[i / 8] |= 0x80 >> (i % 8);
+    }
+    let proof = Proof {
+        number:     block.header.number,
+        round:      0,
+        block_hash: H256(block_hash),
+        signature:  Bytes::from(signature.compress().to_vec()),
+        bitmap:     Bytes::from(bitmap),
+    };
+
+    axon_tools::verify_proof(
+        &block,
+        PREVIOUS_STATE_ROOT,
+        &metadata.to_validators(),
+        &proof,
+    )
+    .map_err(|e| format!("Generated proof does not verify: {}", e))?;
+
+    let dir = Path::new(dir);
+    std::fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    write_json(&dir.join("block.json"), &block)?;
+    write_json(&dir.join("proof.json"), &proof)?;
+    write_json(&dir.join("metadata.json"), &metadata)?;
+
+    Ok(format!(
+        "wrote {} validators to {}\nprevious state root: {:?}",
+        count,
+        dir.display(),
+        PREVIOUS_STATE_ROOT
+    ))
+}
+
+fn validator_keys(seed: u8) -> Result<(SecretKey, ValidatorExtend), String> {
+    let ikm = [seed; 32];
+    let bls_key =
+        SecretKey::key_gen(&ikm, &[]).map_err(|e| format!("BLS key gen error: {:?}", e))?;
+    let secp_key =
+        SigningKey::from_slice(&ikm).map_err(|e| format!("secp256k1 key error: {}", e))?;
+
+    let validator = ValidatorExtend {
+        bls_pub_key:    Hex::encode(bls_key.sk_to_pk().compress()),
+        pub_key:        Hex::encode(secp_key.verifying_key().to_sec1_bytes()),
+        address:        secret_key_to_address(&secp_key),
+        propose_weight: 1,
+        vote_weight:    1,
+    };
+    Ok((bls_key, validator))
+}
+
+fn block(proposer: H160) -> AxonBlock {
+    AxonBlock {
+        header:    AxonHeader {
+            version:                  BlockVersion::V0,
+            prev_hash:                H256([0x01; 32]),
+            proposer,
+            state_root:               H256([0x02; 32]),
+            transactions_root:        H256([0x03; 32]),
+            signed_txs_hash:          H256([0x04; 32]),
+            receipts_root:            H256([0x05; 32]),
+            log_bloom:                Bloom::default(),
+            timestamp:                1_700_000_000,
+            number:                   1,
+            gas_used:                 U256::zero(),
+            gas_limit:                U256::from(30_000_000),
+            extra_data:               Vec::new(),
+            base_fee_per_gas:         U256::from(MIN_BASE_FEE_PER_GAS),
+            proof:                    Proof::default(),
+            call_system_script_count: 0,
+            chain_id:                 2022,
+        },
+        tx_hashes: Vec::new(),
+    }
+}
+
+fn write_json<T: serde::Serialize>(path: &Path, value: &T) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    std::fs::write(path, json).map_err(|e| format!("{}: {}", path.display(), e))
+}
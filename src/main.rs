@@ -0,0 +1,49 @@
+//! Command line tools for inspecting Axon data with the `axon-tools` codecs.
+
+mod block_hash;
+mod decode;
+mod gen_metadata;
+#[cfg(feature = "signing")]
+mod gen_vectors;
+
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Usage: axon-tools <COMMAND>
+
+Commands:
+  decode --type <block|header|proof|metadata> <HEX>
+      RLP-decode captured bytes and print them as JSON
+  block-hash <FILE> [--prev-state-root <HEX>]
+      Print the RLP and keccak of a block or header, and of its proposal
+  gen-metadata --start <N> --end <N> [--epoch <N>] <KEY_FILE>...
+      Build metadata JSON from validator key files
+  gen-vectors <DIR> [--validators <N>]
+      Write a signed block, proof and metadata, needs the `signing` feature";
+
+fn main() -> ExitCode {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let res = match args.first().map(String::as_str) {
+        Some("decode") => decode::run(&args[1..]),
+        Some("block-hash") => block_hash::run(&args[1..]),
+        Some("gen-metadata") => gen_metadata::run(&args[1..]),
+        #[cfg(feature = "signing")]
+        Some("gen-vectors") => gen_vectors::run(&args[1..]),
+        Some("-h" | "--help") => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        _ => Err(USAGE.to_string()),
+    };
+
+    match res {
+        Ok(output) => {
+            println!("{}", output);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}